@@ -0,0 +1,265 @@
+//! The module responsible for the astronomical values derived from the forecast: day length and moon phase.
+//!
+//! The values are calculated locally, no requests to the servers are made.
+//! The `--astro` view displays them with the sun and moon times of the provider.
+
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
+use serde::Serialize;
+
+use crate::{format::Format, geo::Geo, out::Out, table::Table};
+
+/// Length of the synodic month, days.
+const SYNODIC_MONTH: f64 = 29.530_588_853;
+
+/// Sun and moon times of the place for the `--astro` view.
+///
+/// * `provider: &'static str` - Provider name.
+/// * `address: String` - Request address.
+/// * `geo: Geo` - Geo position.
+/// * `date: DateTime<Local>` - Requested date.
+/// * `offset: Option<FixedOffset>` - UTC offset of the location.
+/// * `sunrise: Option<DateTime<Local>>` - Sunrise time.
+/// * `sunset: Option<DateTime<Local>>` - Sunset time.
+/// * `moonrise: Option<DateTime<Local>>` - Moonrise time.
+/// * `moonset: Option<DateTime<Local>>` - Moonset time.
+/// * `moon_phase: String` - Moon phase name.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AstroReport {
+    /// Provider name.
+    pub provider: &'static str,
+    /// Request address.
+    pub address: String,
+    /// Geo position.
+    pub geo: Geo,
+    /// Requested date.
+    pub date: DateTime<Local>,
+    /// UTC offset of the location, the system timezone is used when it's unknown.
+    #[serde(skip)]
+    pub offset: Option<FixedOffset>,
+    /// Sunrise time, `None` during the polar day or night.
+    pub sunrise: Option<DateTime<Local>>,
+    /// Sunset time, `None` during the polar day or night.
+    pub sunset: Option<DateTime<Local>>,
+    /// Moonrise time, few providers supply it.
+    pub moonrise: Option<DateTime<Local>>,
+    /// Moonset time, few providers supply it.
+    pub moonset: Option<DateTime<Local>>,
+    /// Moon phase name of the provider or calculated for the date.
+    pub moon_phase: String,
+}
+
+impl AstroReport {
+    /// Create the report without the sun and moon times, the moon phase is calculated for the date.
+    ///
+    /// * `provider: &'static str` - Provider name.
+    /// * `address: String` - Request address.
+    /// * `geo: Geo` - Geo position.
+    /// * `date: DateTime<Local>` - Requested date.
+    /// * `offset: Option<FixedOffset>` - UTC offset of the location.
+    pub fn new(
+        provider: &'static str,
+        address: String,
+        geo: Geo,
+        date: DateTime<Local>,
+        offset: Option<FixedOffset>,
+    ) -> AstroReport {
+        AstroReport {
+            provider,
+            address,
+            geo,
+            date,
+            offset,
+            sunrise: None,
+            sunset: None,
+            moonrise: None,
+            moonset: None,
+            moon_phase: Astro::moon_phase(&date).to_owned(),
+        }
+    }
+
+    /// Rows of the view: the sun and moon times, the day length and the moon phase.
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        let time = |dt: Option<DateTime<Local>>| {
+            dt.map_or("None".to_owned(), |dt| Format::time(&dt, self.offset))
+        };
+        vec![
+            ("Sunrise time", time(self.sunrise)),
+            ("Sunset time", time(self.sunset)),
+            (
+                "Day length",
+                Astro::day_length(self.sunrise, self.sunset).unwrap_or_else(|| "None".to_owned()),
+            ),
+            ("Moonrise time", time(self.moonrise)),
+            ("Moonset time", time(self.moonset)),
+            ("Moon phase", self.moon_phase.clone()),
+        ]
+    }
+
+    /// Display the view.
+    ///
+    /// * `duration: i64` - Request time, ms.
+    pub fn show(&self, duration: i64) {
+        Out::line(&format!(
+            "Astronomy for '{}'. {} server. Request time {} ms.",
+            self.date.format("%Y-%m-%d"),
+            self.provider,
+            duration
+        ));
+        Out::line(&format!("Request address: {}.", self.address));
+        Out::line(&format!(
+            "Found address: {} ({},{}).",
+            self.geo.address, self.geo.lat, self.geo.lon
+        ));
+        Table::show(None, &self.rows());
+    }
+}
+
+/// Unit struct for astronomical calculations
+pub struct Astro;
+
+impl Astro {
+    /// Day length formatted as `HhMm`, e.g. `14h18m`.
+    ///
+    /// * `sunrise: Option<DateTime<Local>>` - Sunrise time.
+    /// * `sunset: Option<DateTime<Local>>` - Sunset time.
+    ///
+    /// Return
+    ///
+    /// `Option<String>` - `None` if any time is unknown or the sunset is before the sunrise.
+    pub fn day_length(
+        sunrise: Option<DateTime<Local>>,
+        sunset: Option<DateTime<Local>>,
+    ) -> Option<String> {
+        let minutes = (sunset? - sunrise?).num_minutes();
+        if minutes < 0 {
+            return None;
+        }
+        Some(format!("{}h{:02}m", minutes / 60, minutes % 60))
+    }
+
+    /// Age of the moon, days since the new moon.
+    ///
+    /// * `date: &DateTime<Local>` - Date of the forecast.
+    pub fn moon_age(date: &DateTime<Local>) -> f64 {
+        // The new moon of 2000-01-06 18:14 UTC
+        let new_moon = Utc.with_ymd_and_hms(2000, 1, 6, 18, 14, 0).unwrap();
+        let days = date.signed_duration_since(new_moon).num_seconds() as f64 / 86_400.0;
+        days.rem_euclid(SYNODIC_MONTH)
+    }
+
+    /// Moon phase name for the date.
+    ///
+    /// * `date: &DateTime<Local>` - Date of the forecast.
+    pub fn moon_phase(date: &DateTime<Local>) -> &'static str {
+        Astro::phase_name(Astro::moon_age(date) / SYNODIC_MONTH)
+    }
+
+    /// Moon phase name for the part of the lunar cycle.
+    ///
+    /// * `fraction: f64` - Part of the cycle: 0 and 1 - new moon, 0.25 - first quarter, 0.5 - full moon, 0.75 - last quarter.
+    pub fn phase_name(fraction: f64) -> &'static str {
+        // Eight phases, each takes 1/8 of the month, the main phases are centered on their moment
+        let phases = [
+            "New Moon",
+            "Waxing Crescent",
+            "First Quarter",
+            "Waxing Gibbous",
+            "Full Moon",
+            "Waning Gibbous",
+            "Last Quarter",
+            "Waning Crescent",
+        ];
+        let index = (fraction.rem_euclid(1.0) * 8.0 + 0.5).floor() as usize % 8;
+        phases[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, Local, TimeZone, Utc};
+
+    use super::{Astro, AstroReport};
+    use crate::geo::Geo;
+
+    #[test]
+    fn test_day_length() {
+        let sunrise = Local.with_ymd_and_hms(2023, 4, 24, 5, 46, 52).unwrap();
+        let sunset = Local.with_ymd_and_hms(2023, 4, 24, 20, 5, 4).unwrap();
+        assert_eq!(
+            Astro::day_length(Some(sunrise), Some(sunset)),
+            Some("14h18m".to_owned())
+        );
+        assert_eq!(Astro::day_length(Some(sunset), Some(sunrise)), None);
+        assert_eq!(Astro::day_length(None, Some(sunset)), None);
+    }
+
+    #[test]
+    fn test_moon_phase() {
+        let date = |y, m, d, h| {
+            Utc.with_ymd_and_hms(y, m, d, h, 0, 0)
+                .unwrap()
+                .with_timezone(&Local)
+        };
+        // Full moon of 2023-05-05 17:34 UTC
+        assert_eq!(Astro::moon_phase(&date(2023, 5, 5, 17)), "Full Moon");
+        // New moon of 2023-05-19 15:53 UTC
+        assert_eq!(Astro::moon_phase(&date(2023, 5, 19, 16)), "New Moon");
+        // First quarter of 2023-05-27 15:22 UTC
+        assert_eq!(Astro::moon_phase(&date(2023, 5, 27, 15)), "First Quarter");
+        assert_eq!(Astro::moon_phase(&date(2023, 5, 23, 12)), "Waxing Crescent");
+    }
+
+    #[test]
+    fn test_report_rows() {
+        let date = Utc
+            .with_ymd_and_hms(2023, 4, 24, 9, 0, 0)
+            .unwrap()
+            .with_timezone(&Local);
+        let offset = FixedOffset::east_opt(3 * 3600);
+        let time = |h, m| {
+            offset
+                .unwrap()
+                .with_ymd_and_hms(2023, 4, 24, h, m, 0)
+                .unwrap()
+                .with_timezone(&Local)
+        };
+        let geo = Geo {
+            lat: "50.45".to_owned(),
+            lon: "30.52".to_owned(),
+            address: "Kyiv, Ukraine".to_owned(),
+            importance: None,
+        };
+        let report = AstroReport {
+            sunrise: Some(time(5, 46)),
+            sunset: Some(time(20, 5)),
+            ..AstroReport::new("WeatherAPI", "Kyiv".to_owned(), geo, date, offset)
+        };
+        assert_eq!(
+            report.rows(),
+            vec![
+                (
+                    "Sunrise time",
+                    "2023-04-24 05:46:00 (+03:00, location time)".to_owned()
+                ),
+                (
+                    "Sunset time",
+                    "2023-04-24 20:05:00 (+03:00, location time)".to_owned()
+                ),
+                ("Day length", "14h19m".to_owned()),
+                ("Moonrise time", "None".to_owned()),
+                ("Moonset time", "None".to_owned()),
+                ("Moon phase", "Waxing Crescent".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_phase_name() {
+        assert_eq!(Astro::phase_name(0.0), "New Moon");
+        assert_eq!(Astro::phase_name(0.25), "First Quarter");
+        assert_eq!(Astro::phase_name(0.5), "Full Moon");
+        assert_eq!(Astro::phase_name(0.75), "Last Quarter");
+        assert_eq!(Astro::phase_name(0.97), "New Moon");
+        assert_eq!(Astro::phase_name(1.0), "New Moon");
+    }
+}
@@ -0,0 +1,228 @@
+//! The module responsible for the daily budget of the requests to the weather providers.
+//!
+//! The requests are counted per provider in the file `requests.json` while `--max-requests` is set,
+//! the counters are reset at local midnight.
+//!
+//! While the forecast is displayed the answers are kept in memory, so the other dates of the same
+//! place are served from the forecast already received instead of a new request.
+
+use std::{cell::RefCell, collections::HashMap, fs};
+
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    http::{Http, HttpClient, HttpError},
+    init::Options,
+    log::Log,
+};
+
+/// File with the request counters of the day.
+const BUDGET_FILE: &str = "requests.json";
+
+thread_local! {
+    /// Answers received by the provider name and url, `None` - the answers are not kept.
+    static ANSWERS: RefCell<Option<HashMap<String, Value>>> = const { RefCell::new(None) };
+}
+
+/// Request counters of the day.
+///
+/// * `date: NaiveDate` - Local date of the counters.
+/// * `counts: HashMap<String, u32>` - Number of the requests by the provider name.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+struct Counters {
+    /// Local date of the counters.
+    date: NaiveDate,
+    /// Number of the requests by the provider name.
+    counts: HashMap<String, u32>,
+}
+
+/// Unit struct for the request budget
+pub struct Budget;
+
+impl Budget {
+    /// Load json from the provider server within the daily budget of `--max-requests`.
+    ///
+    /// The request is counted before it's sent, the servers count the failed requests too.
+    /// The url is reported for `--explain`, the duration for `-v`.
+    /// The answer kept by `Budget::remember` is returned without a request.
+    ///
+    /// * `provider: &str` - Weather provider name.
+    /// * `client: &dyn HttpClient` - Source of the server answers.
+    /// * `url: &str` - Requested url.
+    /// * `headers: &[(&str, &str)]` - Additional request headers.
+    pub fn get_json(
+        provider: &str,
+        client: &dyn HttpClient,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<Value, HttpError> {
+        let key = format!("{} {}", provider, url);
+        let kept = ANSWERS.with(|answers| {
+            answers
+                .borrow()
+                .as_ref()
+                .and_then(|answers| answers.get(&key).cloned())
+        });
+        if let Some(json) = kept {
+            if Log::is_verbose(1) {
+                Log::info(&format!("The {} answer is reused.", provider));
+            }
+            return Ok(json);
+        }
+        let options = Options::get();
+        if let (Some(limit), false) = (options.max_requests, options.dry_run) {
+            let left = Budget::take(BUDGET_FILE, provider, limit, Local::now().date_naive())?;
+            Log::info(&format!(
+                "{} of {} requests to {} remain today.",
+                left, limit, provider
+            ));
+        }
+        if options.explain && !options.dry_run {
+            Log::info(&format!(
+                "Explain: {} endpoint {}",
+                provider,
+                Http::redact_url(url)
+            ));
+        }
+        let json = Log::timed(1, &format!("{} request", provider), || {
+            client.get_json(url, headers)
+        })?;
+        ANSWERS.with(|answers| {
+            if let Some(answers) = answers.borrow_mut().as_mut() {
+                answers.insert(key, json.clone());
+            }
+        });
+        Ok(json)
+    }
+
+    /// Start keeping the answers, so the repeated requests are served from memory.
+    pub fn remember() {
+        ANSWERS.with(|answers| *answers.borrow_mut() = Some(HashMap::new()));
+    }
+
+    /// Stop keeping the answers and drop them, the next request is sent to the server.
+    pub fn forget() {
+        ANSWERS.with(|answers| *answers.borrow_mut() = None);
+    }
+
+    /// Count the request if the budget isn't exhausted.
+    ///
+    /// * `file: &str` - Counters file.
+    /// * `provider: &str` - Weather provider name.
+    /// * `limit: u32` - Number of the requests allowed per day.
+    /// * `today: NaiveDate` - Local date, the counters of another date are reset.
+    ///
+    /// Return
+    ///
+    /// `Result<u32, HttpError>` - Number of the requests left after this one.
+    fn take(file: &str, provider: &str, limit: u32, today: NaiveDate) -> Result<u32, HttpError> {
+        let mut counters = Budget::load(file);
+        if counters.date != today {
+            counters = Counters {
+                date: today,
+                counts: HashMap::new(),
+            };
+        }
+        let count = counters.counts.entry(provider.to_owned()).or_insert(0);
+        if *count >= limit {
+            return Err(HttpError::Budget {
+                provider: provider.to_owned(),
+                limit,
+            });
+        }
+        *count += 1;
+        let left = limit - *count;
+        Budget::save(file, &counters);
+        Ok(left)
+    }
+
+    /// Load the counters, a missing or broken file is an empty budget.
+    ///
+    /// * `file: &str` - Counters file.
+    fn load(file: &str) -> Counters {
+        match fs::read_to_string(file) {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_default(),
+            Err(_) => Counters::default(),
+        }
+    }
+
+    /// Save the counters.
+    ///
+    /// * `file: &str` - Counters file.
+    /// * `counters: &Counters` - Request counters of the day.
+    fn save(file: &str, counters: &Counters) {
+        let data = match serde_json::to_string(counters) {
+            Ok(data) => data,
+            Err(e) => {
+                Log::info(&format!(
+                    "Unable to save the request counters. Error: {}.",
+                    e
+                ));
+                return;
+            }
+        };
+        if let Err(e) = fs::write(file, data) {
+            Log::info(&format!(
+                "Unable to save the request counters. Error: {}.",
+                e
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::MockHttp;
+
+    #[test]
+    fn test_take() {
+        let file = std::env::temp_dir().join(format!("weather_budget_{}.json", std::process::id()));
+        let file = file.to_str().unwrap();
+        let _ = fs::remove_file(file);
+        let today = NaiveDate::from_ymd_opt(2023, 5, 11).unwrap();
+        assert_eq!(Budget::take(file, "OpenWeather", 2, today), Ok(1));
+        assert_eq!(Budget::take(file, "OpenWeather", 2, today), Ok(0));
+        assert_eq!(
+            Budget::take(file, "OpenWeather", 2, today),
+            Err(HttpError::Budget {
+                provider: "OpenWeather".to_owned(),
+                limit: 2
+            })
+        );
+        // Other providers have their own counters
+        assert_eq!(Budget::take(file, "WeatherAPI", 2, today), Ok(1));
+        // The counters are reset on the next day
+        let tomorrow = today.succ_opt().unwrap();
+        assert_eq!(Budget::take(file, "OpenWeather", 2, tomorrow), Ok(1));
+        assert_eq!(Budget::load(file).counts.get("WeatherAPI"), None);
+        let _ = fs::remove_file(file);
+    }
+
+    #[test]
+    fn test_remember() {
+        let url = "https://example.com/forecast?lat=50.45&lon=30.52";
+        let server = MockHttp::new(&[("forecast", r#"{"list": [1, 2, 3]}"#)]);
+        let down = MockHttp::new(&[]);
+        assert!(Budget::get_json("Test", &server, url, &[]).is_ok());
+        assert!(Budget::get_json("Test", &down, url, &[]).is_err());
+        Budget::remember();
+        assert!(Budget::get_json("Test", &down, url, &[]).is_err());
+        assert_eq!(
+            Budget::get_json("Test", &server, url, &[]),
+            Ok(serde_json::json!({"list": [1, 2, 3]}))
+        );
+        // The kept answer is served without a request
+        assert_eq!(
+            Budget::get_json("Test", &down, url, &[]),
+            Ok(serde_json::json!({"list": [1, 2, 3]}))
+        );
+        // Other providers and places are requested
+        assert!(Budget::get_json("Other", &down, url, &[]).is_err());
+        assert!(Budget::get_json("Test", &down, &url.replace("50.45", "48.92"), &[]).is_err());
+        Budget::forget();
+        assert!(Budget::get_json("Test", &down, url, &[]).is_err());
+    }
+}
@@ -0,0 +1,111 @@
+//! The module responsible for the comfort values derived from the forecast: wind chill and heat index.
+//!
+//! The values are calculated locally by the formulas of the US National Weather Service
+//! for the providers that don't return them.
+
+use crate::format::Format;
+
+/// The highest temperature of the wind chill formula, °C.
+const WIND_CHILL_MAX_TEMP: f32 = 10.0;
+
+/// The lowest wind speed of the wind chill formula, km/h.
+const WIND_CHILL_MIN_SPEED: f32 = 4.8;
+
+/// The lowest temperature of the heat index formula, °C.
+const HEAT_INDEX_MIN_TEMP: f32 = 27.0;
+
+/// Unit struct for comfort calculations
+pub struct Comfort;
+
+impl Comfort {
+    /// Wind chill temperature, the metric form of the NWS formula.
+    ///
+    /// * `temp_c: f32` - Temperature, °C.
+    /// * `wind_kph: f32` - Wind speed, km/h.
+    ///
+    /// Return
+    ///
+    /// `Option<f32>` - Wind chill, °C. `None` above 10°C or below 4.8 km/h, where the formula isn't valid.
+    pub fn wind_chill(temp_c: f32, wind_kph: f32) -> Option<f32> {
+        if temp_c > WIND_CHILL_MAX_TEMP || wind_kph < WIND_CHILL_MIN_SPEED {
+            return None;
+        }
+        let speed = wind_kph.powf(0.16);
+        Some(13.12 + 0.6215 * temp_c - 11.37 * speed + 0.3965 * temp_c * speed)
+    }
+
+    /// Heat index, the Rothfusz regression with the NWS adjustments.
+    ///
+    /// * `temp_c: f32` - Temperature, °C.
+    /// * `humidity: f32` - Relative humidity, %.
+    ///
+    /// Return
+    ///
+    /// `Option<f32>` - Heat index, °C. `None` below 27°C, where the formula isn't valid.
+    pub fn heat_index(temp_c: f32, humidity: f32) -> Option<f32> {
+        if temp_c < HEAT_INDEX_MIN_TEMP {
+            return None;
+        }
+        // The regression is defined in °F
+        let t = temp_c * 9.0 / 5.0 + 32.0;
+        let rh = humidity;
+        let mut index = -42.379 + 2.049_015_2 * t + 10.143_331 * rh
+            - 0.224_755_4 * t * rh
+            - 0.006_837_83 * t * t
+            - 0.054_817_17 * rh * rh
+            + 0.001_228_74 * t * t * rh
+            + 0.000_852_82 * t * rh * rh
+            - 0.000_001_99 * t * t * rh * rh;
+        if rh < 13.0 && (80.0..=112.0).contains(&t) {
+            index -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+        } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+            index += (rh - 85.0) / 10.0 * ((87.0 - t) / 5.0);
+        }
+        Some((index - 32.0) * 5.0 / 9.0)
+    }
+
+    /// Displayed comfort value in the selected units.
+    ///
+    /// * `value: Option<Option<f32>>` - The calculated value, °C.
+    ///   * `Option::None` - The inputs are unknown, displayed as `None`.
+    ///   * `Some(None)` - The formula isn't valid for the weather, displayed as `n/a`.
+    pub fn text(value: Option<Option<f32>>) -> String {
+        match value {
+            Some(Some(value)) => Format::temp(value),
+            Some(None) => "n/a".to_owned(),
+            None => "None".to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Comfort;
+
+    #[test]
+    fn test_wind_chill() {
+        // -10°C and 30 km/h is about -19.5°C
+        let chill = Comfort::wind_chill(-10.0, 30.0).unwrap();
+        assert!((chill + 19.5).abs() < 0.1);
+        assert!(Comfort::wind_chill(10.0, 20.0).unwrap() < 10.0);
+        assert_eq!(Comfort::wind_chill(10.5, 20.0), None);
+        assert_eq!(Comfort::wind_chill(0.0, 3.0), None);
+    }
+
+    #[test]
+    fn test_heat_index() {
+        // 90°F and 60% is 100°F by the NWS table
+        let index = Comfort::heat_index(32.22, 60.0).unwrap();
+        assert!((index - 37.8).abs() < 0.3);
+        // The dry air adjustment: 95°F and 10% is 89.4°F
+        let index = Comfort::heat_index(35.0, 10.0).unwrap();
+        assert!((index - 31.9).abs() < 0.1);
+        assert_eq!(Comfort::heat_index(26.9, 90.0), None);
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(Comfort::text(Some(None)), "n/a");
+        assert_eq!(Comfort::text(None), "None");
+    }
+}
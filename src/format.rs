@@ -0,0 +1,512 @@
+//! The module responsible for formatting of the displayed values.
+//!
+//! The providers keep metric values, the conversion to the selected units is done here.
+
+use chrono::{DateTime, FixedOffset, Local};
+
+use crate::{
+    init::{Options, PressureUnit, Units},
+    wind::WindDeg,
+    work::WeatherReport,
+};
+
+/// Unit struct for value formatting
+pub struct Format;
+
+impl Format {
+    /// Format a measured value with the precision set by `--precision`.
+    ///
+    /// * `value: f32` - Measured value.
+    pub fn num(value: f32) -> String {
+        let options = Options::get();
+        Format::localize(
+            &Format::with_precision(value, options.precision()),
+            options.locale().as_deref(),
+        )
+    }
+
+    /// Apply the decimal separator and digit grouping of the language to a formatted number.
+    ///
+    /// The digits are grouped from 10 000, so the pressure is `1013,5`, not `1.013,5`.
+    ///
+    /// * `number: &str` - Number with the `.` separator, e.g. `-12345.6`.
+    /// * `lang: Option<&str>` - Language code of `--locale`, `None` - the number isn't changed.
+    pub fn localize(number: &str, lang: Option<&str>) -> String {
+        let (decimal, group) = match lang {
+            Some(lang) => Format::separators(lang),
+            None => return number.to_owned(),
+        };
+        let (sign, number) = match number.strip_prefix('-') {
+            Some(number) => ("-", number),
+            None => ("", number),
+        };
+        let (int, fract) = match number.split_once('.') {
+            Some((int, fract)) => (int, Some(fract)),
+            None => (number, None),
+        };
+        let mut text = sign.to_owned();
+        let len = int.chars().count();
+        for (index, c) in int.chars().enumerate() {
+            if len > 4 && index > 0 && (len - index) % 3 == 0 {
+                text.push(group);
+            }
+            text.push(c);
+        }
+        if let Some(fract) = fract {
+            text.push(decimal);
+            text += fract;
+        }
+        text
+    }
+
+    /// Decimal separator and digit grouping of the language, e.g. `,` and `.` for `de`.
+    ///
+    /// * `lang: &str` - Language code, e.g. `uk`.
+    pub fn separators(lang: &str) -> (char, char) {
+        match lang.split(['-', '_']).next().unwrap_or_default() {
+            "de" | "es" | "it" | "nl" | "pt" | "tr" | "da" | "id" | "el" | "ro" | "hr" | "sl"
+            | "sr" => (',', '.'),
+            "fr" | "ru" | "uk" | "be" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "bg"
+            | "hu" | "lt" | "lv" | "et" | "kk" => (',', '\u{a0}'),
+            _ => ('.', ','),
+        }
+    }
+
+    /// Format a measured value with the given number of decimal places.
+    ///
+    /// The values rounded to zero are displayed without the sign, e.g. `-0.04` is `0.0`, not `-0.0`.
+    ///
+    /// * `value: f32` - Measured value.
+    /// * `precision: u8` - Number of decimal places.
+    pub fn with_precision(value: f32, precision: u8) -> String {
+        let text = format!("{:.*}", precision as usize, value);
+        match text.strip_prefix('-') {
+            Some(abs) if abs.chars().all(|c| c == '0' || c == '.') => abs.to_owned(),
+            _ => text,
+        }
+    }
+
+    /// Format a temperature in the selected units.
+    ///
+    /// * `celsius: f32` - Temperature, °C.
+    pub fn temp(celsius: f32) -> String {
+        Format::in_units(|units| Format::temp_in(celsius, units))
+    }
+
+    /// Format a speed in the selected units.
+    ///
+    /// * `kmh: f32` - Speed, km/h.
+    pub fn speed(kmh: f32) -> String {
+        Format::in_units(|units| Format::speed_in(kmh, units))
+    }
+
+    /// Format a speed in meter/sec in the selected units.
+    ///
+    /// * `ms: f32` - Speed, meter/sec.
+    pub fn speed_ms(ms: f32) -> String {
+        Format::in_units(|units| match units {
+            Units::Metric => (ms, "meter/sec"),
+            Units::Imperial => Format::speed_in(ms * 3.6, units),
+        })
+    }
+
+    /// Format a value in the selected units, `--both-units` adds the other unit system,
+    /// e.g. `18 °C / 64.4 °F`.
+    ///
+    /// * `value_in: impl Fn(Units) -> (f32, &'static str)` - The value and its unit name in the units.
+    fn in_units(value_in: impl Fn(Units) -> (f32, &'static str)) -> String {
+        let options = Options::get();
+        Format::in_units_both(value_in, options.units(), options.both_units)
+    }
+
+    /// Format a value in the units and optionally in the other unit system.
+    ///
+    /// * `value_in: impl Fn(Units) -> (f32, &'static str)` - The value and its unit name in the units.
+    /// * `units: Units` - The main units.
+    /// * `both: bool` - Add the other unit system after ` / `.
+    fn in_units_both(
+        value_in: impl Fn(Units) -> (f32, &'static str),
+        units: Units,
+        both: bool,
+    ) -> String {
+        let (value, unit) = value_in(units);
+        let text = format!("{} {}", Format::num(value), unit);
+        if !both {
+            return text;
+        }
+        let other = match units {
+            Units::Metric => Units::Imperial,
+            Units::Imperial => Units::Metric,
+        };
+        let (value, unit) = value_in(other);
+        format!("{} / {} {}", text, Format::num(value), unit)
+    }
+
+    /// Format a precipitation amount in the selected units.
+    ///
+    /// * `mm: f32` - Precipitation, mm.
+    pub fn precip(mm: f32) -> String {
+        let (value, unit) = Format::precip_in(mm, Options::get().units());
+        format!("{} {}", Format::num(value), unit)
+    }
+
+    /// Format an atmospheric pressure in the unit of `--pressure-unit`,
+    /// `--both-units` adds the pressure in inHg, or in hPa if it's displayed in inHg.
+    ///
+    /// * `hpa: f32` - Pressure, hPa or mbar.
+    /// * `unit: &str` - Unit name of the provider, `hPa` or `mbar`, for the default unit.
+    pub fn pressure(hpa: f32, unit: &str) -> String {
+        let options = Options::get();
+        let target = options.pressure_unit;
+        let text = |(value, unit): (f32, &str)| match value.fract() == 0.0 {
+            // The servers return the whole hectopascals mostly, they are displayed without decimals
+            true => format!(
+                "{} {}",
+                Format::localize(&value.to_string(), options.locale().as_deref()),
+                unit
+            ),
+            false => format!("{} {}", Format::num(value), unit),
+        };
+        let main = text(Format::pressure_in(hpa, unit, target));
+        if !options.both_units {
+            return main;
+        }
+        let other = match target {
+            Some(PressureUnit::Inhg) => PressureUnit::Hpa,
+            _ => PressureUnit::Inhg,
+        };
+        format!(
+            "{} / {}",
+            main,
+            text(Format::pressure_in(hpa, unit, Some(other)))
+        )
+    }
+
+    /// Convert an atmospheric pressure to the unit.
+    ///
+    /// * `hpa: f32` - Pressure, hPa or mbar.
+    /// * `unit: &str` - Unit name of the provider for the default unit.
+    /// * `target: Option<PressureUnit>` - The unit, `None` - the unit of the provider.
+    pub fn pressure_in(hpa: f32, unit: &str, target: Option<PressureUnit>) -> (f32, &str) {
+        match target {
+            None => (hpa, unit),
+            Some(PressureUnit::Hpa) => (hpa, "hPa"),
+            Some(PressureUnit::Mmhg) => (hpa * 0.750_06, "mmHg"),
+            Some(PressureUnit::Inhg) => (hpa * 0.029_53, "inHg"),
+        }
+    }
+
+    /// Format a time in the timezone of the location, e.g. `2023-04-24 05:46:52 (+09:00, location time)`.
+    ///
+    /// The system timezone is used when the provider doesn't return the offset of the location.
+    ///
+    /// * `dt: &DateTime<Local>` - Time.
+    /// * `offset: Option<FixedOffset>` - UTC offset of the location.
+    pub fn time(dt: &DateTime<Local>, offset: Option<FixedOffset>) -> String {
+        match offset {
+            Some(offset) => dt
+                .with_timezone(&offset)
+                .format("%Y-%m-%d %H:%M:%S (%:z, location time)")
+                .to_string(),
+            None => dt.format("%Y-%m-%d %H:%M:%S (%:z)").to_string(),
+        }
+    }
+
+    /// UTC offset of the location from the number of seconds, e.g. `10800` is `+03:00`.
+    ///
+    /// * `seconds: f64` - Offset east of UTC.
+    pub fn offset(seconds: f64) -> Option<FixedOffset> {
+        FixedOffset::east_opt(seconds.round() as i32)
+    }
+
+    /// Hide the API key except the last 4 chars, e.g. `***cdef`.
+    ///
+    /// * `key: &str` - API key.
+    pub fn secret(key: &str) -> String {
+        let chars: Vec<char> = key.chars().collect();
+        match chars.len() {
+            0..=4 => "***".to_owned(),
+            len => format!("***{}", chars[len - 4..].iter().collect::<String>()),
+        }
+    }
+
+    /// One line summary for status bars, e.g. `Kyiv: 18°C ☀ wind 12km/h NNE`.
+    ///
+    /// * `report: &WeatherReport` - Normalized weather report.
+    pub fn oneline(report: &WeatherReport) -> String {
+        let options = Options::get();
+        // Whole numbers unless the precision is set explicitly
+        Format::oneline_in(
+            report,
+            options.units(),
+            options.precision.unwrap_or(0),
+            options.locale().as_deref(),
+        )
+    }
+
+    /// One line summary in the given units, precision and language of `--locale`.
+    pub fn oneline_in(
+        report: &WeatherReport,
+        units: Units,
+        precision: u8,
+        lang: Option<&str>,
+    ) -> String {
+        let num = |value: f32| Format::localize(&Format::with_precision(value, precision), lang);
+        // Short place name: the first part of the address
+        let place = match report.address.starts_with('@') {
+            true => &report.geo.address,
+            false => &report.address,
+        };
+        let place = place.split(',').next().unwrap_or_default().trim();
+        let mut line = format!("{}:", place);
+        if let Some(temp) = report.temperature {
+            let (value, unit) = Format::temp_in(temp, units);
+            line += &format!(" {}{}", num(value), unit);
+        }
+        if let Some(condition) = &report.condition {
+            match Format::glyph(condition) {
+                Some(glyph) => line += &format!(" {}", glyph),
+                None => line += &format!(" {}", condition),
+            }
+        }
+        if let Some(speed) = report.wind_speed {
+            let (value, unit) = Format::speed_in(speed, units);
+            line += &format!(" wind {}{}", num(value), unit);
+            if report.wind_dir != WindDeg::None {
+                line += &format!(" {}", report.wind_dir.abbrev());
+            }
+        }
+        line
+    }
+
+    /// Glyph of the weather condition text, `None` if the text is not recognized.
+    ///
+    /// * `condition: &str` - Condition text from the provider.
+    pub fn glyph(condition: &str) -> Option<char> {
+        let condition = condition.to_lowercase();
+        let glyphs = [
+            (&["thunder", "storm"][..], '⛈'),
+            (&["snow", "sleet", "blizzard", "ice"][..], '❄'),
+            (&["rain", "drizzle", "shower"][..], '☔'),
+            (&["fog", "mist", "haze", "smoke"][..], '≡'),
+            (&["partly", "intermittent", "mostly sunny"][..], '⛅'),
+            (&["cloud", "overcast", "dreary"][..], '☁'),
+            (&["clear", "sun", "fair"][..], '☀'),
+        ];
+        glyphs
+            .iter()
+            .find(|(words, _)| words.iter().any(|w| condition.contains(w)))
+            .map(|(_, glyph)| *glyph)
+    }
+
+    /// Convert a temperature to the units.
+    pub fn temp_in(celsius: f32, units: Units) -> (f32, &'static str) {
+        match units {
+            Units::Metric => (celsius, "°C"),
+            Units::Imperial => (celsius * 9.0 / 5.0 + 32.0, "°F"),
+        }
+    }
+
+    /// Convert a speed to the units.
+    pub fn speed_in(kmh: f32, units: Units) -> (f32, &'static str) {
+        match units {
+            Units::Metric => (kmh, "km/h"),
+            Units::Imperial => (kmh / 1.609_344, "mph"),
+        }
+    }
+
+    /// Convert a precipitation amount to the units.
+    pub fn precip_in(mm: f32, units: Units) -> (f32, &'static str) {
+        match units {
+            Units::Metric => (mm, "mm"),
+            Units::Imperial => (mm / 25.4, "in"),
+        }
+    }
+
+    /// Line of csv, the fields with a comma, quote or line feed are quoted, e.g. `"Rain, heavy"`.
+    ///
+    /// * `fields: &[String]` - Values of the row.
+    pub fn csv(fields: &[String]) -> String {
+        fields
+            .iter()
+            .map(|field| match field.contains([',', '"', '\n', '\r']) {
+                true => format!("\"{}\"", field.replace('"', "\"\"")),
+                false => field.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone, Utc};
+
+    use super::Format;
+    use crate::{
+        geo::Geo,
+        init::{PressureUnit, Units},
+        wind::WindDeg,
+        work::WeatherReport,
+    };
+
+    #[test]
+    fn test_csv() {
+        let fields = ["Kyiv".to_owned(), String::new(), "18.5".to_owned()];
+        assert_eq!(Format::csv(&fields), "Kyiv,,18.5");
+        let fields = [
+            "Rain, heavy".to_owned(),
+            "The \"sky\"".to_owned(),
+            "a\nb".to_owned(),
+        ];
+        assert_eq!(
+            Format::csv(&fields),
+            "\"Rain, heavy\",\"The \"\"sky\"\"\",\"a\nb\""
+        );
+    }
+
+    #[test]
+    fn test_secret() {
+        assert_eq!(Format::secret("0123456789abcdef"), "***cdef");
+        assert_eq!(Format::secret("abcd"), "***");
+        assert_eq!(Format::secret(""), "***");
+    }
+
+    #[test]
+    fn test_time() {
+        let dt = Local.from_utc_datetime(
+            &Utc.with_ymd_and_hms(2023, 4, 24, 20, 46, 52)
+                .unwrap()
+                .naive_utc(),
+        );
+        assert_eq!(
+            Format::time(&dt, Format::offset(32400.0)),
+            "2023-04-25 05:46:52 (+09:00, location time)"
+        );
+        assert_eq!(
+            Format::time(&dt, Format::offset(-12600.0)),
+            "2023-04-24 17:16:52 (-03:30, location time)"
+        );
+        assert_eq!(
+            Format::time(&dt, None),
+            dt.format("%Y-%m-%d %H:%M:%S (%:z)").to_string()
+        );
+        assert_eq!(Format::offset(90000.0), None);
+    }
+
+    #[test]
+    fn test_with_precision() {
+        assert_eq!(Format::with_precision(12.345, 0), "12");
+        assert_eq!(Format::with_precision(12.345, 1), "12.3");
+        assert_eq!(Format::with_precision(12.345, 2), "12.35");
+        assert_eq!(Format::with_precision(-3.0, 3), "-3.000");
+        assert_eq!(Format::with_precision(-0.04, 1), "0.0");
+        assert_eq!(Format::with_precision(-0.0, 0), "0");
+        assert_eq!(Format::with_precision(-0.4, 0), "0");
+        assert_eq!(Format::with_precision(-0.05, 2), "-0.05");
+        assert_eq!(Format::with_precision(-0.0001, 3), "0.000");
+    }
+
+    #[test]
+    fn test_localize() {
+        assert_eq!(Format::localize("-12.5", None), "-12.5");
+        assert_eq!(Format::localize("-12.5", Some("de")), "-12,5");
+        assert_eq!(Format::localize("1013", Some("de")), "1013");
+        assert_eq!(Format::localize("12345.67", Some("de")), "12.345,67");
+        assert_eq!(Format::localize("1234567", Some("en")), "1,234,567");
+        assert_eq!(Format::localize("-10000.0", Some("fr")), "-10\u{a0}000,0");
+        assert_eq!(Format::localize("0.5", Some("pt-BR")), "0,5");
+        assert_eq!(Format::separators("ja"), ('.', ','));
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(Format::temp_in(20.0, Units::Metric), (20.0, "°C"));
+        assert_eq!(Format::temp_in(20.0, Units::Imperial), (68.0, "°F"));
+        assert_eq!(Format::temp_in(-40.0, Units::Imperial), (-40.0, "°F"));
+        let (mph, unit) = Format::speed_in(16.09344, Units::Imperial);
+        assert!((mph - 10.0).abs() < 0.001);
+        assert_eq!(unit, "mph");
+        assert_eq!(Format::precip_in(25.4, Units::Imperial), (1.0, "in"));
+    }
+
+    #[test]
+    fn test_both_units() {
+        let temp = |units| Format::temp_in(18.0, units);
+        assert_eq!(
+            Format::in_units_both(temp, Units::Metric, false),
+            Format::num(18.0) + " °C"
+        );
+        assert_eq!(
+            Format::in_units_both(temp, Units::Metric, true),
+            format!("{} °C / {} °F", Format::num(18.0), Format::num(64.4))
+        );
+        assert_eq!(
+            Format::in_units_both(temp, Units::Imperial, true),
+            format!("{} °F / {} °C", Format::num(64.4), Format::num(18.0))
+        );
+        let speed = |units| Format::speed_in(16.09344, units);
+        assert_eq!(
+            Format::in_units_both(speed, Units::Metric, true),
+            format!("{} km/h / {} mph", Format::num(16.09344), Format::num(10.0))
+        );
+    }
+
+    #[test]
+    fn test_pressure_in() {
+        assert_eq!(Format::pressure_in(1013.0, "mbar", None), (1013.0, "mbar"));
+        assert_eq!(
+            Format::pressure_in(1013.0, "mbar", Some(PressureUnit::Hpa)),
+            (1013.0, "hPa")
+        );
+        let (mmhg, unit) = Format::pressure_in(1013.0, "hPa", Some(PressureUnit::Mmhg));
+        assert!((mmhg - 759.81).abs() < 0.01);
+        assert_eq!(unit, "mmHg");
+        let (inhg, unit) = Format::pressure_in(1013.0, "hPa", Some(PressureUnit::Inhg));
+        assert!((inhg - 29.91).abs() < 0.01);
+        assert_eq!(unit, "inHg");
+    }
+
+    #[test]
+    fn test_oneline() {
+        assert_eq!(Format::glyph("Partly cloudy"), Some('⛅'));
+        assert_eq!(Format::glyph("clearsky_day"), Some('☀'));
+        assert_eq!(Format::glyph("Light rain shower"), Some('☔'));
+        assert_eq!(Format::glyph("Хмарно"), None);
+
+        let geo = Geo {
+            lat: "50.45".to_owned(),
+            lon: "30.52".to_owned(),
+            address: "Kyiv, Ukraine".to_owned(),
+            importance: None,
+        };
+        let mut report = WeatherReport {
+            condition: Some("Clear".to_owned()),
+            temperature: Some(18.2),
+            wind_speed: Some(12.4),
+            wind_deg: Some(22),
+            wind_dir: WindDeg::NorthNorthEast,
+            ..WeatherReport::new(
+                "OpenWeather",
+                "Kyiv, Ukraine".to_owned(),
+                geo,
+                Local::now(),
+                None,
+            )
+        };
+        assert_eq!(
+            Format::oneline_in(&report, Units::Metric, 0, None),
+            "Kyiv: 18°C ☀ wind 12km/h NNE"
+        );
+        report.condition = Some("Хмарно".to_owned());
+        report.wind_speed = None;
+        assert_eq!(
+            Format::oneline_in(&report, Units::Imperial, 1, None),
+            "Kyiv: 64.8°F Хмарно"
+        );
+        assert_eq!(
+            Format::oneline_in(&report, Units::Imperial, 1, Some("uk")),
+            "Kyiv: 64,8°F Хмарно"
+        );
+    }
+}
@@ -1,94 +1,650 @@
 //! The module responsible for detecting Geo data be user address via [Nominatim](https://nominatim.openstreetmap.org).
 //!
 
-use std::time::Duration;
+use std::{collections::HashMap, fs, sync::Mutex, thread, time::Duration};
 
-use reqwest::blocking::Client;
-use serde::Deserialize;
+use chrono::Utc;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
 use urlencoding::encode;
 
+use crate::{
+    http::{Http, HttpError},
+    init::Options,
+    log::Log,
+};
+
+/// File with the geocoding cache.
+const CACHE_FILE: &str = "geo.json";
+
+/// Prefix of the country hint in the address, e.g. `10115 country:Germany`.
+const COUNTRY_HINT: &str = "country:";
+
+/// Number of retries when the server is busy (status 403/429).
+const RETRIES: u32 = 2;
+
+/// Maximum delay before a retry, seconds.
+const MAX_DELAY: u64 = 5;
+
+/// Number of the candidates requested with `--strict-geo` at least.
+const STRICT_LIMIT: u32 = 5;
+
+/// Places found during the run by the language, limit and address, so the dates, the fallback
+/// providers and the `--watch` updates don't repeat the lookup.
+static FOUND: Mutex<Vec<(String, Geo)>> = Mutex::new(Vec::new());
+
 /// Determine geographic coordinates by address string.
 ///
 /// * `pub lat: String` - Latitude.
 /// * `pub lon: String` - Longitude.
 /// * `pub address: String` - Full address.
-#[derive(Debug, Deserialize, PartialEq, Clone)]
+/// * `pub importance: Option<f64>` - Relevance of the place estimated by the geocoder, 0..1.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Geo {
     /// Latitude.
+    #[serde(deserialize_with = "Geo::coord")]
     pub lat: String,
     /// Longitude.
+    #[serde(deserialize_with = "Geo::coord")]
     pub lon: String,
     /// Full address.
     #[serde(rename = "display_name")]
     pub address: String,
+    /// Relevance of the place estimated by the geocoder, 0..1. The most relevant place is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub importance: Option<f64>,
+}
+
+/// Geocoding cache entry.
+///
+/// * `time: i64` - Time of the request to the server, unix timestamp.
+/// * `geos: Vec<Geo>` - Server answer.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct GeoCache {
+    /// Time of the request to the server, unix timestamp.
+    time: i64,
+    /// Server answer.
+    geos: Vec<Geo>,
 }
 
 impl Geo {
     /// Get geographic coordinates by address string.
-    pub fn get(address: &str) -> Option<Vec<Geo>> {
+    ///
+    /// The answers of the server are cached in the file for `--geo-ttl` hours.
+    ///
+    /// * `address: &str` - Address string.
+    /// * `limit: u32` - Maximum number of the candidates, the most relevant is the first.
+    pub fn get(address: &str, limit: u32) -> Option<Vec<Geo>> {
+        let options = Options::get();
+        let ttl = options.geo_ttl;
+        // Place names depend on the language, the default limit keeps the old cache keys
+        let key = match limit {
+            1 => format!("{}:{}", options.lang(), Geo::cache_key(address)),
+            _ => format!("{}:{}:{}", options.lang(), limit, Geo::cache_key(address)),
+        };
+        if ttl > 0 {
+            if let Some(geos) = Geo::from_cache(CACHE_FILE, &key, ttl) {
+                return Some(geos);
+            }
+        }
+        let geos = Geo::request(address, limit)?;
+        if ttl > 0 && !options.dry_run {
+            Geo::to_cache(CACHE_FILE, &key, &geos);
+        }
+        Some(geos)
+    }
+
+    /// Find the place by address string or by coordinates in the format `@lat,lon`.
+    ///
+    /// For coordinates, the place name is detected by reverse geocoding.
+    /// If it fails, the coordinates are used as the place name.
+    /// The duration of the lookup is reported for `-v`, the place found once is reused for the run.
+    pub fn find(address: &str) -> Option<Geo> {
+        let options = Options::get();
+        let key = format!(
+            "{}:{}:{}",
+            options.lang(),
+            Geo::limit(),
+            Geo::cache_key(address)
+        );
+        let mut found = match FOUND.lock() {
+            Ok(found) => found,
+            Err(e) => e.into_inner(),
+        };
+        if let Some((_, geo)) = found.iter().find(|(name, _)| *name == key) {
+            return Some(geo.clone());
+        }
+        let geo = Log::timed(1, "geocoding", || Geo::locate(address))?;
+        found.push((key, geo.clone()));
+        Some(geo)
+    }
+
+    /// Find the place by address string or by coordinates, see `Geo::find`.
+    fn locate(address: &str) -> Option<Geo> {
+        if let Some((lat, lon)) = Geo::parse_coords(address) {
+            return match Geo::reverse(&lat, &lon) {
+                Some(geo) => Some(Geo {
+                    lat,
+                    lon,
+                    address: geo.address,
+                    importance: None,
+                }),
+                None => Some(Geo {
+                    address: format!("{},{}", lat, lon),
+                    lat,
+                    lon,
+                    importance: None,
+                }),
+            };
+        }
+        let geos = Geo::get(address, Geo::limit())?;
+        if geos.len() > 1 && Options::get().strict_geo {
+            Log::error(&format!(
+                "The address {} matches {} places, --strict-geo requires exactly one:",
+                address,
+                geos.len()
+            ));
+            for geo in &geos {
+                Log::error(&format!("  {} ({},{})", geo.address, geo.lat, geo.lon));
+            }
+            return None;
+        }
+        if geos.len() > 1 {
+            Log::info(&format!(
+                "Found {} places, the most relevant one is used:",
+                geos.len()
+            ));
+            for geo in &geos {
+                Log::info(&format!("  {} ({},{})", geo.address, geo.lat, geo.lon));
+            }
+        }
+        match geos.into_iter().next() {
+            Some(geo) => Some(geo),
+            None => {
+                Log::info(&format!(
+                    "Sorry, we couldn't find your address: {}",
+                    address
+                ));
+                None
+            }
+        }
+    }
+
+    /// Number of the places requested from the geocoder, `--strict-geo` needs several of them
+    /// to detect an ambiguous address.
+    fn limit() -> u32 {
+        let options = Options::get();
+        match options.strict_geo {
+            true => options.address_limit.max(STRICT_LIMIT),
+            false => options.address_limit,
+        }
+    }
+
+    /// Get the place name by geographic coordinates.
+    pub fn reverse(lat: &str, lon: &str) -> Option<Geo> {
         let url = format!(
-            "https://nominatim.openstreetmap.org/search?q={}&format=json&limit=1",
-            encode(address)
+            "{}?lat={}&lon={}&format=json",
+            Geo::reverse_url(Options::get().geocoder_url()),
+            encode(lat),
+            encode(lon)
         );
+        let json_str = Geo::load(&url)?;
+        // Parse json, the server returns `{"error": "..."}` if nothing found
+        match serde_json::from_str(&json_str) {
+            Ok(geo) => Some(geo),
+            Err(e) => {
+                Log::info(&format!(
+                    "Unable to determine the place name for coordinates {},{}. Error text: {}",
+                    lat, lon, e
+                ));
+                None
+            }
+        }
+    }
+
+    /// Reverse geocoding endpoint next to the search endpoint, e.g. `.../search` -> `.../reverse`.
+    ///
+    /// * `search: &str` - Search endpoint set by `--geocoder-url`.
+    fn reverse_url(search: &str) -> String {
+        let search = search.trim_end_matches('/');
+        match search.strip_suffix("search") {
+            Some(base) => format!("{}reverse", base),
+            None => format!("{}/reverse", search),
+        }
+    }
 
-        // Client for url query
-        let client = match Client::builder().timeout(Duration::from_secs(3)).build() {
-            Ok(c) => c,
+    /// Deserialize a coordinate from a string or a number.
+    ///
+    /// The public Nominatim returns strings, e.g. `"lat": "50.45"`, some self-hosted servers
+    /// and proxies return numbers, e.g. `"lat": 50.45`.
+    fn coord<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::String(coord) => Ok(coord),
+            Value::Number(coord) => Ok(coord.to_string()),
+            value => Err(de::Error::custom(format!(
+                "a coordinate is expected, found {}",
+                value
+            ))),
+        }
+    }
+
+    /// Parse coordinates in the format `@lat,lon`.
+    pub fn parse_coords(address: &str) -> Option<(String, String)> {
+        let (lat, lon) = address.strip_prefix('@')?.split_once(',')?;
+        let (lat, lon) = (lat.trim(), lon.trim());
+        let lat_value = lat.parse::<f64>().ok()?;
+        let lon_value = lon.parse::<f64>().ok()?;
+        if !(-90.0..=90.0).contains(&lat_value) || !(-180.0..=180.0).contains(&lon_value) {
+            return None;
+        }
+        Some((lat.to_owned(), lon.to_owned()))
+    }
+
+    /// Query parameters of the search: the structured query for a postal code or a `country:` hint,
+    /// otherwise the free text `q`.
+    ///
+    /// * `address: &str` - The address, e.g. `10115 country:Germany` or `Springfield country:US`.
+    ///
+    /// Return
+    ///
+    /// `Vec<(&'static str, String)>` - Names and values of the parameters.
+    fn query(address: &str) -> Vec<(&'static str, String)> {
+        // The hint takes the rest of the address, the country names can have several words.
+        // The ASCII lowercase keeps the byte offsets of the address, e.g. for `İzmir`
+        let lower = address.to_ascii_lowercase();
+        let (place, country) = match lower.find(COUNTRY_HINT) {
+            Some(pos) if pos == 0 || lower[..pos].ends_with(char::is_whitespace) => (
+                address[..pos].trim(),
+                Some(address[pos + COUNTRY_HINT.len()..].trim()).filter(|c| !c.is_empty()),
+            ),
+            _ => (address.trim(), None),
+        };
+        let mut params = Vec::new();
+        match (Geo::is_postal_code(place), country) {
+            (true, _) => params.push(("postalcode", place.to_owned())),
+            (false, None) => return vec![("q", place.to_owned())],
+            (false, Some(_)) if place.is_empty() => {}
+            (false, Some(_)) => params.push(("city", place.to_owned())),
+        }
+        if let Some(country) = country {
+            params.push(("country", country.to_owned()));
+        }
+        params
+    }
+
+    /// Checking whether the address is a postal code, e.g. `10115` or `SW1A 1AA`.
+    ///
+    /// Each word has a digit, so `Paris 75001` is a place name.
+    fn is_postal_code(address: &str) -> bool {
+        let words: Vec<&str> = address.split_whitespace().collect();
+        (1..=2).contains(&words.len())
+            && (3..=10).contains(&address.len())
+            && words.iter().all(|word| {
+                word.chars().any(|c| c.is_ascii_digit())
+                    && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            })
+    }
+
+    /// Request geographic coordinates from the server.
+    fn request(address: &str, limit: u32) -> Option<Vec<Geo>> {
+        let query: Vec<String> = Geo::query(address)
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, encode(value)))
+            .collect();
+        let options = Options::get();
+        let url = format!(
+            "{}?{}&format=json&limit={}",
+            options.geocoder_url(),
+            query.join("&"),
+            limit
+        );
+        let json_str = match Geo::load(&url) {
+            Some(json_str) => json_str,
+            // The plan of the requests goes on with a placeholder place
+            None if options.dry_run => {
+                return Some(vec![Geo {
+                    lat: "{lat}".to_owned(),
+                    lon: "{lon}".to_owned(),
+                    address: address.to_owned(),
+                    importance: None,
+                }])
+            }
+            None => return None,
+        };
+        // Parse json
+        let mut geos: Vec<Geo> = match serde_json::from_str(&json_str) {
+            Ok(geos) => geos,
             Err(e) => {
-                println!("The following error occurred while requesting coordinates for your address: {}", e);
+                Log::info(&format!(
+                    "Unable to recognize json response from server. Error text: {}",
+                    e
+                ));
                 return None;
             }
         };
-        let json_str = match client.get(&url).header("User-Agent", "weather bot").send() {
-            Ok(s) => {
-                let status = s.status();
-                if status != 200 {
-                    println!("Error connecting to {}. Status code: {}", &url, status);
-                    return None;
-                }
-                match s.text() {
-                    Ok(s) => s,
+        Geo::sort(&mut geos);
+        Some(geos)
+    }
+
+    /// Sort the places by importance, the most relevant is the first.
+    ///
+    /// The places without importance are moved to the end, the order of the server is kept for equal ones.
+    fn sort(geos: &mut [Geo]) {
+        geos.sort_by(|a, b| {
+            b.importance
+                .unwrap_or(f64::MIN)
+                .total_cmp(&a.importance.unwrap_or(f64::MIN))
+        });
+    }
+
+    /// Load the answer from the server.
+    fn load(url: &str) -> Option<String> {
+        let options = Options::get();
+        let lang = options.lang();
+        let headers = [
+            ("User-Agent", options.user_agent()),
+            ("Accept-Language", lang.as_str()),
+        ];
+        let mut attempt = 0;
+        loop {
+            match Http::fetch_text(url, Duration::from_secs(options.geo_timeout()), &headers) {
+                Ok(text) => match Http::check_json(url, &text) {
+                    Ok(()) => return Some(text),
                     Err(e) => {
-                        println!("Error getting answer from {}. Error text: {}", &url, e);
+                        e.log(None);
                         return None;
                     }
-                }
-            }
-            Err(e) => {
-                println!("Error connecting to {}. Error text: {}", &url, e);
-                return None;
+                },
+                Err(e) => match Geo::retry_delay(&e, attempt) {
+                    Some(delay) => {
+                        Log::info(&format!(
+                            "The geocoding server is busy, retry in {} s.",
+                            delay.as_secs()
+                        ));
+                        thread::sleep(delay);
+                        attempt += 1;
+                    }
+                    None => {
+                        e.log(None);
+                        return None;
+                    }
+                },
             }
+        }
+    }
+
+    /// Delay before the next retry, `None` - don't retry.
+    ///
+    /// Nominatim answers 403/429 under load. The delay grows exponentially (1, 2 s)
+    /// or follows `Retry-After`, but never exceeds `MAX_DELAY`.
+    ///
+    /// * `error: &HttpError` - Error of the request.
+    /// * `attempt: u32` - Number of the retries made.
+    fn retry_delay(error: &HttpError, attempt: u32) -> Option<Duration> {
+        if attempt >= RETRIES {
+            return None;
+        }
+        let backoff = 1u64 << attempt;
+        let secs = match error {
+            HttpError::RateLimit { retry_after, .. } => retry_after
+                .as_ref()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(backoff),
+            HttpError::Denied { status: 403, .. } => backoff,
+            _ => return None,
         };
-        // Parse json
-        let geo: Option<Vec<Geo>> = match serde_json::from_str(&json_str) {
-            Ok(geo) => geo,
+        Some(Duration::from_secs(secs.min(MAX_DELAY)))
+    }
+
+    /// Normalize the address for using as a cache key.
+    fn cache_key(address: &str) -> String {
+        address
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .to_lowercase()
+    }
+
+    /// Load the cache file.
+    fn load_cache(file: &str) -> HashMap<String, GeoCache> {
+        match fs::read_to_string(file) {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_default(),
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    /// Get the cached answer if it isn't older than `ttl` hours.
+    fn from_cache(file: &str, key: &str, ttl: u64) -> Option<Vec<Geo>> {
+        let cache = Geo::load_cache(file).remove(key)?;
+        let age = Utc::now().timestamp() - cache.time;
+        if age >= 0 && (age as u64) < ttl * 3600 {
+            Some(cache.geos)
+        } else {
+            None
+        }
+    }
+
+    /// Save the answer to the cache.
+    fn to_cache(file: &str, key: &str, geos: &[Geo]) {
+        let mut cache = Geo::load_cache(file);
+        cache.insert(
+            key.to_owned(),
+            GeoCache {
+                time: Utc::now().timestamp(),
+                geos: geos.to_vec(),
+            },
+        );
+        let data = match serde_json::to_string(&cache) {
+            Ok(data) => data,
             Err(e) => {
-                println!(
-                    "Unable to recognize json response from server. Error text: {}",
+                Log::info(&format!(
+                    "Unable to save the geocoding cache. Error: {}.",
                     e
-                );
-                return None;
+                ));
+                return;
             }
         };
-        geo
+        if let Err(e) = fs::write(file, data) {
+            Log::info(&format!(
+                "Unable to save the geocoding cache. Error: {}.",
+                e
+            ));
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::geo::Geo;
+    use std::time::Duration;
+
+    use crate::{geo::Geo, http::HttpError};
+
+    #[test]
+    fn test_reverse_url() {
+        assert_eq!(
+            Geo::reverse_url("https://nominatim.openstreetmap.org/search"),
+            "https://nominatim.openstreetmap.org/reverse"
+        );
+        assert_eq!(
+            Geo::reverse_url("http://localhost:8080/search/"),
+            "http://localhost:8080/reverse"
+        );
+        assert_eq!(
+            Geo::reverse_url("http://geo.local/nominatim"),
+            "http://geo.local/nominatim/reverse"
+        );
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let busy = HttpError::RateLimit {
+            url: String::new(),
+            retry_after: None,
+        };
+        assert_eq!(Geo::retry_delay(&busy, 0), Some(Duration::from_secs(1)));
+        assert_eq!(Geo::retry_delay(&busy, 1), Some(Duration::from_secs(2)));
+        assert_eq!(Geo::retry_delay(&busy, 2), None);
+        let busy = HttpError::RateLimit {
+            url: String::new(),
+            retry_after: Some("120".to_owned()),
+        };
+        assert_eq!(Geo::retry_delay(&busy, 0), Some(Duration::from_secs(5)));
+        let denied = HttpError::Denied {
+            url: String::new(),
+            status: 403,
+            body: String::new(),
+        };
+        assert_eq!(Geo::retry_delay(&denied, 0), Some(Duration::from_secs(1)));
+        let failed = HttpError::Status {
+            url: String::new(),
+            status: 500,
+            body: String::new(),
+        };
+        assert_eq!(Geo::retry_delay(&failed, 0), None);
+    }
+
+    #[test]
+    fn test_cache_key() {
+        assert_eq!(Geo::cache_key("  Kyiv,   Ukraine "), "kyiv, ukraine");
+        assert_eq!(
+            Geo::cache_key("KYIV, Ukraine"),
+            Geo::cache_key("kyiv, ukraine")
+        );
+    }
+
+    #[test]
+    fn test_cache() {
+        let file = std::env::temp_dir().join(format!("weather_geo_{}.json", std::process::id()));
+        let file = file.to_str().unwrap();
+        let geos = vec![Geo {
+            lat: "50.4500336".to_owned(),
+            lon: "30.5241361".to_owned(),
+            address: "Київ, Україна".to_owned(),
+            importance: None,
+        }];
+        assert_eq!(Geo::from_cache(file, "kyiv", 72), None);
+        Geo::to_cache(file, "kyiv", &geos);
+        assert_eq!(Geo::from_cache(file, "kyiv", 72), Some(geos));
+        assert_eq!(Geo::from_cache(file, "kyiv", 0), None);
+        assert_eq!(Geo::from_cache(file, "dnipro", 72), None);
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn test_sort() {
+        let geo = |address: &str, importance: Option<f64>| Geo {
+            lat: "0".to_owned(),
+            lon: "0".to_owned(),
+            address: address.to_owned(),
+            importance,
+        };
+        let mut geos = vec![
+            geo("Paris, Texas", Some(0.45)),
+            geo("Paris street", None),
+            geo("Paris, France", Some(0.88)),
+            geo("Paris, Kentucky", Some(0.45)),
+        ];
+        Geo::sort(&mut geos);
+        let addresses: Vec<&str> = geos.iter().map(|geo| geo.address.as_str()).collect();
+        assert_eq!(
+            addresses,
+            vec![
+                "Paris, France",
+                "Paris, Texas",
+                "Paris, Kentucky",
+                "Paris street"
+            ]
+        );
+        let geo: Geo = serde_json::from_str(
+            r#"{"lat": "48.85", "lon": "2.35", "display_name": "Paris", "importance": 0.88, "class": "boundary"}"#,
+        )
+        .unwrap();
+        assert_eq!(geo.importance, Some(0.88));
+    }
+
+    #[test]
+    fn test_coord() {
+        let kyiv = Geo {
+            lat: "50.4500336".to_owned(),
+            lon: "30.5241361".to_owned(),
+            address: "Київ, Україна".to_owned(),
+            importance: None,
+        };
+        let geos: Vec<Geo> = serde_json::from_str(
+            r#"[{"lat": "50.4500336", "lon": "30.5241361", "display_name": "Київ, Україна"}]"#,
+        )
+        .unwrap();
+        assert_eq!(geos, vec![kyiv.clone()]);
+        let geos: Vec<Geo> = serde_json::from_str(
+            r#"[{"lat": 50.4500336, "lon": 30.5241361, "display_name": "Київ, Україна"}]"#,
+        )
+        .unwrap();
+        assert_eq!(geos, vec![kyiv]);
+        let geo: Geo =
+            serde_json::from_str(r#"{"lat": -33, "lon": 151, "display_name": "Sydney"}"#).unwrap();
+        assert_eq!((geo.lat.as_str(), geo.lon.as_str()), ("-33", "151"));
+        assert!(serde_json::from_str::<Geo>(
+            r#"{"lat": null, "lon": 151, "display_name": "Sydney"}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_query() {
+        let query = |address: &str| {
+            Geo::query(address)
+                .into_iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<String>>()
+                .join("&")
+        };
+        assert_eq!(query("Kyiv, Ukraine"), "q=Kyiv, Ukraine");
+        assert_eq!(
+            query("10115 country:Germany"),
+            "postalcode=10115&country=Germany"
+        );
+        assert_eq!(query("SW1A 1AA"), "postalcode=SW1A 1AA");
+        assert_eq!(
+            query("Springfield Country:United States"),
+            "city=Springfield&country=United States"
+        );
+        assert_eq!(query("country:Ukraine"), "country=Ukraine");
+        assert_eq!(query("Paris 75001"), "q=Paris 75001");
+        assert_eq!(query("Mycountry:land"), "q=Mycountry:land");
+        assert_eq!(query("Kyiv country:"), "q=Kyiv");
+        assert_eq!(query("İzmir country:Türkiye"), "city=İzmir&country=Türkiye");
+        assert_eq!(query("İ country:Ü"), "city=İ&country=Ü");
+        assert_eq!(query("Київ COUNTRY:Україна"), "city=Київ&country=Україна");
+    }
+
+    #[test]
+    fn test_parse_coords() {
+        assert_eq!(
+            Geo::parse_coords("@50.45,30.52"),
+            Some(("50.45".to_owned(), "30.52".to_owned()))
+        );
+        assert_eq!(
+            Geo::parse_coords("@-33.87, 151.21"),
+            Some(("-33.87".to_owned(), "151.21".to_owned()))
+        );
+        assert_eq!(Geo::parse_coords("50.45,30.52"), None);
+        assert_eq!(Geo::parse_coords("@Kyiv"), None);
+        assert_eq!(Geo::parse_coords("@91,30"), None);
+        assert_eq!(Geo::parse_coords("@50,181"), None);
+    }
 
     #[test]
     fn test_geo() {
         assert_eq!(
-            Geo::get("Kyiv, Ukraine"),
+            Geo::get("Kyiv, Ukraine", 1),
             Some(vec![Geo {
                 lat: "50.4500336".to_owned(),
                 lon: "30.5241361".to_owned(),
-                address: "Київ, Україна".to_owned()
+                address: "Київ, Україна".to_owned(),
+                importance: None,
             }])
         );
-        assert_eq!(Geo::get("Дніпро, Україна"), Some(vec![ Geo { lat: "48.4680221".to_owned(), lon: "35.0417711".to_owned(), address: "Дніпро, Дніпровська міська громада, Дніпровський район, Дніпропетровська область, 49000, Україна".to_owned() }]));
-        assert_eq!(Geo::get("unknown galaxy"), Some(vec![]));
+        assert_eq!(Geo::get("Дніпро, Україна", 1), Some(vec![ Geo { lat: "48.4680221".to_owned(), lon: "35.0417711".to_owned(), address: "Дніпро, Дніпровська міська громада, Дніпровський район, Дніпропетровська область, 49000, Україна".to_owned(), importance: None }]));
+        assert_eq!(Geo::get("unknown galaxy", 1), Some(vec![]));
     }
 }
@@ -0,0 +1,106 @@
+//! The module responsible for detecting the location by the IP address via [ipinfo.io](https://ipinfo.io).
+//!
+//! The lookup is opt-in by `--geoip`: it's used only if neither the address nor the home address is given.
+
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::{geo::Geo, http::Http, init::Options, log::Log};
+
+/// Url of the IP geolocation service, it answers for the address of the request.
+const GEOIP_URL: &str = "https://ipinfo.io/json";
+
+/// Unit struct for the IP geolocation
+pub struct GeoIp;
+
+impl GeoIp {
+    /// Detect the location of the computer by its public IP address.
+    ///
+    /// Return
+    ///
+    /// `Option<Geo>` - The place, `None` if the service is unavailable or its answer is not recognized.
+    pub fn find() -> Option<Geo> {
+        let options = Options::get();
+        let json = match Http::fetch_json(
+            GEOIP_URL,
+            Duration::from_secs(options.geo_timeout()),
+            &[("User-Agent", options.user_agent())],
+        ) {
+            Ok(json) => json,
+            Err(e) => {
+                e.log(None);
+                return None;
+            }
+        };
+        let geo = GeoIp::parse(&json);
+        if geo.is_none() {
+            Log::info(
+                "Unable to recognize the location in the answer of the IP geolocation service.",
+            );
+        }
+        geo
+    }
+
+    /// Parse the answer of ipinfo.io, e.g. `{"city": "Kyiv", "region": "Kyiv City", "country": "UA", "loc": "50.4501,30.5234"}`.
+    ///
+    /// * `json: &Value` - Answer of the service.
+    ///
+    /// Return
+    ///
+    /// `Option<Geo>` - The place, the address consists of the known parts of the city, region and country.
+    fn parse(json: &Value) -> Option<Geo> {
+        let (lat, lon) = Geo::parse_coords(&format!("@{}", json["loc"].as_str()?))?;
+        let address = ["city", "region", "country"]
+            .iter()
+            .filter_map(|key| json[*key].as_str())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+        Some(Geo {
+            address: match address.is_empty() {
+                true => format!("{},{}", lat, lon),
+                false => address,
+            },
+            lat,
+            lon,
+            importance: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            GeoIp::parse(&json!({
+                "ip": "192.0.2.1",
+                "city": "Kyiv",
+                "region": "Kyiv City",
+                "country": "UA",
+                "loc": "50.4501,30.5234"
+            })),
+            Some(Geo {
+                lat: "50.4501".to_owned(),
+                lon: "30.5234".to_owned(),
+                address: "Kyiv, Kyiv City, UA".to_owned(),
+                importance: None,
+            })
+        );
+        assert_eq!(
+            GeoIp::parse(&json!({"loc": "50.4501,30.5234"})).map(|geo| geo.address),
+            Some("50.4501,30.5234".to_owned())
+        );
+        // Private addresses are answered without the location
+        assert_eq!(
+            GeoIp::parse(&json!({"ip": "10.0.0.1", "bogon": true})),
+            None
+        );
+        assert_eq!(GeoIp::parse(&json!({"loc": "north"})), None);
+    }
+}
@@ -0,0 +1,164 @@
+//! The module responsible for the `--graph` sparkline of the temperature curve of the day.
+//!
+//! The providers with the hourly or 3-hourly forecast pass all items of the requested day,
+//! the curve is drawn with the block characters, so it fits one line of the terminal.
+
+use chrono::{DateTime, FixedOffset, Local};
+
+use crate::out::Out;
+
+/// Block characters from the lowest to the highest value.
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Marker of the lowest value under the sparkline.
+const MIN_MARK: char = '▼';
+
+/// Marker of the highest value under the sparkline.
+const MAX_MARK: char = '▲';
+
+/// Unit struct for the temperature curve
+pub struct Graph;
+
+impl Graph {
+    /// Sparkline of the values, one block per value.
+    ///
+    /// * `values: &[f32]` - Values in the order of time.
+    pub fn sparkline(values: &[f32]) -> String {
+        let min = values.iter().cloned().fold(f32::INFINITY, f32::min);
+        let max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let top = (BLOCKS.len() - 1) as f32;
+        values
+            .iter()
+            .map(|value| match max - min {
+                // The flat curve is drawn in the middle
+                range if range <= f32::EPSILON => BLOCKS[BLOCKS.len() / 2],
+                range => BLOCKS[((value - min) / range * top).round() as usize],
+            })
+            .collect()
+    }
+
+    /// Lines of the temperature curve: the sparkline, the min/max markers and their values.
+    ///
+    /// * `points: &[(DateTime<Local>, f32)]` - Time and temperature of the forecast items.
+    /// * `offset: Option<FixedOffset>` - UTC offset of the location, the system timezone is used when it's unknown.
+    /// * `temp: &dyn Fn(f32) -> String` - Temperature with the unit, e.g. `18.5 °C`.
+    ///
+    /// Return
+    ///
+    /// `Vec<String>` - Lines for displaying, empty if there are less than 2 points.
+    pub fn lines(
+        points: &[(DateTime<Local>, f32)],
+        offset: Option<FixedOffset>,
+        temp: &dyn Fn(f32) -> String,
+    ) -> Vec<String> {
+        if points.len() < 2 {
+            return Vec::new();
+        }
+        let time = |dt: &DateTime<Local>| match offset {
+            Some(offset) => dt.with_timezone(&offset).format("%H:%M").to_string(),
+            None => dt.format("%H:%M").to_string(),
+        };
+        let values: Vec<f32> = points.iter().map(|(_, value)| *value).collect();
+        let (min, max) =
+            points
+                .iter()
+                .enumerate()
+                .fold((0, 0), |(min, max), (index, (_, value))| {
+                    (
+                        if *value < values[min] { index } else { min },
+                        if *value > values[max] { index } else { max },
+                    )
+                });
+        let marks: String = (0..points.len())
+            .map(|index| match index {
+                index if index == max => MAX_MARK,
+                index if index == min => MIN_MARK,
+                _ => ' ',
+            })
+            .collect();
+        vec![
+            format!(
+                "Temperature curve of the day ({} - {}):",
+                time(&points[0].0),
+                time(&points[points.len() - 1].0)
+            ),
+            format!("  {}", Graph::sparkline(&values)),
+            format!("  {}", marks.trim_end()),
+            format!(
+                "  {} min {} at {}, {} max {} at {}",
+                MIN_MARK,
+                temp(values[min]),
+                time(&points[min].0),
+                MAX_MARK,
+                temp(values[max]),
+                time(&points[max].0)
+            ),
+        ]
+    }
+
+    /// Display the temperature curve.
+    ///
+    /// * `points: &[(DateTime<Local>, f32)]` - Time and temperature of the forecast items.
+    /// * `offset: Option<FixedOffset>` - UTC offset of the location.
+    /// * `temp: &dyn Fn(f32) -> String` - Temperature with the unit.
+    pub fn show(
+        points: &[(DateTime<Local>, f32)],
+        offset: Option<FixedOffset>,
+        temp: &dyn Fn(f32) -> String,
+    ) {
+        let lines = Graph::lines(points, offset, temp);
+        if lines.is_empty() {
+            Out::line(
+                "Temperature curve of the day: not available, it needs the forecast for a date.",
+            );
+        }
+        for line in lines {
+            Out::line(&line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(Graph::sparkline(&[0.0, 7.0, 3.5, 14.0]), "▁▅▃█");
+        assert_eq!(Graph::sparkline(&[5.0, 5.0, 5.0]), "▅▅▅");
+        assert_eq!(Graph::sparkline(&[]), "");
+    }
+
+    #[test]
+    fn test_lines() {
+        let offset = FixedOffset::east_opt(3 * 3600);
+        let point = |hour: u32, value: f32| {
+            (
+                Utc.with_ymd_and_hms(2023, 5, 11, hour, 0, 0)
+                    .unwrap()
+                    .with_timezone(&Local),
+                value,
+            )
+        };
+        let points = [
+            point(0, 12.0),
+            point(3, 10.0),
+            point(6, 15.0),
+            point(9, 19.0),
+            point(12, 17.0),
+        ];
+        let temp = |t: f32| format!("{:.1} °C", t);
+        assert_eq!(
+            Graph::lines(&points, offset, &temp),
+            vec![
+                "Temperature curve of the day (03:00 - 15:00):",
+                "  ▃▁▅█▆",
+                "   ▼ ▲",
+                "  ▼ min 10.0 °C at 06:00, ▲ max 19.0 °C at 12:00",
+            ]
+        );
+        assert!(Graph::lines(&points[..1], offset, &temp).is_empty());
+    }
+}
@@ -19,21 +19,107 @@ For help information, type: \"weather help\"",
         } else {
             println!(
 "weather: {} v:{}
-Usage: weather help | configure [provider] | get [provider] <address> [date=format]
+Usage: weather [flags] help | version | repl | config [set|unset] | list [--json] | capabilities | doctor | configure [provider] [--show|--reset] | get [provider] [address] [date=format]
 
 This application displays weather information for CLI on Windows, Linux, and macOS:
 
   help                      - Shows this help message
+  version, --version, -V    - Prints the version of the application
+  repl                      - Reads the commands from stdin until \"exit\", e.g. get Kyiv date=now
+  list                      - Displays a list of available providers and allows to set the default, like configure
+  list --json               - Prints the providers as a json array of {{index, name, is_default, configured}} objects
+  capabilities              - Prints a table of the fields each provider supplies, e.g. UV index or alerts
+  doctor                    - Checks Nominatim and each configured provider, prints the status and latency of each
   configure                 - Displays a list of available providers and allows to set the default
   configure <provider>      - Configures credentials for the selected provider
+  configure <provider> --show - Prints the saved credentials of the provider, the keys are masked
+  configure <provider> --reset - Removes the saved credentials of the provider without a prompt
+  configure default <provider> - Sets the default provider without a prompt
+  configure <name>=<value>  - Saves the default value of the flag: units, format, precision,
+                              geocoder-url, user-agent, geo-timeout, api-timeout or proxy. An empty value removes the setting
+  config                    - Prints the effective configuration and the source of each value
+  config set <name> <value> - Saves the setting, e.g. config set units imperial or config set provider MetNorway
+  config unset <name>       - Removes the saved setting
+  configure home <address>  - Saves the home address for \"get\" without an address, e.g. configure home Kyiv, Ukraine
+  configure order <providers> - Saves the providers of \"get\" without a provider in the order of the requests,
+                              e.g. configure order OpenWeather WeatherAPI MetNorway. configure order= removes it
+  get                       - Displays weather for the saved home address using the default provider
   get <address>             - Displays weather for the provided address using the default provider
   get [provider] <address>  - Displays weather for the provided address using the specified provider
       [date=format]         - Displays weather for the specified date
+                              Several dates are shown in turn, e.g. date=today date=tomorrow
 
-  format = now | yyyy-mm-dd | yyyy-mm-ddThh:mm:ss
+  address = <place name> [country:<name>] | <postal code> | @lat,lon | key:<id> | -
+    <postal code>           - Searches the postal code with the structured query, e.g. 10115 or SW1A 1AA
+    country:<name>          - Country hint at the end of the address, e.g. 10115 country:Germany
+    @lat,lon                - Coordinates of the place, e.g. @50.45,30.52
+    key:<id>                - AccuWeather location key, e.g. key:324505. Skips the address lookups
+    -                       - Reads addresses from stdin, one per line, e.g. weather get - < cities.txt
+
+  format = now | today | tomorrow | yyyy-mm-dd | yyyy-mm-ddThh:mm:ss | <start>..<end>
     now                     - Displays weather for the current date and time
+    today, tomorrow         - Displays weather for today or tomorrow and current time, e.g. today..tomorrow
     yyyy-mm-dd              - Displays weather for the specified date and current time
     yyyy-mm-ddThh:mm:ss     - Displays weather for the specified date and time
+                              With the UTC offset, e.g. 2023-10-29T03:30:00+02:00, for the hour repeated by the DST change
+    <start>..<end>          - Displays a daily summary table for the range of days, e.g. 2023-05-01..2023-05-05
+    The dates must be within a year from today, VisualCrossing accepts the past dates since 1970
+    The other providers only answer from today to the end of their forecast, e.g. 4 days for AccuWeather
+
+Flags:
+  -q, --quiet               - Suppresses diagnostic messages, only errors are printed to stderr
+  -v, -vv, --verbose        - Prints the duration of the geocoding and of the provider requests to stderr,
+                              -vv also prints each HTTP call and the JSON parsing
+  --geocoder-url=<url>      - Search endpoint of a self-hosted Nominatim server, e.g. http://localhost:8080/search
+  --user-agent=<text>       - User-Agent of the geocoder and MetNorway requests, e.g. \"weather (me@example.com)\"
+  --proxy=<url>|none        - Proxy of all requests, e.g. http://proxy.local:3128 (default HTTPS_PROXY or ALL_PROXY)
+  --address-limit=<1..40>   - Number of the places requested from the geocoder, the most relevant one is used (default 1)
+  --geo-ttl=<hours>         - Lifetime of the cached address coordinates, 0 disables the cache (default 72)
+  --geo-timeout=<seconds>   - Timeout of the geocoder requests, from 1 to 300 (default 3)
+  --api-timeout=<seconds>   - Timeout of the weather provider requests, from 1 to 300 (default 3)
+  --precision=<0..3>        - Number of decimal places of the displayed values (default 1)
+  --locale=<code>|system    - Decimal separator and digit grouping of the values, e.g. --locale=de for 12,5 °C
+                              (default 12.5 °C), system - the LC_NUMERIC or LANG of the system
+  --units=metric|imperial   - Units of the temperature, wind speed and precipitation (default metric)
+  --pressure-unit=hpa|mmhg|inhg - Unit of the atmospheric pressure (default hPa or mbar of the provider)
+  --both-units              - Displays the temperature, wind and pressure in both unit systems, e.g. 18 °C / 64.4 °F
+  --units-in-header         - Prints the units line at the top of the forecast, e.g. Units: metric (°C, km/h, hPa)
+                              It's printed anyway for the imperial units or --pressure-unit
+  --oneline                 - Prints a one line summary of the current weather, e.g. for status bars
+  --table                   - Displays the fields of the forecast in a bordered table
+  --fields=<list>           - Displays only the selected fields, e.g. --fields=temp,wind,humidity
+                              Fields: condition, temp, feels, dew, humidity, pressure, wind, precip,
+                              cloud, visibility, uv, sun, moon, air
+  --brief                   - Displays only the condition, temperature, feels-like, wind and precipitation
+  --average=<2..24>         - Averages the temperature, wind, humidity and pressure of the forecast items closest to
+                              the date (OpenWeather, WeatherAPI, VisualCrossing, MetNorway and TomorrowIO, date only)
+  --graph                   - Displays the temperature curve of the day (OpenWeather and WeatherAPI, date only)
+  --aqi                     - Displays the air quality (OpenWeather and WeatherAPI only)
+  --max-age=<minutes>       - Warns if the current conditions are older, e.g. ⚠ The observation is 2 h old.
+                              (default 60, 0 disables the warning)
+  --astro                   - Displays only the sun and moon times and the moon phase for the date
+                              (not MetNorway and TomorrowIO, the moonrise by WeatherAPI and OpenWeatherOneCall)
+  --geoip                   - Detects the location by the IP address via ipinfo.io if no address or home is given
+  --strict-geo              - Fails with the list of the places if the address matches several of them
+  --explain                 - Prints the requested endpoints and how the forecast item was selected
+  --dry-run                 - Prints the urls of the requests with the hidden API keys instead of sending them
+  --watch=<seconds>         - Updates the forecast with the interval until Ctrl-C, e.g. --watch=600
+  --output <path>           - Writes the forecast to the file instead of stdout
+  --append                  - Appends the forecast to the --output file after a \"# <time>\" line
+  --fallback                - Requests the other configured providers if the selected one fails
+  --max-requests=<n>        - Daily budget of the requests to each provider, reset at local midnight
+  --save-json <path>        - Saves the raw answers of the servers to the file for debugging
+  --lang=<code>             - Language of the condition text and place names, e.g. uk (default system locale)
+  --format=text|json|json-lines|csv - Output format, json prints the normalized metric report (default text)
+  --json-lines              - Prints each json report on its own line as soon as it's ready
+                              csv prints the header row once and a row per report, also for stdin and --watch
+                              In the json formats the errors are printed as {{\"error\":{{...}}}} objects
+                              The flags override the values saved by \"configure <name>=<value>\"
+
+Environment variables (take precedence over the saved keys):
+  WEATHER_OPENWEATHER_KEY, WEATHER_WEATHERAPI_KEY, WEATHER_ACCUWEATHER_KEY,
+  WEATHER_VISUALCROSSING_KEY, WEATHER_TOMORROWIO_KEY, WEATHER_ONECALL_KEY, WEATHER_AERIS_ID
+  and WEATHER_AERIS_SECRET
 
 Examples:
   \"weather get Kyiv, Ukraine\"
@@ -48,9 +134,19 @@ Examples:
 Note:
     We would like to note separately that not all weather providers provide a forecast for the specified date,
     so the program searches for the closest date to the entered one.
+    If the closest forecast is more than 24 hours away, a warning is printed, --explain shows the details.
+    The times are displayed in the timezone of the place, TomorrowIO and MetNorway use the system timezone.
+    The MetNorway provider is free and does not require an API key.
+    The OpenWeatherOneCall provider requires the One Call 3.0 subscription.
+    Active weather alerts of OpenWeatherOneCall and of the WeatherAPI forecast are displayed before the forecast.
 
 Please report any bugs to {}"
             , env!("CARGO_PKG_DESCRIPTION"), env!("CARGO_PKG_VERSION"), env!("CARGO_PKG_AUTHORS"));
         };
     }
+
+    /// Prints the version of the application, e.g. for scripts.
+    pub fn version() {
+        println!("weather {}", env!("CARGO_PKG_VERSION"));
+    }
 }
@@ -1,19 +1,54 @@
 //! The module responsible for initialization parameters, as well as saving and loading keys to a file.
 //!
 
-use std::env;
+use std::{env, fmt, sync::RwLock};
 
-use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{http::TIMEOUT, log::Log, table::Table};
 
 const PROVIDER: &str = "provider=";
 const DATE: &str = "date=";
 
+/// Format of the date with the UTC offset in the json and the `date=` parameter.
+const DATE_OFFSET_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+/// The shortest interval of `--watch` in seconds, the providers limit the number of requests.
+const WATCH_MIN: u64 = 10;
+
+/// Value of `--locale` for the locale of the system.
+const LOCALE_SYSTEM: &str = "system";
+
+/// The largest number of the forecast items of `--average`, a day of the hourly forecast.
+const AVERAGE_MAX: usize = 24;
+
+/// The longest timeout of the requests in seconds.
+const TIMEOUT_MAX: u64 = 300;
+
+/// Search endpoint of the public Nominatim server.
+pub const GEOCODER_URL: &str = "https://nominatim.openstreetmap.org/search";
+
+/// Default `User-Agent` of the requests to the geocoder and MET Norway, their policies require an identifier of the application.
+pub const USER_AGENT: &str = concat!(
+    "weather/",
+    env!("CARGO_PKG_VERSION"),
+    " (+https://github.com/tryteex/weather)"
+);
+
+/// Number of days before and after today the providers can answer for by default.
+pub const DATE_WINDOW: i64 = 366;
+
+/// Global flags of the running application.
+static OPTIONS: RwLock<Options> = RwLock::new(Options::new());
+
 /// Describes date value.
 ///
 /// * `Now` - Current data and time (now).
 /// * `Error` - Error set data.
 /// * `Set(DateTime<Local>)` - The given date.
-#[derive(Debug, PartialEq)]
+/// * `Range(DateTime<Local>, DateTime<Local>)` - The given range of days.
+#[derive(Debug, PartialEq, Clone)]
 pub enum Date {
     /// Current data and time (now).
     Now,
@@ -21,60 +56,1113 @@ pub enum Date {
     Error,
     /// The given date.
     Set(DateTime<Local>),
+    /// The given range of days, start and end inclusive.
+    Range(DateTime<Local>, DateTime<Local>),
+}
+
+impl Date {
+    /// Checking whether the date is within the window of days around `now`.
+    ///
+    /// * `now: &DateTime<Local>` - Current date.
+    /// * `past: i64` - Number of days before `now`.
+    /// * `future: i64` - Number of days after `now`.
+    ///
+    /// Both ends of the range must be within the window, `now` is always valid.
+    pub fn in_window(&self, now: &DateTime<Local>, past: i64, future: i64) -> bool {
+        let valid = |dt: &DateTime<Local>| {
+            *dt >= *now - Duration::days(past) && *dt <= *now + Duration::days(future)
+        };
+        match self {
+            Date::Now | Date::Error => true,
+            Date::Set(dt) => valid(dt),
+            Date::Range(start, end) => valid(start) && valid(end),
+        }
+    }
+
+    /// Checking whether the date is within the forecast of the provider: from today to `days` after it.
+    ///
+    /// * `today: NaiveDate` - Current local date.
+    /// * `days: i64` - The last day of the forecast after today.
+    ///
+    /// The range must overlap the forecast, the days outside of it are skipped. `now` is always valid.
+    pub fn in_horizon(&self, today: NaiveDate, days: i64) -> bool {
+        let day = |dt: &DateTime<Local>| (dt.date_naive() - today).num_days();
+        match self {
+            Date::Now | Date::Error => true,
+            Date::Set(dt) => (0..=days).contains(&day(dt)),
+            Date::Range(start, end) => day(start) <= days && day(end) >= 0,
+        }
+    }
+}
+
+/// The json value is the value of the `date=` parameter with the UTC offset, e.g. `"now"`, `"2023-05-11T12:00:00+03:00"`
+/// or `"2023-05-11T00:00:00+03:00..2023-05-13T00:00:00+03:00"`, so the repeated hour of the DST change is kept.
+/// `Date::Error` has no value.
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let format = |dt: &DateTime<Local>| dt.format(DATE_OFFSET_FORMAT).to_string();
+        match self {
+            Date::Now => serializer.serialize_str("now"),
+            Date::Error => Err(ser::Error::custom("the wrong date can't be serialized")),
+            Date::Set(dt) => serializer.serialize_str(&format(dt)),
+            Date::Range(start, end) => {
+                serializer.serialize_str(&format!("{}..{}", format(start), format(end)))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Date, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Init::read_date(&value).map_err(de::Error::custom)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Date::Now => write!(f, "now"),
+            Date::Error => write!(f, "error"),
+            Date::Set(dt) => write!(f, "{}", dt.format("%Y-%m-%d %H:%M:%S (%:z)")),
+            Date::Range(start, end) => write!(
+                f,
+                "{}..{}",
+                start.format("%Y-%m-%d"),
+                end.format("%Y-%m-%d")
+            ),
+        }
+    }
 }
 
 /// The command to launch the application.
 ///
 /// * `List` - Displays a list of available providers and allows to set the default.
+/// * `Capabilities` - Displays the table of the fields each provider supplies.
+/// * `Doctor` - Checks the geocoder and each configured provider with a request.
 /// * `Configure { provider }` - Configures credentials for the selected provider.
 ///   * `provider: String` - The selected provider.
-/// * `Get { provider, address, date }` - Displays weather for the provided address.
+/// * `ShowCredentials { provider }` - Prints the stored credentials of the provider with the masked secrets.
+///   * `provider: String` - The selected provider.
+/// * `ResetCredentials { provider }` - Removes the saved credentials of the provider without a prompt.
+///   * `provider: String` - The selected provider.
+/// * `SetDefault { provider }` - Sets the default provider without a prompt.
+///   * `provider: String` - The selected provider.
+/// * `Setting { name, value }` - Saves the setting to the config file, `configure home <address>` too.
+///   * `name: String` - Setting name.
+///   * `value: String` - Setting value.
+/// * `ListJson` - Prints the providers as a json array without a prompt.
+/// * `Config` - Prints the effective configuration.
+/// * `Get { provider, address, dates }` - Displays weather for the provided address.
 ///   * `provider: Option<String>` - Using the default provider.
 ///   * `address: String` - The provided address.
-///   * `date: Date` - Displays weather for the specified date.
+///   * `dates: Vec<Date>` - Displays weather for each of the specified dates.
+/// * `Version` - Prints the version of the application.
+/// * `Repl` - Reads and runs the commands from stdin until `exit`.
 /// * `Help { error}` - Shows the help message.
 ///   * `error: bool` - True: an error occurred while recognizing the launch command.
 #[derive(Debug, PartialEq)]
 pub enum Command {
     /// Displays a list of available providers and allows to set the default.
     List,
+    /// Prints the providers and their status as a json array without a prompt.
+    ListJson,
+    /// Prints the effective configuration: the default provider and the settings with their source.
+    Config,
+    /// Displays the table of the fields each provider supplies.
+    Capabilities,
+    /// Checks the geocoder and each configured provider with a request, prints a summary table.
+    Doctor,
     /// Configures credentials for the selected provider.
     /// * `provider` - The selected provider.
     Configure { provider: String },
+    /// Prints the stored credentials of the provider with the masked secrets.
+    /// * `provider` - The selected provider.
+    ShowCredentials { provider: String },
+    /// Removes the saved credentials of the provider without a prompt.
+    /// * `provider` - The selected provider.
+    ResetCredentials { provider: String },
+    /// Sets the default provider without a prompt.
+    /// * `provider` - The selected provider.
+    SetDefault { provider: String },
+    /// Saves the setting to the config file.
+    /// * `name` - Setting name.
+    /// * `value` - Setting value.
+    Setting { name: String, value: String },
     /// Displays weather for the provided address.
     /// * `provider` - Using the default provider.
     /// * `address` - The provided address.
-    /// * `date` - Displays weather for the specified date.
+    /// * `dates` - Displays weather for each of the specified dates in the given order.
     Get {
         provider: Option<String>,
         address: String,
-        date: Date,
+        dates: Vec<Date>,
     },
+    /// Prints the version of the application.
+    Version,
+    /// Reads and runs the commands from stdin until `exit`.
+    Repl,
     /// Shows the help message.
     /// * `error` - True: an error occurred while recognizing the launch command.
     Help { error: bool },
 }
 
+/// Unit system of the displayed values.
+///
+/// * `Metric` - °C, km/h, mm.
+/// * `Imperial` - °F, mph, in.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Units {
+    /// °C, km/h, mm.
+    Metric,
+    /// °F, mph, in.
+    Imperial,
+}
+
+impl Units {
+    /// Recognize units by name.
+    pub fn parse(value: &str) -> Option<Units> {
+        match value {
+            "metric" => Some(Units::Metric),
+            "imperial" => Some(Units::Imperial),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Units::Metric => write!(f, "metric"),
+            Units::Imperial => write!(f, "imperial"),
+        }
+    }
+}
+
+/// Unit of the displayed atmospheric pressure.
+///
+/// * `Hpa` - Hectopascals, the same as millibars.
+/// * `Mmhg` - Millimeters of mercury.
+/// * `Inhg` - Inches of mercury.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PressureUnit {
+    /// Hectopascals, the same as millibars.
+    Hpa,
+    /// Millimeters of mercury.
+    Mmhg,
+    /// Inches of mercury.
+    Inhg,
+}
+
+impl PressureUnit {
+    /// Recognize the pressure unit by name.
+    pub fn parse(value: &str) -> Option<PressureUnit> {
+        match value.to_lowercase().as_str() {
+            "hpa" | "mbar" => Some(PressureUnit::Hpa),
+            "mmhg" => Some(PressureUnit::Mmhg),
+            "inhg" => Some(PressureUnit::Inhg),
+            _ => None,
+        }
+    }
+}
+
+/// Output format of the weather forecast.
+///
+/// * `Text` - Human readable text.
+/// * `Json` - Normalized weather report in json.
+/// * `JsonLines` - Normalized weather reports in json, one object per line as soon as it's ready.
+/// * `Csv` - Normalized weather reports in csv, the header row and a row per report.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Output {
+    /// Human readable text.
+    Text,
+    /// Normalized weather report in json.
+    Json,
+    /// Normalized weather reports in json, one object per line as soon as it's ready.
+    JsonLines,
+    /// Normalized weather reports in csv, the header row and a row per report.
+    Csv,
+}
+
+impl Output {
+    /// Recognize output format by name.
+    pub fn parse(value: &str) -> Option<Output> {
+        match value {
+            "text" => Some(Output::Text),
+            "json" => Some(Output::Json),
+            "json-lines" => Some(Output::JsonLines),
+            "csv" => Some(Output::Csv),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Output::Text => write!(f, "text"),
+            Output::Json => write!(f, "json"),
+            Output::JsonLines => write!(f, "json-lines"),
+            Output::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+/// Global flags that can be set for any command.
+///
+/// The flags that can be saved to the config file are `None` when absent,
+/// so the saved settings are applied only if the flag was not set.
+///
+/// * `quiet: bool` - Suppress diagnostic messages.
+/// * `geo_ttl: u64` - Lifetime of the geocoding cache in hours, 0 - cache is disabled.
+/// * `address_limit: u32` - Number of the geocoding candidates requested from the server.
+/// * `precision: Option<u8>` - Number of decimal places of the displayed values.
+/// * `units: Option<Units>` - Unit system of the displayed values.
+/// * `format: Option<Output>` - Output format.
+/// * `lang: Option<String>` - Language of the condition text and place names.
+/// * `oneline: bool` - Print a one line summary of the current weather.
+/// * `save_json: Option<String>` - File for the raw server answers.
+/// * `table: bool` - Display the fields in a bordered table.
+/// * `aqi: bool` - Display the air quality.
+/// * `geocoder_url: Option<String>` - Search endpoint of the Nominatim geocoder.
+/// * `user_agent: Option<String>` - `User-Agent` of the requests to the geocoder and MET Norway.
+/// * `dry_run: bool` - Print the urls of the requests instead of sending them.
+/// * `fields: Option<Vec<String>>` - Keys of the displayed fields.
+/// * `brief: bool` - Display only the top-priority fields.
+/// * `output: Option<String>` - File for the forecast instead of stdout.
+/// * `append: bool` - Append the forecast to the `output` file.
+/// * `max_requests: Option<u32>` - Number of the requests allowed per provider and day.
+/// * `fallback: bool` - Retry with the other configured providers if the selected one fails.
+/// * `graph: bool` - Display the temperature curve of the day.
+/// * `pressure_unit: Option<PressureUnit>` - Unit of the displayed pressure.
+/// * `watch: Option<u64>` - Interval of the forecast updates in seconds.
+/// * `explain: bool` - Report how the displayed forecast item was selected.
+/// * `units_in_header: bool` - Display the units line at the top of the forecast.
+/// * `average: Option<usize>` - Number of the forecast items around the date to average.
+/// * `locale: Option<String>` - Language of the number formatting or `system`.
+/// * `geo_timeout: Option<u64>` - Timeout of the geocoding requests in seconds.
+/// * `api_timeout: Option<u64>` - Timeout of the weather provider requests in seconds.
+/// * `verbose: u8` - Level of the timing messages: 1 - phases, 2 - each request.
+/// * `geoip: bool` - Detect the location by the IP address if no address is given.
+/// * `astro: bool` - Display the sun and moon times instead of the forecast.
+/// * `max_age: Option<u64>` - The largest age of the current conditions without a warning, minutes.
+/// * `strict_geo: bool` - Fail if the address matches several places instead of taking the first one.
+/// * `proxy: Option<String>` - Proxy of the requests or `none`.
+/// * `both_units: bool` - Display the temperature, wind and pressure in both unit systems.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Options {
+    /// Suppress diagnostic messages.
+    pub quiet: bool,
+    /// Lifetime of the geocoding cache in hours, 0 - cache is disabled.
+    pub geo_ttl: u64,
+    /// Number of the geocoding candidates requested from the server (1..=40), the first one is used.
+    pub address_limit: u32,
+    /// Number of decimal places of the displayed values (0..=3), default 1.
+    pub precision: Option<u8>,
+    /// Unit system of the displayed values, default metric.
+    pub units: Option<Units>,
+    /// Output format, default text.
+    pub format: Option<Output>,
+    /// Language of the condition text and place names, default system locale or English.
+    pub lang: Option<String>,
+    /// Print a one line summary of the current weather.
+    pub oneline: bool,
+    /// File for the raw server answers.
+    pub save_json: Option<String>,
+    /// Display the fields in a bordered table.
+    pub table: bool,
+    /// Display the air quality.
+    pub aqi: bool,
+    /// Search endpoint of the Nominatim geocoder, default the public server.
+    pub geocoder_url: Option<String>,
+    /// `User-Agent` of the requests to the geocoder and MET Norway, default `USER_AGENT`.
+    pub user_agent: Option<String>,
+    /// Print the urls of the requests instead of sending them.
+    pub dry_run: bool,
+    /// Keys of the displayed fields, e.g. `temp`, default all fields.
+    pub fields: Option<Vec<String>>,
+    /// Display only the condition, temperature, feels-like temperature, wind and precipitation.
+    pub brief: bool,
+    /// File for the forecast instead of stdout.
+    pub output: Option<String>,
+    /// Append the forecast to the `output` file after a timestamp line, otherwise the file is rewritten.
+    pub append: bool,
+    /// Number of the requests allowed per provider and day, the requests aren't counted when it's `None`.
+    pub max_requests: Option<u32>,
+    /// Retry with the other providers with the credentials if the selected one fails.
+    pub fallback: bool,
+    /// Display the temperature curve of the day for a forecast date.
+    pub graph: bool,
+    /// Unit of the displayed pressure, default the hPa or mbar of the provider.
+    pub pressure_unit: Option<PressureUnit>,
+    /// Interval of the forecast updates in seconds, `None` - the forecast is displayed once.
+    pub watch: Option<u64>,
+    /// Report the endpoints, the candidate items and the selected one.
+    pub explain: bool,
+    /// Display the `Units: ...` line even for the default units.
+    pub units_in_header: bool,
+    /// Number of the hourly items closest to the requested date to average, `None` - the closest item.
+    pub average: Option<usize>,
+    /// Language of the decimal separator and digit grouping, e.g. `de`, or `system` for the system locale.
+    /// `None` - the `.` separator without grouping.
+    pub locale: Option<String>,
+    /// Timeout of the geocoding requests in seconds, default `TIMEOUT`.
+    pub geo_timeout: Option<u64>,
+    /// Timeout of the weather provider requests in seconds, default `TIMEOUT`.
+    pub api_timeout: Option<u64>,
+    /// Level of the timing messages on stderr: 0 - off, 1 - phases (`-v`), 2 - each request (`-vv`).
+    pub verbose: u8,
+    /// Detect the location by the IP address if neither the address nor the home address is given.
+    pub geoip: bool,
+    /// Display the sun and moon times instead of the forecast.
+    pub astro: bool,
+    /// The largest age of the current conditions without a warning in minutes, 0 - no warning, default 60.
+    pub max_age: Option<u64>,
+    /// Fail with the list of the candidates if the address doesn't resolve to exactly one place.
+    pub strict_geo: bool,
+    /// Proxy of all requests, e.g. `http://proxy.local:3128`, or `none` to ignore the environment.
+    /// `None` - the proxy of the `HTTPS_PROXY`, `HTTP_PROXY` or `ALL_PROXY` environment variables.
+    pub proxy: Option<String>,
+    /// Display the temperature, wind speed and pressure in the selected and the other unit system.
+    pub both_units: bool,
+}
+
+impl Options {
+    /// Create default flags.
+    pub const fn new() -> Options {
+        Options {
+            quiet: false,
+            geo_ttl: 72,
+            address_limit: 1,
+            precision: None,
+            units: None,
+            format: None,
+            lang: None,
+            oneline: false,
+            save_json: None,
+            table: false,
+            aqi: false,
+            geocoder_url: None,
+            user_agent: None,
+            dry_run: false,
+            fields: None,
+            brief: false,
+            output: None,
+            append: false,
+            max_requests: None,
+            fallback: false,
+            graph: false,
+            pressure_unit: None,
+            watch: None,
+            explain: false,
+            units_in_header: false,
+            average: None,
+            locale: None,
+            geo_timeout: None,
+            api_timeout: None,
+            verbose: 0,
+            geoip: false,
+            astro: false,
+            max_age: None,
+            strict_geo: false,
+            proxy: None,
+            both_units: false,
+        }
+    }
+
+    /// Get flags of the running application.
+    pub fn get() -> Options {
+        match OPTIONS.read() {
+            Ok(options) => options.clone(),
+            Err(e) => e.into_inner().clone(),
+        }
+    }
+
+    /// Set flags of the running application.
+    pub fn set(options: Options) {
+        Log::set_quiet(options.quiet);
+        Log::set_verbose(options.verbose);
+        match OPTIONS.write() {
+            Ok(mut current) => *current = options,
+            Err(e) => *e.into_inner() = options,
+        }
+    }
+
+    /// Apply the saved settings to the flags that were not set.
+    ///
+    /// * `settings: &Settings` - Settings from the config file.
+    pub fn apply(&mut self, settings: &Settings) {
+        self.precision = self.precision.or(settings.precision);
+        self.units = self.units.or(settings.units);
+        self.format = self.format.or(settings.format);
+        if self.geocoder_url.is_none() {
+            self.geocoder_url = settings.geocoder_url.clone();
+        }
+        if self.user_agent.is_none() {
+            self.user_agent = settings.user_agent.clone();
+        }
+        self.geo_timeout = self.geo_timeout.or(settings.geo_timeout);
+        self.api_timeout = self.api_timeout.or(settings.api_timeout);
+        if self.proxy.is_none() {
+            self.proxy = settings.proxy.clone();
+        }
+    }
+
+    /// Effective value of the setting, e.g. `metric` for `units`, for `weather config`.
+    ///
+    /// * `name: &str` - Setting name.
+    ///
+    /// Return
+    ///
+    /// `Option<String>` - `None` for the settings that are not flags, e.g. `home`.
+    pub fn value(&self, name: &str) -> Option<String> {
+        match name {
+            "units" => Some(self.units().to_string()),
+            "format" => Some(self.format().to_string()),
+            "precision" => Some(self.precision().to_string()),
+            "geocoder-url" => Some(self.geocoder_url().to_owned()),
+            "user-agent" => Some(self.user_agent().to_owned()),
+            "geo-timeout" => Some(self.geo_timeout().to_string()),
+            "api-timeout" => Some(self.api_timeout().to_string()),
+            "proxy" => Some(
+                self.proxy
+                    .clone()
+                    .unwrap_or_else(|| "environment".to_owned()),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Number of decimal places of the displayed values.
+    pub fn precision(&self) -> u8 {
+        self.precision.unwrap_or(1)
+    }
+
+    /// Unit system of the displayed values.
+    pub fn units(&self) -> Units {
+        self.units.unwrap_or(Units::Metric)
+    }
+
+    /// Output format.
+    pub fn format(&self) -> Output {
+        self.format.unwrap_or(Output::Text)
+    }
+
+    /// Search endpoint of the Nominatim geocoder.
+    pub fn geocoder_url(&self) -> &str {
+        self.geocoder_url.as_deref().unwrap_or(GEOCODER_URL)
+    }
+
+    /// `User-Agent` of the requests to the geocoder and MET Norway.
+    pub fn user_agent(&self) -> &str {
+        self.user_agent.as_deref().unwrap_or(USER_AGENT)
+    }
+
+    /// The largest age of the current conditions without a warning, minutes.
+    pub fn max_age(&self) -> u64 {
+        self.max_age.unwrap_or(60)
+    }
+
+    /// Timeout of the geocoding requests in seconds.
+    pub fn geo_timeout(&self) -> u64 {
+        self.geo_timeout.unwrap_or(TIMEOUT.as_secs())
+    }
+
+    /// Timeout of the weather provider requests in seconds.
+    pub fn api_timeout(&self) -> u64 {
+        self.api_timeout.unwrap_or(TIMEOUT.as_secs())
+    }
+
+    /// Parse the timeout in seconds, from 1 to `TIMEOUT_MAX`.
+    pub fn parse_timeout(value: &str) -> Option<u64> {
+        value
+            .parse::<u64>()
+            .ok()
+            .filter(|secs| (1..=TIMEOUT_MAX).contains(secs))
+    }
+
+    /// Checking the `User-Agent`: printable ASCII, it's sent as a header value.
+    pub fn is_user_agent(value: &str) -> bool {
+        !value.trim().is_empty() && value.chars().all(|c| c.is_ascii_graphic() || c == ' ')
+    }
+
+    /// Checking the geocoder url: http or https.
+    pub fn is_url(value: &str) -> bool {
+        (value.starts_with("https://") || value.starts_with("http://"))
+            && !value.contains(char::is_whitespace)
+    }
+
+    /// Checking the value of `--proxy`: http or https url or `none`.
+    pub fn is_proxy(value: &str) -> bool {
+        value == "none" || Options::is_url(value)
+    }
+
+    /// Language code of the condition text and place names, e.g. "uk".
+    pub fn lang(&self) -> String {
+        match &self.lang {
+            Some(lang) => lang.to_owned(),
+            None => ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .iter()
+                .filter_map(|name| env::var(name).ok())
+                .find_map(|locale| Options::locale_lang(&locale))
+                .unwrap_or_else(|| "en".to_owned()),
+        }
+    }
+
+    /// Language of the number formatting, `None` - the default `.` separator without grouping.
+    pub fn locale(&self) -> Option<String> {
+        match self.locale.as_deref() {
+            Some(LOCALE_SYSTEM) => ["LC_ALL", "LC_NUMERIC", "LANG"]
+                .iter()
+                .filter_map(|name| env::var(name).ok())
+                .find_map(|locale| Options::locale_lang(&locale)),
+            locale => locale.map(|locale| locale.to_owned()),
+        }
+    }
+
+    /// Language code from the system locale, e.g. "uk_UA.UTF-8" -> "uk".
+    fn locale_lang(locale: &str) -> Option<String> {
+        let lang = locale.split(['_', '.', '@']).next()?.to_lowercase();
+        if lang.is_empty() || lang == "c" || lang == "posix" {
+            return None;
+        }
+        Options::is_lang(&lang).then_some(lang)
+    }
+
+    /// Checking the language code, e.g. "en" or "pt-BR".
+    fn is_lang(lang: &str) -> bool {
+        (2..=8).contains(&lang.len()) && lang.chars().all(|c| c.is_ascii_alphabetic() || c == '-')
+    }
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options::new()
+    }
+}
+
+/// Settings saved to the config file as `name=value` lines.
+///
+/// * `precision: Option<u8>` - Number of decimal places of the displayed values.
+/// * `units: Option<Units>` - Unit system of the displayed values.
+/// * `format: Option<Output>` - Output format.
+/// * `geocoder_url: Option<String>` - Search endpoint of the Nominatim geocoder.
+/// * `user_agent: Option<String>` - `User-Agent` of the requests to the geocoder and MET Norway.
+/// * `home: Option<String>` - Address of `get` without an address.
+/// * `geo_timeout: Option<u64>` - Timeout of the geocoding requests in seconds.
+/// * `api_timeout: Option<u64>` - Timeout of the weather provider requests in seconds.
+/// * `order: Option<Vec<String>>` - Providers of `get` without a provider and of `--fallback` in the order of the requests.
+/// * `proxy: Option<String>` - Proxy of the requests or `none`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Settings {
+    /// Number of decimal places of the displayed values.
+    pub precision: Option<u8>,
+    /// Unit system of the displayed values.
+    pub units: Option<Units>,
+    /// Output format.
+    pub format: Option<Output>,
+    /// Search endpoint of the Nominatim geocoder.
+    pub geocoder_url: Option<String>,
+    /// `User-Agent` of the requests to the geocoder and MET Norway.
+    pub user_agent: Option<String>,
+    /// Address of `get` without an address, e.g. `Kyiv, Ukraine`.
+    pub home: Option<String>,
+    /// Timeout of the geocoding requests in seconds.
+    pub geo_timeout: Option<u64>,
+    /// Timeout of the weather provider requests in seconds.
+    pub api_timeout: Option<u64>,
+    /// Providers of `get` without a provider and of `--fallback` in the order of the requests,
+    /// e.g. `OpenWeather,WeatherAPI`.
+    pub order: Option<Vec<String>>,
+    /// Proxy of the requests, e.g. `http://proxy.local:3128`, or `none`.
+    pub proxy: Option<String>,
+}
+
+impl Settings {
+    /// Names of the settings.
+    pub const NAMES: [&'static str; 10] = [
+        "units",
+        "format",
+        "precision",
+        "geocoder-url",
+        "user-agent",
+        "home",
+        "geo-timeout",
+        "api-timeout",
+        "order",
+        "proxy",
+    ];
+
+    /// Name of the default provider for `weather config`, it's stored on the first line of the config file.
+    pub const PROVIDER: &'static str = "provider";
+
+    /// Set the setting by name.
+    ///
+    /// * `name: &str` - Setting name.
+    /// * `value: &str` - Setting value, empty value removes the setting.
+    ///
+    /// Return
+    ///
+    /// `Result<(), String>` - Error text if the name or value is wrong.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        match name {
+            "units" if value.is_empty() => self.units = None,
+            "units" => {
+                self.units = Some(Units::parse(value).ok_or_else(|| {
+                    format!("Wrong units '{}': metric or imperial is expected", value)
+                })?)
+            }
+            "format" if value.is_empty() => self.format = None,
+            "format" => {
+                self.format = Some(Output::parse(value).ok_or_else(|| {
+                    format!(
+                        "Wrong format '{}': text, json, json-lines or csv is expected",
+                        value
+                    )
+                })?)
+            }
+            "precision" if value.is_empty() => self.precision = None,
+            "precision" => match value.parse::<u8>() {
+                Ok(precision) if precision <= 3 => self.precision = Some(precision),
+                _ => {
+                    return Err(format!(
+                        "Wrong precision '{}': an integer from 0 to 3 is expected",
+                        value
+                    ))
+                }
+            },
+            "geocoder-url" if value.is_empty() => self.geocoder_url = None,
+            "geocoder-url" if Options::is_url(value) => self.geocoder_url = Some(value.to_owned()),
+            "geocoder-url" => {
+                return Err(format!(
+                    "Wrong geocoder url '{}': http or https url is expected",
+                    value
+                ))
+            }
+            "user-agent" if value.is_empty() => self.user_agent = None,
+            "user-agent" if Options::is_user_agent(value) => {
+                self.user_agent = Some(value.to_owned())
+            }
+            "user-agent" => {
+                return Err(format!(
+                    "Wrong user agent '{}': printable ASCII characters are expected",
+                    value
+                ))
+            }
+            "home" if value.is_empty() => self.home = None,
+            // The config file has a setting per line, `-` reads the addresses from stdin
+            "home" if value != "-" && !value.chars().any(|c| c.is_control()) => {
+                self.home = Some(value.split_whitespace().collect::<Vec<&str>>().join(" "))
+            }
+            "home" => {
+                return Err(format!(
+                    "Wrong home address '{}': a place name, @lat,lon or key:<id> is expected",
+                    value
+                ))
+            }
+            "geo-timeout" | "api-timeout" if value.is_empty() => match name {
+                "geo-timeout" => self.geo_timeout = None,
+                _ => self.api_timeout = None,
+            },
+            "geo-timeout" | "api-timeout" => match Options::parse_timeout(value) {
+                Some(secs) if name == "geo-timeout" => self.geo_timeout = Some(secs),
+                Some(secs) => self.api_timeout = Some(secs),
+                None => {
+                    return Err(format!(
+                        "Wrong {} '{}': an integer from 1 to {} seconds is expected",
+                        name, value, TIMEOUT_MAX
+                    ))
+                }
+            },
+            "proxy" if value.is_empty() => self.proxy = None,
+            "proxy" if Options::is_proxy(value) => self.proxy = Some(value.to_owned()),
+            "proxy" => {
+                return Err(format!(
+                    "Wrong proxy '{}': http or https url or none is expected",
+                    value
+                ))
+            }
+            // The names are separated by commas or spaces, e.g. `OpenWeather, WeatherAPI`
+            "order" => {
+                let order: Vec<String> = value
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|name| !name.is_empty())
+                    .map(|name| name.to_owned())
+                    .collect();
+                self.order = match order.is_empty() {
+                    true => None,
+                    false => Some(order),
+                };
+            }
+            _ => {
+                return Err(format!(
+                    "Unknown setting '{}'. Valid settings: {}, {}",
+                    name,
+                    Settings::PROVIDER,
+                    Settings::NAMES.join(", ")
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse the line of the config file.
+    ///
+    /// Return
+    ///
+    /// `bool` - True if the line is a setting.
+    pub fn parse_line(&mut self, line: &str) -> bool {
+        let (name, value) = match line.split_once('=') {
+            Some((name, value)) if Settings::NAMES.contains(&name) => (name, value),
+            _ => return false,
+        };
+        if let Err(e) = self.set(name, value) {
+            Log::info(&format!("The setting is ignored. {}", e));
+        }
+        true
+    }
+
+    /// Saved value of the setting.
+    ///
+    /// * `name: &str` - Setting name.
+    ///
+    /// Return
+    ///
+    /// `Option<String>` - `None` if the setting is not saved or unknown.
+    pub fn get(&self, name: &str) -> Option<String> {
+        match name {
+            "units" => self.units.map(|units| units.to_string()),
+            "format" => self.format.map(|format| format.to_string()),
+            "precision" => self.precision.map(|precision| precision.to_string()),
+            "geocoder-url" => self.geocoder_url.clone(),
+            "user-agent" => self.user_agent.clone(),
+            "home" => self.home.clone(),
+            "geo-timeout" => self.geo_timeout.map(|secs| secs.to_string()),
+            "api-timeout" => self.api_timeout.map(|secs| secs.to_string()),
+            "order" => self.order.as_ref().map(|order| order.join(",")),
+            "proxy" => self.proxy.clone(),
+            _ => None,
+        }
+    }
+
+    /// Lines for the config file.
+    pub fn lines(&self) -> Vec<String> {
+        Settings::NAMES
+            .iter()
+            .filter_map(|name| self.get(name).map(|value| format!("{}={}", name, value)))
+            .collect()
+    }
+}
+
 /// Initialization structure.
 ///
 /// * `pub args: String` - Arguments for starting the application.
 /// * `pub command: Command` - The command to launch the application.
+/// * `pub options: Options` - Global flags.
 #[derive(Debug, PartialEq)]
 pub struct Init {
     /// Parameters for starting the application.
     pub args: String,
     /// The command to launch the application.
     pub command: Command,
+    /// Global flags.
+    pub options: Options,
 }
 
 impl Init {
     /// Create empty initialization structure.
     pub fn new() -> Init {
         let list: Vec<String> = env::args().skip(1).collect();
+        Init::parse(&list)
+    }
+
+    /// Create initialization structure from the launch parameters and set the global flags.
+    ///
+    /// * `list: &[String]` - Launch parameters without the program name, e.g. a line of the `repl` command split by spaces.
+    pub fn parse(list: &[String]) -> Init {
         let args = list.join(" ");
-        let command = Init::parse_args(&list);
+        let (command, options) = match Init::parse_options(list) {
+            Some((options, list)) => (Init::parse_args(&list), options),
+            None => (Command::Help { error: true }, Options::default()),
+        };
+        Options::set(options.clone());
 
-        Init { args, command }
+        Init {
+            args,
+            command,
+            options,
+        }
+    }
+
+    /// Separating global flags from the launch parameters
+    ///
+    /// * `list: &[String]` - Array with launch parameters
+    ///
+    /// Return
+    ///
+    /// `Option<(Options, Vec<String>)>` - Turple with flags and the rest of parameters.
+    ///   * `Option::None` - Unknown flag.
+    fn parse_options(list: &[String]) -> Option<(Options, Vec<String>)> {
+        let mut options = Options::default();
+        let mut rest = Vec::with_capacity(list.len());
+        let mut list = list.iter();
+        while let Some(arg) = list.next() {
+            // `--version` is a command, `--show`, `--reset` and `--json` are parts of
+            // `configure <provider> --show|--reset` and `list --json`, not flags
+            if !arg.starts_with("--") && !matches!(arg.as_str(), "-q" | "-v" | "-vv")
+                || matches!(arg.as_str(), "--version" | "--show" | "--reset" | "--json")
+            {
+                rest.push(arg.to_owned());
+                continue;
+            }
+            let (flag, value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag, Some(value)),
+                None => (arg.as_str(), None),
+            };
+            match (flag, value) {
+                ("--quiet" | "-q", None) => options.quiet = true,
+                ("--verbose" | "-v", None) => options.verbose = (options.verbose + 1).min(2),
+                ("-vv", None) => options.verbose = 2,
+                ("--oneline", None) => options.oneline = true,
+                ("--brief", None) => options.brief = true,
+                ("--table", None) => options.table = true,
+                ("--json-lines", None) => options.format = Some(Output::JsonLines),
+                ("--aqi", None) => options.aqi = true,
+                ("--dry-run", None) => options.dry_run = true,
+                ("--append", None) => options.append = true,
+                ("--fallback", None) => options.fallback = true,
+                ("--graph", None) => options.graph = true,
+                ("--explain", None) => options.explain = true,
+                ("--geoip", None) => options.geoip = true,
+                ("--astro", None) => options.astro = true,
+                ("--strict-geo", None) => options.strict_geo = true,
+                ("--both-units", None) => options.both_units = true,
+                ("--units-in-header", None) => options.units_in_header = true,
+                ("--output", value) => match value.or_else(|| list.next().map(|s| s.as_str())) {
+                    Some(path) if !path.is_empty() => options.output = Some(path.to_owned()),
+                    _ => {
+                        Log::error(&format!("The flag {} requires a file path", flag));
+                        return None;
+                    }
+                },
+                ("--save-json", value) => match value.or_else(|| list.next().map(|s| s.as_str())) {
+                    Some(path) if !path.is_empty() => options.save_json = Some(path.to_owned()),
+                    _ => {
+                        Log::error(&format!("The flag {} requires a file path", flag));
+                        return None;
+                    }
+                },
+                ("--geocoder-url", Some(value)) => {
+                    if !Options::is_url(value) {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: http or https url is expected",
+                            arg
+                        ));
+                        return None;
+                    }
+                    options.geocoder_url = Some(value.to_owned());
+                }
+                ("--proxy", Some(value)) => {
+                    if !Options::is_proxy(value) {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: http or https url or none is expected",
+                            arg
+                        ));
+                        return None;
+                    }
+                    options.proxy = Some(value.to_owned());
+                }
+                ("--user-agent", Some(value)) => {
+                    if !Options::is_user_agent(value) {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: printable ASCII characters are expected",
+                            arg
+                        ));
+                        return None;
+                    }
+                    options.user_agent = Some(value.to_owned());
+                }
+                ("--geo-ttl", Some(value)) => match value.parse::<u64>() {
+                    Ok(ttl) => options.geo_ttl = ttl,
+                    Err(e) => {
+                        Log::error(&format!("Wrong value of the flag {}: {}", arg, e));
+                        return None;
+                    }
+                },
+                ("--geo-timeout" | "--api-timeout", Some(value)) => {
+                    match Options::parse_timeout(value) {
+                        Some(secs) if flag == "--geo-timeout" => options.geo_timeout = Some(secs),
+                        Some(secs) => options.api_timeout = Some(secs),
+                        None => {
+                            Log::error(&format!(
+                            "Wrong value of the flag {}: an integer from 1 to {} seconds is expected",
+                            arg, TIMEOUT_MAX
+                        ));
+                            return None;
+                        }
+                    }
+                }
+                ("--max-age", Some(value)) => match value.parse::<u64>() {
+                    Ok(minutes) => options.max_age = Some(minutes),
+                    Err(_) => {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: a number of minutes is expected, 0 disables the warning",
+                            arg
+                        ));
+                        return None;
+                    }
+                },
+                ("--max-requests", Some(value)) => match value.parse::<u32>() {
+                    Ok(limit) => options.max_requests = Some(limit),
+                    Err(_) => {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: a non-negative integer is expected",
+                            arg
+                        ));
+                        return None;
+                    }
+                },
+                ("--address-limit", Some(value)) => match value.parse::<u32>() {
+                    Ok(limit) if (1..=40).contains(&limit) => options.address_limit = limit,
+                    _ => {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: an integer from 1 to 40 is expected",
+                            arg
+                        ));
+                        return None;
+                    }
+                },
+                ("--precision", Some(value)) => match value.parse::<u8>() {
+                    Ok(precision) if precision <= 3 => options.precision = Some(precision),
+                    _ => {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: an integer from 0 to 3 is expected",
+                            arg
+                        ));
+                        return None;
+                    }
+                },
+                ("--watch", Some(value)) => match value.parse::<u64>() {
+                    Ok(secs) if secs >= WATCH_MIN => options.watch = Some(secs),
+                    _ => {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: an integer from {} seconds is expected",
+                            arg, WATCH_MIN
+                        ));
+                        return None;
+                    }
+                },
+                ("--average", Some(value)) => match value.parse::<usize>() {
+                    Ok(count) if (2..=AVERAGE_MAX).contains(&count) => {
+                        options.average = Some(count)
+                    }
+                    _ => {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: an integer from 2 to {} is expected",
+                            arg, AVERAGE_MAX
+                        ));
+                        return None;
+                    }
+                },
+                ("--pressure-unit", Some(value)) => match PressureUnit::parse(value) {
+                    Some(unit) => options.pressure_unit = Some(unit),
+                    None => {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: hpa, mmhg or inhg is expected",
+                            arg
+                        ));
+                        return None;
+                    }
+                },
+                ("--units", Some(value)) => match Units::parse(value) {
+                    Some(units) => options.units = Some(units),
+                    None => {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: metric or imperial is expected",
+                            arg
+                        ));
+                        return None;
+                    }
+                },
+                ("--lang", Some(value)) => {
+                    if !Options::is_lang(value) {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: a language code like en or uk is expected",
+                            arg
+                        ));
+                        return None;
+                    }
+                    options.lang = Some(value.to_owned());
+                }
+                ("--locale", Some(LOCALE_SYSTEM)) => {
+                    options.locale = Some(LOCALE_SYSTEM.to_owned())
+                }
+                ("--locale", Some(value)) => match Options::locale_lang(value) {
+                    Some(lang) => options.locale = Some(lang),
+                    None => {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: a locale like de, uk_UA or system is expected",
+                            arg
+                        ));
+                        return None;
+                    }
+                },
+                ("--fields", Some(value)) => {
+                    let valid = Table::fields();
+                    let fields: Vec<String> = value
+                        .split(',')
+                        .map(|field| field.trim().to_lowercase())
+                        .filter(|field| !field.is_empty())
+                        .collect();
+                    if fields.is_empty() {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: a comma-separated list is expected. Valid fields: {}",
+                            arg,
+                            valid.join(", ")
+                        ));
+                        return None;
+                    }
+                    if let Some(field) =
+                        fields.iter().find(|field| !valid.contains(&field.as_str()))
+                    {
+                        Log::error(&format!(
+                            "Unknown field '{}' of the flag --fields. Valid fields: {}",
+                            field,
+                            valid.join(", ")
+                        ));
+                        return None;
+                    }
+                    options.fields = Some(fields);
+                }
+                ("--format", Some(value)) => match Output::parse(value) {
+                    Some(format) => options.format = Some(format),
+                    None => {
+                        Log::error(&format!(
+                            "Wrong value of the flag {}: text, json, json-lines or csv is expected",
+                            arg
+                        ));
+                        return None;
+                    }
+                },
+                _ => {
+                    Log::error(&format!("Unknown flag: {}", arg));
+                    return None;
+                }
+            }
+        }
+        if options.append && options.output.is_none() {
+            Log::error("The flag --append requires --output <path>");
+            return None;
+        }
+        if options.brief {
+            if options.fields.is_some() {
+                Log::error("The flag --brief can't be combined with --fields");
+                return None;
+            }
+            options.fields = Some(Table::brief());
+        }
+        Some((options, rest))
     }
 
     /// Parsing of the launch parameters
@@ -85,23 +1173,86 @@ impl Init {
     ///
     /// `Command` - The command to launch the application.
     fn parse_args(list: &[String]) -> Command {
-        let first = match list.get(0) {
+        let first = match list.first() {
             None => return Command::Help { error: false },
             Some(first) => first.as_ref(),
         };
+        // `--show` and `--reset` are valid only after `configure <provider>`
+        if first != "configure" && list.iter().any(|arg| arg == "--show" || arg == "--reset") {
+            return Command::Help { error: true };
+        }
+        // `--json` is valid only after `list`
+        if first != "list" && list.iter().any(|arg| arg == "--json") {
+            return Command::Help { error: true };
+        }
         match first {
             "help" => Command::Help { error: false },
+            "version" | "--version" | "-V" => Command::Version,
+            "repl" => Command::Repl,
+            "capabilities" => Command::Capabilities,
+            "doctor" => Command::Doctor,
+            "list" => match list.get(1).map(|arg| arg.as_str()) {
+                Some("--json") if list.len() == 2 => Command::ListJson,
+                None => Command::List,
+                _ => Command::Help { error: true },
+            },
+            // The values can have spaces, e.g. `config set home Kyiv, Ukraine`
+            "config" => match (list.get(1).map(|arg| arg.as_str()), list.get(2)) {
+                (None, _) => Command::Config,
+                (Some("set"), Some(name)) if list.len() > 3 => Command::Setting {
+                    name: name.to_owned(),
+                    value: list[3..].join(" "),
+                },
+                (Some("unset"), Some(name)) if list.len() == 3 => Command::Setting {
+                    name: name.to_owned(),
+                    value: String::new(),
+                },
+                _ => Command::Help { error: true },
+            },
             "configure" => match list.get(1) {
-                Some(provider) => Command::Configure {
-                    provider: provider.to_string(),
+                Some(default) if default == "default" => match list.get(2) {
+                    Some(provider) => Command::SetDefault {
+                        provider: provider.to_string(),
+                    },
+                    None => Command::Help { error: true },
+                },
+                // The address can be quoted or not, e.g. `configure home Kyiv, Ukraine`
+                Some(home) if home == "home" && list.len() > 2 => Command::Setting {
+                    name: "home".to_owned(),
+                    value: list[2..].join(" "),
+                },
+                Some(home) if home == "home" => Command::Help { error: true },
+                // The provider names can be separated by spaces, e.g. `configure order OpenWeather WeatherAPI`
+                Some(order) if order == "order" && list.len() > 2 => Command::Setting {
+                    name: "order".to_owned(),
+                    value: list[2..].join(" "),
+                },
+                Some(order) if order == "order" => Command::Help { error: true },
+                Some(setting) if setting.contains('=') => match setting.split_once('=') {
+                    Some((name, value)) => Command::Setting {
+                        name: name.to_owned(),
+                        value: value.to_owned(),
+                    },
+                    None => Command::Help { error: true },
+                },
+                Some(provider) => match list.get(2) {
+                    Some(show) if show == "--show" => Command::ShowCredentials {
+                        provider: provider.to_string(),
+                    },
+                    Some(reset) if reset == "--reset" => Command::ResetCredentials {
+                        provider: provider.to_string(),
+                    },
+                    _ => Command::Configure {
+                        provider: provider.to_string(),
+                    },
                 },
                 None => Command::List,
             },
             "get" => match Init::parse_get_command(&list[1..]) {
-                Some((provider, address, date)) => Command::Get {
+                Some((provider, address, dates)) => Command::Get {
                     provider,
                     address,
-                    date,
+                    dates,
                 },
                 None => Command::Help { error: true },
             },
@@ -111,127 +1262,253 @@ impl Init {
 
     /// Detail parsing 'get' command.
     ///
-    /// * `parts: &[String]` - Non empty array with launch parameters from `get` command.
+    /// * `parts: &[String]` - Array with launch parameters from `get` command.
     ///
     /// Return
     ///
-    /// `Option<(provider, address, date)>` - Turple with provider, address and date.
+    /// `Option<(provider, address, dates)>` - Turple with provider, address and dates.
     ///   * `Option::None` - Error recognizing the parameters.
     ///   * `Option::Some` - Parameters recognized successfully.
     ///     * `provider: Option<String>` - Weather provider.
-    ///     * `address: String` - The address to which you need to receive a weather forecast.
-    ///     * `date: Date` - Forecast date.
-    fn parse_get_command(parts: &[String]) -> Option<(Option<String>, String, Date)> {
-        // First parameter
-        let first = parts.first();
-        // Last parameter
-        let mut last = if parts.len() > 1 { parts.last() } else { None };
-        // Middle part
-        let middle = if parts.len() > 2 {
-            Some(parts[1..parts.len() - 1].join(" "))
-        } else {
-            last.take().cloned()
-        };
-        match (first, middle, last) {
-            // Nothing
-            (None, _, _) => None,
-            // Only one part
-            (Some(first), None, None) | (Some(first), None, Some(_)) => {
-                if first.starts_with(PROVIDER) || first.starts_with(DATE) {
-                    None
-                } else {
-                    Some((None, first.to_owned(), Date::Now))
+    ///     * `address: String` - The address to which you need to receive a weather forecast,
+    ///       empty - the saved home address.
+    ///     * `dates: Vec<Date>` - Forecast dates in the given order, `now` if none is given.
+    fn parse_get_command(parts: &[String]) -> Option<(Option<String>, String, Vec<Date>)> {
+        let mut parts = parts;
+        let mut provider = None;
+        // The dates before the address and the dates after it in the reverse order
+        let mut head = Vec::new();
+        let mut tail = Vec::new();
+        // `provider=` and `date=` are recognized at both ends of the address in any order,
+        // the parameters inside the address, e.g. `Kyiv date=now Ukraine`, belong to it
+        loop {
+            let (part, rest, dates) = match (parts.first(), parts.last()) {
+                (Some(first), _) if Init::is_param(first, &provider) => {
+                    (first, &parts[1..], &mut head)
                 }
-            }
-            // Two parts
-            (Some(first), Some(middle), None) => {
-                if first.starts_with(PROVIDER) {
-                    if middle.starts_with(DATE) {
-                        None
-                    } else {
-                        Some((Init::set_provider(first), middle, Date::Now))
-                    }
-                } else if middle.starts_with(DATE) {
-                    let dt = match Init::set_date(&middle) {
-                        Date::Error => return None,
-                        dt => dt,
-                    };
-                    Some((None, first.to_owned(), dt))
-                } else {
-                    Some((None, first.to_owned() + " " + &middle, Date::Now))
+                (_, Some(last)) if Init::is_param(last, &provider) => {
+                    (last, &parts[..parts.len() - 1], &mut tail)
                 }
+                _ => break,
+            };
+            if part.starts_with(PROVIDER) {
+                provider = Some(Init::set_provider(part));
+            } else {
+                dates.push(match Init::set_date(part) {
+                    Date::Error => return None,
+                    dt => dt,
+                });
             }
-            // All parts
-            (Some(first), Some(middle), Some(last)) => {
-                if first.starts_with(PROVIDER) {
-                    if last.starts_with(DATE) {
-                        let dt = match Init::set_date(last) {
-                            Date::Error => return None,
-                            dt => dt,
-                        };
-                        Some((Init::set_provider(first), middle, dt))
-                    } else {
-                        Some((Init::set_provider(first), middle + " " + last, Date::Now))
-                    }
-                } else if last.starts_with(DATE) {
-                    let dt = match Init::set_date(last) {
-                        Date::Error => return None,
-                        dt => dt,
-                    };
-                    Some((None, first.to_owned() + " " + &middle, dt))
-                } else {
-                    Some((
-                        None,
-                        first.to_owned() + " " + &middle + " " + last,
-                        Date::Now,
-                    ))
-                }
+            parts = rest;
+        }
+        // The provider left at the end is given twice, e.g. `provider=A provider=B Kyiv`
+        if let Some(part) = [parts.first(), parts.last()]
+            .into_iter()
+            .flatten()
+            .find(|part| part.starts_with(PROVIDER))
+        {
+            Log::error(&format!("The parameter {} is repeated.", part));
+            return None;
+        }
+        let mut dates: Vec<Date> = Vec::new();
+        for date in head.into_iter().chain(tail.into_iter().rev()) {
+            if dates.contains(&date) {
+                Log::error(&format!("The date {} is repeated.", date));
+                return None;
+            }
+            dates.push(date);
+        }
+        if dates.is_empty() {
+            dates.push(Date::Now);
+        }
+        // An empty address is replaced by the saved home address before any request to the geocoder
+        let address = parts.join(" ").trim().to_owned();
+        Some((provider.flatten(), address, dates))
+    }
+
+    /// Checking whether the part is a `date=` parameter or a `provider=` parameter that is not set yet.
+    ///
+    /// * `part: &str` - Part of the `get` command.
+    /// * `provider: &Option<Option<String>>` - The recognized provider.
+    fn is_param(part: &str, provider: &Option<Option<String>>) -> bool {
+        (part.starts_with(PROVIDER) && provider.is_none()) || part.starts_with(DATE)
+    }
+
+    /// Checking for an empty provider.
+    ///
+    /// The provider names have no spaces, the quoted `"provider=Open Weather"` means `OpenWeather`.
+    #[inline]
+    fn set_provider(provider: &str) -> Option<String> {
+        let name: String = provider[PROVIDER.len()..].split_whitespace().collect();
+        if name.is_empty() {
+            None
+        } else {
+            Some(name)
+        }
+    }
+
+    /// Checking for an empty date
+    #[inline]
+    fn set_date(date: &str) -> Date {
+        match Init::read_date(&date[DATE.len()..]) {
+            Ok(date) => date,
+            Err(e) => {
+                Log::error(&e);
+                Date::Error
             }
         }
     }
 
-    /// Checking for an empty provider
-    #[inline]
-    fn set_provider(provider: &str) -> Option<String> {
-        if provider == PROVIDER {
-            None
-        } else {
-            Some(provider[PROVIDER.len()..].to_owned())
+    /// Parsing the value of the `date=` parameter without logging, also for the json.
+    ///
+    /// * `dt: &str` - The value, e.g. `now`, `2023-05-11` or `2023-05-11..2023-05-13`.
+    ///
+    /// Return
+    ///
+    /// `Result<Date, String>` - The date or the error message.
+    fn read_date(dt: &str) -> Result<Date, String> {
+        if dt.is_empty() || dt.eq_ignore_ascii_case("now") {
+            return Ok(Date::Now);
+        }
+        match dt.split_once("..") {
+            Some((start, end)) => match (Init::parse_date(start)?, Init::parse_date(end)?) {
+                (start, end) if start <= end => Ok(Date::Range(start, end)),
+                _ => Err(format!(
+                    "Unable to determine date range: {}. The end is earlier than the start.",
+                    dt
+                )),
+            },
+            None => Init::parse_date(dt).map(Date::Set),
+        }
+    }
+
+    /// Parsing one date in the format `today`, `tomorrow`, `yyyy-mm-dd`, `yyyy-mm-ddThh:mm:ss` or `yyyy-mm-ddThh:mm:ss+hh:mm`
+    fn parse_date(date: &str) -> Result<DateTime<Local>, String> {
+        // The keywords keep the current time like the date without the time
+        if date.eq_ignore_ascii_case("today") {
+            return Ok(Local::now());
+        }
+        if date.eq_ignore_ascii_case("tomorrow") {
+            return Ok(Local::now() + Duration::days(1));
+        }
+        // The offset picks the hour repeated by the DST change
+        if let Ok(dt) = DateTime::parse_from_str(date, DATE_OFFSET_FORMAT) {
+            return Ok(dt.with_timezone(&Local));
+        }
+        let mut dt = date.to_owned();
+        // Add curent time to date without time
+        if dt.len() == 10 {
+            let now: DateTime<Local> = Local::now();
+            dt.push_str(&now.format("T%H:%M:%S").to_string());
+        }
+        match NaiveDateTime::parse_from_str(&dt, "%Y-%m-%dT%H:%M:%S") {
+            Ok(naive) => Local.from_local_datetime(&naive).single().ok_or(format!(
+                "Unable to determine date: {}. The local time is repeated or skipped by the DST change.",
+                dt
+            )),
+            Err(e) => Err(format!("Unable to determine date: {}. Error: {}.", dt, e)),
         }
     }
+}
 
-    /// Checking for an empty date
-    #[inline]
-    fn set_date(date: &str) -> Date {
-        if date == DATE || date.to_lowercase() == format!("{}now", DATE) {
-            Date::Now
-        } else {
-            let mut dt = date[DATE.len()..].to_owned();
-            // Add curent time to date without time
-            if dt.len() == 10 {
-                let now: DateTime<Local> = Local::now();
-                dt.push_str(&now.format("T%H:%M:%S").to_string());
-            }
-            match NaiveDateTime::parse_from_str(&dt, "%Y-%m-%dT%H:%M:%S") {
-                Ok(dt) => match Local.from_local_datetime(&dt).single() {
-                    Some(dt) => Date::Set(dt),
-                    None => Date::Error,
-                },
-                Err(e) => {
-                    println!("Unable to determine date: {}. Error: {}.", dt, e);
-                    Date::Error
-                }
-            }
-        }
+impl Default for Init {
+    fn default() -> Init {
+        Init::new()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use chrono::{Local, NaiveDateTime, TimeZone};
+    use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone};
 
     use super::Init;
-    use crate::init::{Command, Date};
+    use crate::init::{
+        Command, Date, Options, Output, PressureUnit, Settings, Units, DATE_WINDOW, USER_AGENT,
+    };
+    use crate::table::Table;
+
+    #[test]
+    fn test_in_window() {
+        let now = Local.with_ymd_and_hms(2023, 5, 11, 12, 0, 0).unwrap();
+        let days = |days: i64| now + Duration::days(days);
+        let window = |date: Date| date.in_window(&now, DATE_WINDOW, DATE_WINDOW);
+        assert!(window(Date::Now));
+        assert!(window(Date::Set(now)));
+        assert!(window(Date::Set(days(DATE_WINDOW))));
+        assert!(window(Date::Set(days(-DATE_WINDOW))));
+        assert!(!window(Date::Set(days(DATE_WINDOW) + Duration::seconds(1))));
+        assert!(!window(Date::Set(
+            days(-DATE_WINDOW) - Duration::seconds(1)
+        )));
+        let old = Local.with_ymd_and_hms(1850, 1, 1, 12, 0, 0).unwrap();
+        assert!(!window(Date::Set(old)));
+        assert!(!window(Date::Range(days(1), days(DATE_WINDOW + 1))));
+        assert!(window(Date::Range(days(-3), days(3))));
+        // A wider window of the historical provider
+        assert!(Date::Set(old).in_window(&now, 365 * 200, DATE_WINDOW));
+    }
+
+    #[test]
+    fn test_serde() {
+        let start = Local.with_ymd_and_hms(2023, 5, 11, 12, 30, 0).unwrap();
+        let end = Local.with_ymd_and_hms(2023, 5, 13, 0, 0, 0).unwrap();
+        let offset = |dt: &DateTime<Local>| dt.format("%:z").to_string();
+        let dates = [
+            (Date::Now, r#""now""#.to_owned()),
+            (
+                Date::Set(start),
+                format!(r#""2023-05-11T12:30:00{}""#, offset(&start)),
+            ),
+            (
+                Date::Range(start, end),
+                format!(
+                    r#""2023-05-11T12:30:00{}..2023-05-13T00:00:00{}""#,
+                    offset(&start),
+                    offset(&end)
+                ),
+            ),
+        ];
+        for (date, json) in dates {
+            assert_eq!(serde_json::to_string(&date).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Date>(&json).unwrap(), date);
+        }
+        // Without the offset it's the local time, like the parameter
+        assert_eq!(
+            serde_json::from_str::<Date>(r#""2023-05-11T12:30:00""#).unwrap(),
+            Date::Set(start)
+        );
+        // The hour repeated by the DST change in Kyiv, the same local time of two moments
+        let first = r#""2023-10-29T03:30:00+03:00""#;
+        let second = r#""2023-10-29T03:30:00+02:00""#;
+        let (first, second) = (
+            serde_json::from_str::<Date>(first).unwrap(),
+            serde_json::from_str::<Date>(second).unwrap(),
+        );
+        assert_ne!(first, second);
+        for date in [first, second] {
+            let json = serde_json::to_string(&date).unwrap();
+            assert_eq!(serde_json::from_str::<Date>(&json).unwrap(), date);
+        }
+        assert!(serde_json::to_string(&Date::Error).is_err());
+        assert_eq!(serde_json::from_str::<Date>(r#""NOW""#).unwrap(), Date::Now);
+        assert!(serde_json::from_str::<Date>(r#""2023-05-13..2023-05-11""#).is_err());
+        assert!(serde_json::from_str::<Date>(r#""someday""#).is_err());
+        assert!(serde_json::from_str::<Date>("5").is_err());
+    }
+
+    #[test]
+    fn test_in_horizon() {
+        let now = Local.with_ymd_and_hms(2023, 5, 11, 12, 0, 0).unwrap();
+        let today = now.date_naive();
+        let days = |days: i64| now + Duration::days(days);
+        assert!(Date::Now.in_horizon(today, 4));
+        assert!(Date::Set(days(4)).in_horizon(today, 4));
+        assert!(!Date::Set(days(5)).in_horizon(today, 4));
+        assert!(!Date::Set(days(-1)).in_horizon(today, 4));
+        // The range overlapping the forecast is valid
+        assert!(Date::Range(days(-2), days(2)).in_horizon(today, 4));
+        assert!(!Date::Range(days(5), days(7)).in_horizon(today, 4));
+    }
 
     fn setup_args(args: &str) -> Command {
         let args: Vec<String> = args
@@ -242,6 +1519,280 @@ mod tests {
         Init::parse_args(&args)
     }
 
+    fn setup_options(args: &str) -> Option<(Options, Vec<String>)> {
+        let args: Vec<String> = args
+            .split(' ')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect();
+        Init::parse_options(&args)
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(
+            setup_options("get Kyiv"),
+            Some((
+                Options::default(),
+                vec!["get".to_owned(), "Kyiv".to_owned()]
+            ))
+        );
+        assert_eq!(
+            setup_options("--quiet get Kyiv"),
+            Some((
+                Options {
+                    quiet: true,
+                    ..Options::default()
+                },
+                vec!["get".to_owned(), "Kyiv".to_owned()]
+            ))
+        );
+        assert_eq!(
+            setup_options("get Kyiv -q"),
+            Some((
+                Options {
+                    quiet: true,
+                    ..Options::default()
+                },
+                vec!["get".to_owned(), "Kyiv".to_owned()]
+            ))
+        );
+        assert_eq!(
+            setup_options("--geo-ttl=0 get Kyiv"),
+            Some((
+                Options {
+                    geo_ttl: 0,
+                    ..Options::default()
+                },
+                vec!["get".to_owned(), "Kyiv".to_owned()]
+            ))
+        );
+        assert_eq!(setup_options("get Kyiv --geo-ttl=week"), None);
+        assert_eq!(
+            setup_options("--geo-timeout=1 --api-timeout=10 get Kyiv")
+                .map(|(options, _)| (options.geo_timeout(), options.api_timeout())),
+            Some((1, 10))
+        );
+        assert_eq!(
+            setup_options("get Kyiv")
+                .map(|(options, _)| (options.geo_timeout(), options.api_timeout())),
+            Some((3, 3))
+        );
+        assert_eq!(setup_options("get Kyiv --api-timeout=0"), None);
+        assert_eq!(
+            setup_options("--geoip get").map(|(options, _)| options.geoip),
+            Some(true)
+        );
+        assert_eq!(
+            setup_options("--strict-geo get Kyiv").map(|(options, _)| options.strict_geo),
+            Some(true)
+        );
+        assert_eq!(setup_options("get Kyiv --strict-geo=yes"), None);
+        assert_eq!(
+            setup_options("get Kyiv --both-units").map(|(options, _)| options.both_units),
+            Some(true)
+        );
+        assert_eq!(
+            setup_options("--proxy=none get Kyiv").map(|(options, _)| options.proxy),
+            Some(Some("none".to_owned()))
+        );
+        assert_eq!(setup_options("get Kyiv --proxy=proxy.local"), None);
+        assert_eq!(
+            setup_options("--max-age=30 get Kyiv").map(|(options, _)| options.max_age()),
+            Some(30)
+        );
+        assert_eq!(setup_options("get Kyiv --max-age=1h"), None);
+        assert_eq!(
+            setup_options("get Kyiv --astro").map(|(options, _)| options.astro),
+            Some(true)
+        );
+        assert_eq!(
+            setup_options("-v get Kyiv").map(|(options, _)| options.verbose),
+            Some(1)
+        );
+        assert_eq!(
+            setup_options("get Kyiv -vv").map(|(options, _)| options.verbose),
+            Some(2)
+        );
+        assert_eq!(
+            setup_options("-v --verbose -v get Kyiv").map(|(options, _)| options.verbose),
+            Some(2)
+        );
+        assert_eq!(
+            setup_options("--address-limit=5 get Kyiv").map(|(options, _)| options.address_limit),
+            Some(5)
+        );
+        assert_eq!(setup_options("get Kyiv --address-limit=0"), None);
+        assert_eq!(
+            setup_options("--precision=0 get Kyiv"),
+            Some((
+                Options {
+                    precision: Some(0),
+                    ..Options::default()
+                },
+                vec!["get".to_owned(), "Kyiv".to_owned()]
+            ))
+        );
+        assert_eq!(setup_options("get Kyiv --precision=4"), None);
+        assert_eq!(
+            setup_options("--units=imperial --format=json get Kyiv"),
+            Some((
+                Options {
+                    units: Some(Units::Imperial),
+                    format: Some(Output::Json),
+                    ..Options::default()
+                },
+                vec!["get".to_owned(), "Kyiv".to_owned()]
+            ))
+        );
+        assert_eq!(
+            setup_options("--json-lines get - "),
+            setup_options("--format=json-lines get -")
+        );
+        assert_eq!(
+            setup_options("--json-lines get -").map(|(options, _)| options.format()),
+            Some(Output::JsonLines)
+        );
+        assert_eq!(
+            setup_options("--format=csv get Kyiv").map(|(options, _)| options.format()),
+            Some(Output::Csv)
+        );
+        assert_eq!(setup_options("get Kyiv --units=kelvin"), None);
+        assert_eq!(
+            setup_options("--lang=uk get Kyiv"),
+            Some((
+                Options {
+                    lang: Some("uk".to_owned()),
+                    ..Options::default()
+                },
+                vec!["get".to_owned(), "Kyiv".to_owned()]
+            ))
+        );
+        assert_eq!(setup_options("--lang=u&k get Kyiv"), None);
+        assert_eq!(
+            setup_options("get Kyiv --save-json raw.json"),
+            Some((
+                Options {
+                    save_json: Some("raw.json".to_owned()),
+                    ..Options::default()
+                },
+                vec!["get".to_owned(), "Kyiv".to_owned()]
+            ))
+        );
+        assert_eq!(
+            setup_options("--save-json=raw.json get Kyiv"),
+            setup_options("get Kyiv --save-json raw.json")
+        );
+        assert_eq!(setup_options("get Kyiv --save-json"), None);
+        assert_eq!(
+            setup_options("get Kyiv --oneline"),
+            Some((
+                Options {
+                    oneline: true,
+                    ..Options::default()
+                },
+                vec!["get".to_owned(), "Kyiv".to_owned()]
+            ))
+        );
+        assert_eq!(
+            setup_options("--dry-run get Kyiv"),
+            Some((
+                Options {
+                    dry_run: true,
+                    ..Options::default()
+                },
+                vec!["get".to_owned(), "Kyiv".to_owned()]
+            ))
+        );
+        assert_eq!(setup_options("--dry-run=yes get Kyiv"), None);
+        assert_eq!(
+            setup_options("--user-agent=weather/1.0 get Kyiv")
+                .map(|(options, _)| options.user_agent),
+            Some(Some("weather/1.0".to_owned()))
+        );
+        assert_eq!(setup_options("--user-agent= get Kyiv"), None);
+        assert_eq!(
+            setup_options("--fields=temp,Wind,humidity get Kyiv")
+                .map(|(options, _)| options.fields),
+            Some(Some(vec![
+                "temp".to_owned(),
+                "wind".to_owned(),
+                "humidity".to_owned()
+            ]))
+        );
+        assert_eq!(setup_options("--fields=temp,speed get Kyiv"), None);
+        assert_eq!(setup_options("--fields= get Kyiv"), None);
+        assert_eq!(
+            setup_options("--brief get Kyiv").map(|(options, _)| (options.brief, options.fields)),
+            Some((true, Some(Table::brief())))
+        );
+        assert_eq!(setup_options("--brief --fields=temp get Kyiv"), None);
+        assert_eq!(
+            setup_options("get Kyiv --output weather.log --append").map(|(options, rest)| (
+                options.output,
+                options.append,
+                rest.len()
+            )),
+            Some((Some("weather.log".to_owned()), true, 2))
+        );
+        assert_eq!(
+            setup_options("--output=weather.log get Kyiv").map(|(options, _)| options.append),
+            Some(false)
+        );
+        assert_eq!(setup_options("get Kyiv --output"), None);
+        assert_eq!(setup_options("--append get Kyiv"), None);
+        assert_eq!(
+            setup_options("--max-requests=50 get Kyiv").map(|(options, _)| options.max_requests),
+            Some(Some(50))
+        );
+        assert_eq!(setup_options("--max-requests=-1 get Kyiv"), None);
+        assert_eq!(
+            setup_options("get Kyiv --fallback").map(|(options, _)| options.fallback),
+            Some(true)
+        );
+        assert_eq!(
+            setup_options("--graph get Kyiv").map(|(options, _)| options.graph),
+            Some(true)
+        );
+        assert_eq!(
+            setup_options("get Kyiv --explain").map(|(options, _)| options.explain),
+            Some(true)
+        );
+        assert_eq!(
+            setup_options("--units-in-header get Kyiv").map(|(options, _)| options.units_in_header),
+            Some(true)
+        );
+        assert_eq!(
+            setup_options("--average=3 get Kyiv").map(|(options, _)| options.average),
+            Some(Some(3))
+        );
+        assert_eq!(setup_options("--average=1 get Kyiv"), None);
+        assert_eq!(
+            setup_options("--locale=uk_UA.UTF-8 get Kyiv").map(|(options, _)| options.locale),
+            Some(Some("uk".to_owned()))
+        );
+        assert_eq!(
+            setup_options("--locale=system get Kyiv").map(|(options, _)| options.locale),
+            Some(Some("system".to_owned()))
+        );
+        assert_eq!(setup_options("--locale=1,5 get Kyiv"), None);
+        assert_eq!(setup_options("--average=25 get Kyiv"), None);
+        assert_eq!(
+            setup_options("--pressure-unit=mmHg get Kyiv")
+                .map(|(options, _)| options.pressure_unit),
+            Some(Some(PressureUnit::Mmhg))
+        );
+        assert_eq!(setup_options("--pressure-unit=psi get Kyiv"), None);
+        assert_eq!(
+            setup_options("--watch=600 get Kyiv").map(|(options, _)| options.watch),
+            Some(Some(600))
+        );
+        assert_eq!(setup_options("--watch=1 get Kyiv"), None);
+        assert_eq!(Options::locale_lang("uk_UA.UTF-8"), Some("uk".to_owned()));
+        assert_eq!(Options::locale_lang("C.UTF-8"), None);
+        assert_eq!(setup_options("get Kyiv --unknown"), None);
+    }
+
     #[test]
     fn test_parse_args_help() {
         assert_eq!(setup_args(""), Command::Help { error: false });
@@ -253,6 +1804,62 @@ mod tests {
         assert_eq!(setup_args("unknown command"), Command::Help { error: true });
     }
 
+    #[test]
+    fn test_parse_args_version() {
+        assert_eq!(setup_args("version"), Command::Version);
+        assert_eq!(setup_args("capabilities"), Command::Capabilities);
+        assert_eq!(setup_args("doctor"), Command::Doctor);
+        assert_eq!(setup_args("list"), Command::List);
+        assert_eq!(setup_args("config"), Command::Config);
+        assert_eq!(
+            setup_args("config set home Kyiv, Ukraine"),
+            Command::Setting {
+                name: "home".to_owned(),
+                value: "Kyiv, Ukraine".to_owned()
+            }
+        );
+        assert_eq!(
+            setup_args("config unset units"),
+            Command::Setting {
+                name: "units".to_owned(),
+                value: String::new()
+            }
+        );
+        assert_eq!(
+            setup_args("config set units"),
+            Command::Help { error: true }
+        );
+        assert_eq!(setup_args("config show"), Command::Help { error: true });
+        assert_eq!(setup_args("list --json"), Command::ListJson);
+        assert_eq!(setup_args("list Kyiv"), Command::Help { error: true });
+        assert_eq!(setup_args("get Kyiv --json"), Command::Help { error: true });
+        assert_eq!(setup_args("-V"), Command::Version);
+        assert_eq!(
+            setup_options("--version"),
+            Some((Options::default(), vec!["--version".to_owned()]))
+        );
+    }
+
+    #[test]
+    fn test_parse_repl() {
+        assert_eq!(setup_args("repl"), Command::Repl);
+        // A line of the repl is parsed like the launch parameters
+        let line: Vec<String> = "get Kyiv, Ukraine date=now"
+            .split_whitespace()
+            .map(|s| s.to_owned())
+            .collect();
+        let init = Init::parse(&line);
+        assert_eq!(init.args, "get Kyiv, Ukraine date=now");
+        assert_eq!(
+            init.command,
+            Command::Get {
+                provider: None,
+                address: "Kyiv, Ukraine".to_owned(),
+                dates: vec![Date::Now]
+            }
+        );
+    }
+
     #[test]
     fn test_parse_args_configure() {
         assert_eq!(setup_args("configure"), Command::List);
@@ -262,17 +1869,88 @@ mod tests {
                 provider: "AccuWeather".to_owned()
             }
         );
+        assert_eq!(
+            setup_args("configure default AccuWeather"),
+            Command::SetDefault {
+                provider: "AccuWeather".to_owned()
+            }
+        );
+        assert_eq!(
+            setup_args("configure default"),
+            Command::Help { error: true }
+        );
+        assert_eq!(
+            setup_args("configure home Kyiv, Ukraine"),
+            Command::Setting {
+                name: "home".to_owned(),
+                value: "Kyiv, Ukraine".to_owned()
+            }
+        );
+        assert_eq!(setup_args("configure home"), Command::Help { error: true });
+        assert_eq!(
+            setup_args("configure order OpenWeather WeatherAPI"),
+            Command::Setting {
+                name: "order".to_owned(),
+                value: "OpenWeather WeatherAPI".to_owned()
+            }
+        );
+        assert_eq!(setup_args("configure order"), Command::Help { error: true });
+        assert_eq!(
+            setup_args("configure AerisWeather --show"),
+            Command::ShowCredentials {
+                provider: "AerisWeather".to_owned()
+            }
+        );
+        assert_eq!(
+            setup_options("configure AerisWeather --show").map(|(_, rest)| rest.len()),
+            Some(3)
+        );
+        assert_eq!(setup_args("get Kyiv --show"), Command::Help { error: true });
+        assert_eq!(
+            setup_args("configure AerisWeather --reset"),
+            Command::ResetCredentials {
+                provider: "AerisWeather".to_owned()
+            }
+        );
+        assert_eq!(
+            setup_args("get Kyiv --reset"),
+            Command::Help { error: true }
+        );
     }
 
     #[test]
     fn test_parse_args_get() {
-        assert_eq!(setup_args("get"), Command::Help { error: true });
+        // The empty address is the saved home address
+        let home = |provider: Option<&str>| Command::Get {
+            provider: provider.map(|s| s.to_owned()),
+            address: String::new(),
+            dates: vec![Date::Now],
+        };
+        assert_eq!(setup_args("get"), home(None));
+        let args = |list: &[&str]| {
+            let list: Vec<String> = list.iter().map(|s| s.to_string()).collect();
+            Init::parse_args(&list)
+        };
+        assert_eq!(args(&["get", ""]), home(None));
+        assert_eq!(args(&["get", "   "]), home(None));
+        assert_eq!(
+            args(&["get", "provider=OpenWeather", "  ", "date=now"]),
+            home(Some("OpenWeather"))
+        );
         assert_eq!(
             setup_args("get address"),
             Command::Get {
                 provider: None,
                 address: "address".to_owned(),
-                date: Date::Now
+                dates: vec![Date::Now]
+            }
+        );
+        assert_eq!(
+            setup_args("get - date=now"),
+            Command::Get {
+                provider: None,
+                address: "-".to_owned(),
+                dates: vec![Date::Now]
             }
         );
         assert_eq!(
@@ -280,7 +1958,7 @@ mod tests {
             Command::Get {
                 provider: None,
                 address: "some address else".to_owned(),
-                date: Date::Now
+                dates: vec![Date::Now]
             }
         );
         assert_eq!(
@@ -288,7 +1966,7 @@ mod tests {
             Command::Get {
                 provider: None,
                 address: "some address".to_owned(),
-                date: Date::Now
+                dates: vec![Date::Now]
             }
         );
         assert_eq!(
@@ -296,7 +1974,7 @@ mod tests {
             Command::Get {
                 provider: None,
                 address: "some address".to_owned(),
-                date: Date::Now
+                dates: vec![Date::Now]
             }
         );
         assert_eq!(
@@ -304,7 +1982,7 @@ mod tests {
             Command::Get {
                 provider: Some("AccuWeather".to_owned()),
                 address: "some address".to_owned(),
-                date: Date::Now
+                dates: vec![Date::Now]
             }
         );
         assert_eq!(
@@ -312,7 +1990,7 @@ mod tests {
             Command::Get {
                 provider: Some("AccuWeather".to_owned()),
                 address: "some address".to_owned(),
-                date: Date::Now
+                dates: vec![Date::Now]
             }
         );
         assert_eq!(
@@ -320,7 +1998,7 @@ mod tests {
             Command::Get {
                 provider: Some("AccuWeather".to_owned()),
                 address: "some address".to_owned(),
-                date: Date::Now
+                dates: vec![Date::Now]
             }
         );
         assert_eq!(
@@ -328,7 +2006,7 @@ mod tests {
             Command::Get {
                 provider: Some("AccuWeather".to_owned()),
                 address: "some address".to_owned(),
-                date: Date::Set(
+                dates: vec![Date::Set(
                     Local
                         .from_local_datetime(
                             &NaiveDateTime::parse_from_str(
@@ -339,7 +2017,7 @@ mod tests {
                         )
                         .single()
                         .unwrap()
-                )
+                )]
             }
         );
         assert_eq!(
@@ -347,7 +2025,7 @@ mod tests {
             Command::Get {
                 provider: None,
                 address: "some address".to_owned(),
-                date: Date::Now
+                dates: vec![Date::Now]
             }
         );
         assert_eq!(
@@ -355,14 +2033,253 @@ mod tests {
             Command::Get {
                 provider: None,
                 address: "some address".to_owned(),
-                date: Date::Now
+                dates: vec![Date::Now]
             }
         );
     }
-}
 
-impl Default for Init {
-    fn default() -> Init {
-        Init::new()
+    #[test]
+    fn test_parse_get_command_edge_cases() {
+        let get = |list: &[&str]| {
+            let list: Vec<String> = list.iter().map(|s| s.to_string()).collect();
+            Init::parse_get_command(&list)
+        };
+        let day = Local
+            .from_local_datetime(
+                &NaiveDateTime::parse_from_str("2023-05-01T10:00:00", "%Y-%m-%dT%H:%M:%S").unwrap(),
+            )
+            .single()
+            .unwrap();
+        // No address between the provider and the date, the home address is used
+        assert_eq!(
+            get(&["provider=OpenWeather", "date=2023-05-01T10:00:00"]),
+            Some((
+                Some("OpenWeather".to_owned()),
+                String::new(),
+                vec![Date::Set(day)]
+            ))
+        );
+        assert_eq!(
+            get(&["provider=OpenWeather"]),
+            Some((
+                Some("OpenWeather".to_owned()),
+                String::new(),
+                vec![Date::Now]
+            ))
+        );
+        // The date before the address
+        assert_eq!(
+            get(&["date=2023-05-01T10:00:00", "Kyiv"]),
+            Some((None, "Kyiv".to_owned(), vec![Date::Set(day)]))
+        );
+        assert_eq!(
+            get(&["date=2023-05-01T10:00:00", "provider=MetNorway", "Kyiv"]),
+            Some((
+                Some("MetNorway".to_owned()),
+                "Kyiv".to_owned(),
+                vec![Date::Set(day)]
+            ))
+        );
+        // The provider after a single word address
+        assert_eq!(
+            get(&["Kyiv", "provider=MetNorway"]),
+            Some((
+                Some("MetNorway".to_owned()),
+                "Kyiv".to_owned(),
+                vec![Date::Now]
+            ))
+        );
+        assert_eq!(
+            get(&["Kyiv", "provider=MetNorway", "date=now"]),
+            Some((
+                Some("MetNorway".to_owned()),
+                "Kyiv".to_owned(),
+                vec![Date::Now]
+            ))
+        );
+        // `date=` inside the address belongs to it
+        assert_eq!(
+            get(&["Kyiv", "date=now", "Ukraine"]),
+            Some((None, "Kyiv date=now Ukraine".to_owned(), vec![Date::Now]))
+        );
+        assert_eq!(
+            get(&["Update=Street", "5"]),
+            Some((None, "Update=Street 5".to_owned(), vec![Date::Now]))
+        );
+        // The provider names have no spaces
+        assert_eq!(
+            get(&["provider=Open Weather", "Kyiv"]),
+            Some((
+                Some("OpenWeather".to_owned()),
+                "Kyiv".to_owned(),
+                vec![Date::Now]
+            ))
+        );
+        // The repeated parameters
+        assert_eq!(
+            get(&["provider=MetNorway", "provider=OpenWeather", "Kyiv"]),
+            None
+        );
+        assert_eq!(get(&["Kyiv", "date=now", "date=now"]), None);
+        // Several dates in the given order at both ends of the address
+        assert_eq!(
+            get(&["date=2023-05-01T10:00:00", "Kyiv", "date=now"]),
+            Some((None, "Kyiv".to_owned(), vec![Date::Set(day), Date::Now]))
+        );
+        assert_eq!(
+            get(&[
+                "Kyiv",
+                "date=now",
+                "provider=MetNorway",
+                "date=2023-05-01T10:00:00"
+            ]),
+            Some((
+                Some("MetNorway".to_owned()),
+                "Kyiv".to_owned(),
+                vec![Date::Now, Date::Set(day)]
+            ))
+        );
+        assert_eq!(get(&["Kyiv", "date=", "date=now"]), None);
+        assert_eq!(get(&["Kyiv", "date=someday"]), None);
+        // The keywords of the request
+        match get(&["Kyiv", "date=today", "date=tomorrow"]) {
+            Some((None, address, dates)) if address == "Kyiv" => match dates[..] {
+                [Date::Set(today), Date::Set(tomorrow)] => {
+                    assert!((tomorrow - today - Duration::days(1)).num_seconds().abs() < 5)
+                }
+                _ => panic!("Unexpected dates {:?}", dates),
+            },
+            command => panic!("Unexpected command {:?}", command),
+        }
+    }
+
+    #[test]
+    fn test_read_date_keywords() {
+        let near = |date: Result<Date, String>, days: i64| match date {
+            Ok(Date::Set(dt)) => {
+                (dt - (Local::now() + Duration::days(days)))
+                    .num_seconds()
+                    .abs()
+                    < 5
+            }
+            _ => false,
+        };
+        assert!(near(Init::read_date("today"), 0));
+        assert!(near(Init::read_date("Tomorrow"), 1));
+        match Init::read_date("today..tomorrow") {
+            Ok(Date::Range(start, end)) => assert_eq!((end - start).num_days(), 1),
+            date => panic!("Unexpected date {:?}", date),
+        }
+        assert!(Init::read_date("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_parse_args_range() {
+        let day = |dt: &str| {
+            Local
+                .from_local_datetime(
+                    &NaiveDateTime::parse_from_str(dt, "%Y-%m-%dT%H:%M:%S").unwrap(),
+                )
+                .single()
+                .unwrap()
+        };
+        assert_eq!(
+            setup_args("get Kyiv date=2023-05-01T00:00:00..2023-05-05T00:00:00"),
+            Command::Get {
+                provider: None,
+                address: "Kyiv".to_owned(),
+                dates: vec![Date::Range(
+                    day("2023-05-01T00:00:00"),
+                    day("2023-05-05T00:00:00")
+                )]
+            }
+        );
+        match setup_args("get Kyiv date=2023-05-01..2023-05-05") {
+            Command::Get { dates, .. } if dates.len() == 1 => {
+                let Date::Range(start, end) = &dates[0] else {
+                    panic!("Unexpected date {:?}", dates[0]);
+                };
+                assert_eq!(start.format("%Y-%m-%d").to_string(), "2023-05-01");
+                assert_eq!(end.format("%Y-%m-%d").to_string(), "2023-05-05");
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+        assert_eq!(
+            setup_args("get Kyiv date=2023-05-05..2023-05-01"),
+            Command::Help { error: true }
+        );
+        assert_eq!(
+            setup_args("get Kyiv date=2023-05-01.."),
+            Command::Help { error: true }
+        );
+    }
+
+    #[test]
+    fn test_settings() {
+        let mut settings = Settings::default();
+        assert!(settings.parse_line("units=imperial"));
+        assert!(settings.parse_line("precision=2"));
+        assert!(settings.parse_line("format=wrong"));
+        assert!(!settings.parse_line("OpenWeather:key"));
+        assert_eq!(settings.units, Some(Units::Imperial));
+        assert_eq!(settings.precision, Some(2));
+        assert_eq!(settings.format, None);
+        assert_eq!(settings.lines(), vec!["units=imperial", "precision=2"]);
+        assert!(settings.set("theme", "dark").is_err());
+        assert!(settings.set("geocoder-url", "nominatim.local").is_err());
+        assert!(settings
+            .set("geocoder-url", "http://nominatim.local/search")
+            .is_ok());
+        assert!(settings.parse_line("geocoder-url="));
+        assert_eq!(settings.geocoder_url, None);
+        assert!(settings.parse_line("user-agent=weather (me@example.com)"));
+        assert_eq!(
+            settings.lines(),
+            vec![
+                "units=imperial",
+                "precision=2",
+                "user-agent=weather (me@example.com)"
+            ]
+        );
+        assert!(settings.set("user-agent", "weather\u{7}").is_err());
+        assert!(settings.set("user-agent", "погода").is_err());
+        assert!(settings.set("home", "-").is_err());
+        assert!(settings.set("home", "Kyiv,\nUkraine").is_err());
+        assert!(settings.parse_line("home=  Kyiv,   Ukraine "));
+        assert_eq!(settings.home.as_deref(), Some("Kyiv, Ukraine"));
+        assert_eq!(settings.lines().last().unwrap(), "home=Kyiv, Ukraine");
+        assert!(settings.set("api-timeout", "0").is_err());
+        assert!(settings.parse_line("api-timeout=10"));
+        assert_eq!(settings.get("api-timeout").as_deref(), Some("10"));
+        assert_eq!(settings.get("geo-timeout"), None);
+        assert!(settings.parse_line("order=OpenWeather, WeatherAPI  AccuWeather"));
+        assert_eq!(
+            settings.get("order").as_deref(),
+            Some("OpenWeather,WeatherAPI,AccuWeather")
+        );
+        assert!(settings.set("order", " , ").is_ok());
+        assert_eq!(settings.order, None);
+        assert!(settings.set("proxy", "proxy.local:3128").is_err());
+        assert!(settings.parse_line("proxy=http://proxy.local:3128"));
+
+        // Flags take precedence over the saved settings
+        let mut options = Options {
+            precision: Some(0),
+            ..Options::default()
+        };
+        options.apply(&settings);
+        assert_eq!(options.precision(), 0);
+        assert_eq!(options.units(), Units::Imperial);
+        assert_eq!(options.format(), Output::Text);
+        assert_eq!(options.user_agent(), "weather (me@example.com)");
+        assert_eq!(options.api_timeout(), 10);
+        assert_eq!(options.value("geo-timeout").as_deref(), Some("3"));
+        assert_eq!(options.value("home"), None);
+        assert_eq!(options.proxy.as_deref(), Some("http://proxy.local:3128"));
+        assert_eq!(
+            Options::default().value("proxy").as_deref(),
+            Some("environment")
+        );
+        assert_eq!(Options::default().user_agent(), USER_AGENT);
     }
 }
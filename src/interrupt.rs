@@ -0,0 +1,102 @@
+//! The module responsible for stopping `--watch` by Ctrl-C.
+//!
+//! While the forecast is watched, Ctrl-C only raises a flag, the loop checks it between the updates
+//! and returns, e.g. to the `repl` prompt. Otherwise Ctrl-C terminates the application as usual.
+
+use std::{
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Once,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::log::Log;
+
+/// Step of the wait between the updates, the flag is checked after each one.
+const STEP: Duration = Duration::from_millis(100);
+
+/// Exit code of the application terminated by Ctrl-C, as the shells report it.
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// Global flag: true if Ctrl-C was pressed while it's caught.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Global flag: true between `Interrupt::catch` and `Interrupt::release`.
+static CAUGHT: AtomicBool = AtomicBool::new(false);
+
+/// The handler of Ctrl-C is installed only once per process.
+static INSTALL: Once = Once::new();
+
+/// Handler of Ctrl-C, it raises the flag while it's caught and terminates the application otherwise.
+fn on_interrupt() {
+    if CAUGHT.load(Ordering::SeqCst) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    } else {
+        process::exit(EXIT_INTERRUPTED);
+    }
+}
+
+/// Unit struct for the Ctrl-C handling
+pub struct Interrupt;
+
+impl Interrupt {
+    /// Catch Ctrl-C instead of terminating the application.
+    pub fn catch() {
+        INSTALL.call_once(|| {
+            if let Err(e) = ctrlc::set_handler(on_interrupt) {
+                Log::error(&format!(
+                    "Ctrl-C can't be caught, it will terminate the application: {}",
+                    e
+                ));
+            }
+        });
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        CAUGHT.store(true, Ordering::SeqCst);
+    }
+
+    /// Restore the default action of Ctrl-C.
+    pub fn release() {
+        CAUGHT.store(false, Ordering::SeqCst);
+    }
+
+    /// Checking whether Ctrl-C was pressed since `Interrupt::catch`.
+    pub fn is_set() -> bool {
+        INTERRUPTED.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the time passes or Ctrl-C is pressed.
+    ///
+    /// * `time: Duration` - Duration of the wait.
+    ///
+    /// Return
+    ///
+    /// `bool` - False if the wait was interrupted.
+    pub fn sleep(time: Duration) -> bool {
+        let mut left = time;
+        while !left.is_zero() {
+            if Interrupt::is_set() {
+                return false;
+            }
+            let step = left.min(STEP);
+            thread::sleep(step);
+            left -= step;
+        }
+        !Interrupt::is_set()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sleep() {
+        assert!(Interrupt::sleep(Duration::from_millis(10)));
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        assert!(!Interrupt::sleep(Duration::from_secs(60)));
+        INTERRUPTED.store(false, Ordering::SeqCst);
+    }
+}
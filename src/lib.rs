@@ -0,0 +1,44 @@
+//! # Weather
+//!
+//! Elastio Rust Test Task from [link](https://gist.github.com/anelson/0029f620105a19702b5eed5935880a28).
+//!
+//! This library displays weather information for CLI on Windows, Linux, and macOS,
+//! and can also be embedded to get a normalized weather report from any provider.
+//!
+pub mod astro;
+pub mod budget;
+pub mod comfort;
+pub mod format;
+pub mod geo;
+pub mod geoip;
+pub mod graph;
+pub mod help;
+pub mod http;
+pub mod init;
+pub mod interrupt;
+pub mod log;
+pub mod out;
+pub mod provider;
+pub mod table;
+pub mod wind;
+pub mod work;
+
+use init::Date;
+use work::{WeatherError, WeatherReport, Work};
+
+/// Get a normalized weather report.
+///
+/// * `provider: &str` - Weather provider name.
+/// * `address: &str` - The address to which you need to receive a weather forecast.
+/// * `date: Date` - Forecast date.
+///
+/// Return
+///
+/// `Result<WeatherReport, WeatherError>` - Weather report or the reason why it can't be received.
+pub fn fetch_weather(
+    provider: &str,
+    address: &str,
+    date: Date,
+) -> Result<WeatherReport, WeatherError> {
+    Work::new().fetch(Some(provider), address.to_owned(), date)
+}
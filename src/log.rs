@@ -0,0 +1,100 @@
+//! The module responsible for routing diagnostic and error messages to stderr.
+//!
+//! The weather forecast itself is printed to stdout, so the output can be piped without any noise.
+
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use chrono::Local;
+
+/// Global flag: true if diagnostic messages are suppressed (`--quiet`).
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Global verbosity level: 0 - off, 1 - timing of the phases (`-v`), 2 - timing of each request (`-vv`).
+static VERBOSE: AtomicU8 = AtomicU8::new(0);
+
+/// Unit struct for the message sink
+pub struct Log;
+
+impl Log {
+    /// Set verbosity level.
+    ///
+    /// * `quiet: bool` - True: suppress diagnostic messages.
+    pub fn set_quiet(quiet: bool) {
+        QUIET.store(quiet, Ordering::Relaxed);
+    }
+
+    /// Checking whether diagnostic messages are suppressed.
+    pub fn is_quiet() -> bool {
+        QUIET.load(Ordering::Relaxed)
+    }
+
+    /// Set the level of the timing messages.
+    ///
+    /// * `level: u8` - 0 - off, 1 - phases, 2 - each request.
+    pub fn set_verbose(level: u8) {
+        VERBOSE.store(level, Ordering::Relaxed);
+    }
+
+    /// Checking whether the messages of the level are printed.
+    ///
+    /// * `level: u8` - Level of the message.
+    pub fn is_verbose(level: u8) -> bool {
+        level > 0 && VERBOSE.load(Ordering::Relaxed) >= level && !Log::is_quiet()
+    }
+
+    /// Run the phase and report its duration for `-v` or `-vv`.
+    ///
+    /// * `level: u8` - Level of the message: 1 - phase, 2 - single request.
+    /// * `what: &str` - Name of the phase, e.g. `geocoding`.
+    /// * `phase: impl FnOnce() -> T` - The timed code.
+    ///
+    /// Return
+    ///
+    /// `T` - Result of the phase.
+    pub fn timed<T>(level: u8, what: &str, phase: impl FnOnce() -> T) -> T {
+        if !Log::is_verbose(level) {
+            return phase();
+        }
+        let start = Local::now();
+        let result = phase();
+        let duration = Local::now() - start;
+        eprintln!("{}", Log::timing(what, duration.num_milliseconds()));
+        result
+    }
+
+    /// Text of the timing message.
+    ///
+    /// * `what: &str` - Name of the phase.
+    /// * `ms: i64` - Duration in milliseconds.
+    fn timing(what: &str, ms: i64) -> String {
+        format!("Timing: {} {} ms.", what, ms)
+    }
+
+    /// Diagnostic message (progress, network details, etc.). Suppressed by `--quiet`.
+    ///
+    /// * `text: &str` - Message text.
+    pub fn info(text: &str) {
+        if !Log::is_quiet() {
+            eprintln!("{}", text);
+        }
+    }
+
+    /// Error message. Always printed.
+    ///
+    /// * `text: &str` - Message text.
+    pub fn error(text: &str) {
+        eprintln!("{}", text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timed() {
+        assert_eq!(Log::timing("geocoding", 120), "Timing: geocoding 120 ms.");
+        assert!(!Log::is_verbose(0));
+        assert_eq!(Log::timed(2, "parsing", || 42), 42);
+    }
+}
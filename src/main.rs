@@ -4,33 +4,23 @@
 //!
 //! This application displays weather information for CLI on Windows, Linux, and macOS.
 //!
-pub mod geo;
-pub mod help;
-pub mod init;
-pub mod provider;
-pub mod wind;
-pub mod work;
-
-use init::Init;
-
-use crate::{help::Help, work::Work};
+use weather::{
+    help::Help,
+    init::{self, Init},
+    work::Work,
+};
 
 /// Program entry point
 fn main() {
     let init = Init::new();
     match init.command {
         init::Command::Help { error } => Help::show(error, &init.args),
-        com => {
-            let mut work = Work::new();
-            match com {
-                init::Command::List => work.list(),
-                init::Command::Configure { provider } => work.configure(provider),
-                init::Command::Get {
-                    provider,
-                    address,
-                    date,
-                } => work.get(provider, address, date),
-                _ => {}
+        init::Command::Version => Help::version(),
+        init::Command::Repl => Work::new().repl(),
+        command => {
+            // The failed request is reported by the exit code, e.g. for the json consumers
+            if !Work::new().run(command) {
+                std::process::exit(1);
             }
         }
     }
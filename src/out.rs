@@ -0,0 +1,119 @@
+//! The module responsible for the output of the weather forecast: stdout or the `--output` file.
+//!
+//! The forecast is collected to a buffer while the `--output` file is written, so the providers
+//! display it the same way in both cases.
+
+use std::{fs::OpenOptions, io::Write, sync::Mutex};
+
+use chrono::Local;
+
+use crate::log::Log;
+
+/// Global buffer of the forecast, `None` - the forecast is printed to stdout.
+static BUFFER: Mutex<Option<String>> = Mutex::new(None);
+
+/// Unit struct for the forecast sink
+pub struct Out;
+
+impl Out {
+    /// Start collecting the forecast instead of printing it.
+    pub fn capture() {
+        match BUFFER.lock() {
+            Ok(mut buffer) => *buffer = Some(String::new()),
+            Err(e) => *e.into_inner() = Some(String::new()),
+        }
+    }
+
+    /// Stop collecting the forecast.
+    ///
+    /// Return
+    ///
+    /// `Option<String>` - The collected forecast, `None` if it wasn't collected.
+    pub fn take() -> Option<String> {
+        match BUFFER.lock() {
+            Ok(mut buffer) => buffer.take(),
+            Err(e) => e.into_inner().take(),
+        }
+    }
+
+    /// Write the collected forecast to the `--output` file.
+    ///
+    /// * `path: &str` - File path.
+    /// * `append: bool` - True: the forecast is appended, so the file is a log.
+    ///   False: the file is rewritten.
+    /// * `stamp: bool` - The appended forecast starts with a `# <time>` line.
+    /// * `text: &str` - The forecast.
+    pub fn save(path: &str, append: bool, stamp: bool, text: &str) {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path);
+        let res = file.and_then(|mut file| match append && stamp {
+            true => write!(
+                file,
+                "# {}\n{}",
+                Local::now().format("%Y-%m-%d %H:%M:%S (%:z)"),
+                text
+            ),
+            false => write!(file, "{}", text),
+        });
+        if let Err(e) = res {
+            Log::error(&format!(
+                "Unable to write the forecast to {}. Error: {}",
+                path, e
+            ));
+        }
+    }
+
+    /// Line of the forecast.
+    ///
+    /// * `text: &str` - Line text without the line feed.
+    pub fn line(text: &str) {
+        let mut buffer = match BUFFER.lock() {
+            Ok(buffer) => buffer,
+            Err(e) => e.into_inner(),
+        };
+        match buffer.as_mut() {
+            Some(buffer) => {
+                buffer.push_str(text);
+                buffer.push('\n');
+            }
+            None => println!("{}", text),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Out;
+
+    #[test]
+    fn test_capture() {
+        Out::capture();
+        Out::line("Temperature: 18.5°C");
+        Out::line("");
+        assert_eq!(Out::take(), Some("Temperature: 18.5°C\n\n".to_owned()));
+        assert_eq!(Out::take(), None);
+    }
+
+    #[test]
+    fn test_save() {
+        let file = std::env::temp_dir().join(format!("weather_out_{}.txt", std::process::id()));
+        let file = file.to_str().unwrap();
+        Out::save(file, false, true, "first\n");
+        Out::save(file, true, true, "second\n");
+        let text = std::fs::read_to_string(file).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "first");
+        assert!(lines[1].starts_with("# "));
+        assert_eq!(lines[2], "second");
+        Out::save(file, false, true, "third\n");
+        assert_eq!(std::fs::read_to_string(file).unwrap(), "third\n");
+        Out::save(file, true, false, "fourth\n");
+        assert_eq!(std::fs::read_to_string(file).unwrap(), "third\nfourth\n");
+        std::fs::remove_file(file).unwrap();
+    }
+}
@@ -3,25 +3,68 @@
 //!
 
 use std::{
+    collections::HashMap,
+    env, fs,
     io::{stdin, stdout, Write},
-    time::Duration,
 };
 
-use chrono::{DateTime, Local, TimeZone, Utc};
-use reqwest::blocking::Client;
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 
-use crate::{geo::Geo, init::Date, wind::WindDeg, work::Provider};
+use crate::{
+    astro::AstroReport,
+    budget::Budget,
+    format::Format,
+    geo::Geo,
+    http::{Http, HttpClient, HttpError},
+    init::{Date, Options},
+    log::Log,
+    provider::{closest_item, key_rows},
+    wind::WindDeg,
+    work::{Capability, Provider, WeatherReport},
+};
+
+/// Address prefix of the known AccuWeather location key, e.g. `key:324505`.
+const LOCATION_KEY: &str = "key:";
+
+/// Unit system of the parsed values: the sub-object of the current conditions
+/// (`Temperature.Metric.Value`) and the `metric=true` parameter of the daily forecast.
+const UNIT_SYSTEM: &str = "Metric";
+
+/// File with the cached location keys of the coordinates.
+const LOCATION_FILE: &str = "accuweather.json";
+
+/// Lifetime of the cached location keys, days. The keys of the cities don't change.
+const LOCATION_TTL: i64 = 90;
+
+/// Location key cache entry.
+///
+/// * `time: i64` - Time of the request to the server, unix timestamp.
+/// * `id: String` - AccuWeather location key.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct LocationCache {
+    /// Time of the request to the server, unix timestamp.
+    time: i64,
+    /// AccuWeather location key, it isn't always a number.
+    id: String,
+}
 
 /// Describes 'AccuWeather' credentials.
 ///
 /// * `name: &'static str` - Provider name.
+/// * `client: Box<dyn HttpClient>` - Source of the server answers.
 /// * `key: Option<String>` - Api key.
+/// * `env_key: Option<String>` - Api key from the `WEATHER_ACCUWEATHER_KEY` environment variable.
 pub struct AccuWeather {
     /// Provider name.
     name: &'static str,
+    /// Source of the server answers.
+    client: Box<dyn HttpClient>,
     /// Api key.
     key: Option<String>,
+    /// Api key from the environment variable, takes precedence over `key`.
+    env_key: Option<String>,
 }
 
 /// AccuWeather data format for current item
@@ -35,14 +78,12 @@ struct AccuWeatherItemCurrent {
     geo: Geo,
     /// Phrase description of the current weather condition
     weathertext: Option<String>,
-    /// Flag indicating the presence or absence of precipitation.
-    hasprecipitation: Option<bool>,
-    /// The type of precipitation
-    precipitationtype: Option<String>,
     /// Temperature
     temperature: Option<f32>,
     /// RealFeel temperature
     realfeeltemperature: Option<f32>,
+    /// Wind chill temperature
+    windchill: Option<f32>,
     /// Relative humidity
     relativehumidity: Option<u32>,
     /// Dew point temperature
@@ -63,6 +104,8 @@ struct AccuWeatherItemCurrent {
     cloudcover: Option<u8>,
     /// Atmospheric pressure
     pressure: Option<f32>,
+    /// UTC offset of the location, the system timezone is used when it's unknown
+    offset: Option<FixedOffset>,
 }
 
 /// AccuWeather data format for forecast item
@@ -78,6 +121,8 @@ struct AccuWeatherItemForecast {
     sunrise: Option<DateTime<Local>>,
     /// Sun set
     sunset: Option<DateTime<Local>>,
+    /// UTC offset of the location, the system timezone is used when it's unknown
+    offset: Option<FixedOffset>,
     /// Temperature minimum
     temp_min: Option<f32>,
     /// Temperature maximum
@@ -86,10 +131,6 @@ struct AccuWeatherItemForecast {
     realfeel_min: Option<f32>,
     /// RealFeel temperature maximum
     realfeel_max: Option<f32>,
-    /// Daytime Flag indicating the presence or absence of precipitation
-    day_hasprecipitation: Option<bool>,
-    /// Daytime the type of precipitation
-    day_precipitationtype: Option<String>,
     /// Daytime description
     day_longphrase: Option<String>,
     /// Daytime Rain probability
@@ -110,22 +151,12 @@ struct AccuWeatherItemForecast {
     day_snow: Option<f32>,
     /// Daytime cloud cover
     day_cloudcover: Option<u32>,
-    /// Night Flag indicating the presence or absence of precipitation
-    night_hasprecipitation: Option<bool>,
-    /// Night the type of precipitation
-    night_precipitationtype: Option<String>,
     /// Night description
     night_longphrase: Option<String>,
     /// Night Rain probability
     night_rainprobability: Option<u32>,
     /// Night Snow probability
     night_snowprobability: Option<u32>,
-    /// Night Wind Speed
-    night_speed: Option<f32>,
-    /// Night Wind direction in Azimuth degrees
-    night_deg: Option<u16>,
-    /// Wind direction (meteorological)
-    night_dir: WindDeg,
     /// Night Wind gust speed
     night_gust: Option<f32>,
     /// Night Rain volume
@@ -136,135 +167,237 @@ struct AccuWeatherItemForecast {
     night_cloudcover: Option<u32>,
 }
 
+/// AccuWeather result: current conditions or daily forecast
+#[derive(Debug)]
+enum AccuWeatherItem {
+    /// Current conditions for `Date::Now`
+    Current(AccuWeatherItemCurrent),
+    /// Daily forecast for `Date::Set`
+    Forecast(AccuWeatherItemForecast),
+}
+
 impl AccuWeather {
     /// Create new empty provider
     pub fn new() -> AccuWeather {
         AccuWeather {
             name: "AccuWeather",
+            client: Box::new(Http),
             key: None,
+            env_key: None,
         }
     }
 
     /// Load data from provider
-    fn get_json(&self, url: &str) -> Option<String> {
-        // Client for url query
-        let client = match Client::builder().timeout(Duration::from_secs(3)).build() {
-            Ok(c) => c,
+    fn get_json(&self, url: &str) -> Option<Value> {
+        match Budget::get_json(self.name, self.client.as_ref(), url, &[]) {
+            Ok(json) => Some(json),
             Err(e) => {
-                println!("The following error occurred: {}", e);
-                return None;
-            }
-        };
-
-        let json_str = match client.get(url).send() {
-            Ok(s) => {
-                let status = s.status();
-                if status != 200 {
-                    println!("Error connecting to {}. Status code: {}", &url, status);
-                    return None;
-                }
-                match s.text() {
-                    Ok(s) => s,
-                    Err(e) => {
-                        println!("Error getting answer from {}. Error text: {}", &url, e);
-                        return None;
-                    }
-                }
-            }
-            Err(e) => {
-                println!("Error connecting to {}. Error text: {}", &url, e);
-                return None;
+                e.log(Some(self.name));
+                None
             }
-        };
-        Some(json_str)
+        }
     }
 
     /// Get citi ID
-    fn get_id(&self, address: &str) -> Option<(u32, Geo)> {
-        let key = match &self.key {
+    fn get_id(&self, address: &str) -> Option<(String, Geo)> {
+        // The known location key, e.g. `key:324505`, doesn't need any lookups
+        if let Some(id) = address.strip_prefix(LOCATION_KEY) {
+            let id = id.trim();
+            if !AccuWeather::is_location_key(id) {
+                Log::error(&format!("Wrong AccuWeather location key: {}", id));
+                return None;
+            }
+            return Some((
+                id.to_owned(),
+                Geo {
+                    lat: String::new(),
+                    lon: String::new(),
+                    address: format!("AccuWeather location {}", id),
+                    importance: None,
+                },
+            ));
+        }
+        let key = match self.env_key.as_ref().or(self.key.as_ref()) {
             Some(key) => key,
             None => {
-                println!("OpenWeather server API access key is not set. Please install it first.");
+                Log::error(
+                    "OpenWeather server API access key is not set. Please install it first.",
+                );
                 return None;
             }
         };
         // Find geo coordinates by address
-        let mut geo = Geo::get(address)?;
-        let geo = match geo.pop() {
-            Some(geo) => geo,
+        let geo = Geo::find(address)?;
+        let coords = format!("{},{}", geo.lat, geo.lon);
+        if let Some(id) = AccuWeather::from_cache(LOCATION_FILE, &coords) {
+            return Some((id, geo));
+        }
+        let url = AccuWeather::location_url(&geo, key);
+        // Get city ID
+        let json = match self.get_json(&url) {
+            Some(json) => json,
+            // The location key is unknown, the plan of the requests goes on with a placeholder
+            None if Options::get().dry_run => return Some(("0".to_owned(), geo)),
+            None => return None,
+        };
+        let id = match AccuWeather::location_key(&json) {
+            Some(id) => id,
             None => {
-                println!("Sorry, we couldn't find your address: {}", address);
+                Log::info("The AccuWeather server did not provide the location key");
                 return None;
             }
         };
-        let url = format!(
-            "https://dataservice.accuweather.com/locations/v1/cities/geoposition/search?apikey={}&q={},{}",
-            key, geo.lat, geo.lon
-        );
-        // Get city ID
-        let json_str = self.get_json(&url)?;
+        AccuWeather::to_cache(LOCATION_FILE, &coords, &id);
+        Some((id, geo))
+    }
 
-        // Parse json
-        let json: Map<String, Value> = match serde_json::from_str(&json_str) {
-            Ok(json) => json,
+    /// Url of the location search by the coordinates.
+    ///
+    /// * `geo: &Geo` - The place.
+    /// * `key: &str` - API key.
+    fn location_url(geo: &Geo, key: &str) -> String {
+        format!(
+            "https://dataservice.accuweather.com/locations/v1/cities/geoposition/search?apikey={}&q={},{}&language={}",
+            key, geo.lat, geo.lon, Options::get().lang()
+        )
+    }
+
+    /// Get the location key from the answer of the location search.
+    ///
+    /// The server answers with an object, some queries return an array of the locations,
+    /// the first one is used then. The key is usually a number, but not in every region.
+    ///
+    /// * `json: &Value` - Answer of the `geoposition/search` request.
+    fn location_key(json: &Value) -> Option<String> {
+        let location = match json {
+            Value::Array(list) => list.first()?,
+            json => json,
+        };
+        let id = match location.get("Key")? {
+            Value::String(id) => id.trim().to_owned(),
+            Value::Number(id) => id.to_string(),
+            _ => return None,
+        };
+        AccuWeather::is_location_key(&id).then_some(id)
+    }
+
+    /// Check the location key, it's inserted into the url path.
+    ///
+    /// * `id: &str` - Location key, e.g. `324505`.
+    fn is_location_key(id: &str) -> bool {
+        !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    }
+
+    /// Load the location key cache file.
+    fn load_cache(file: &str) -> HashMap<String, LocationCache> {
+        match fs::read_to_string(file) {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_default(),
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    /// Get the cached location key if it isn't older than `LOCATION_TTL` days.
+    ///
+    /// * `file: &str` - Cache file.
+    /// * `coords: &str` - Coordinates of the place, e.g. `50.45,30.52`.
+    fn from_cache(file: &str, coords: &str) -> Option<String> {
+        let cache = AccuWeather::load_cache(file).remove(coords)?;
+        let age = Utc::now().timestamp() - cache.time;
+        if (0..LOCATION_TTL * 24 * 3600).contains(&age) {
+            Some(cache.id)
+        } else {
+            None
+        }
+    }
+
+    /// Save the location key to the cache, nothing is saved for `--dry-run`.
+    ///
+    /// * `file: &str` - Cache file.
+    /// * `coords: &str` - Coordinates of the place, e.g. `50.45,30.52`.
+    /// * `id: &str` - AccuWeather location key.
+    fn to_cache(file: &str, coords: &str, id: &str) {
+        if Options::get().dry_run {
+            return;
+        }
+        let mut cache = AccuWeather::load_cache(file);
+        cache.insert(
+            coords.to_owned(),
+            LocationCache {
+                time: Utc::now().timestamp(),
+                id: id.to_owned(),
+            },
+        );
+        let data = match serde_json::to_string(&cache) {
+            Ok(data) => data,
             Err(e) => {
-                println!(
-                    "Unable to recognize json response from server. Error text: {}",
+                Log::info(&format!(
+                    "Unable to save the AccuWeather location cache. Error: {}.",
                     e
-                );
-                return None;
+                ));
+                return;
             }
         };
-        let id = match json.get("Key")?.as_str()?.parse::<u32>() {
-            Ok(id) => id,
-            Err(_) => return None,
-        };
-        Some((id, geo))
+        if let Err(e) = fs::write(file, data) {
+            Log::info(&format!(
+                "Unable to save the AccuWeather location cache. Error: {}.",
+                e
+            ));
+        }
     }
 
     /// Getting weather forecast for now
     fn get_now(&self, address: String) -> Option<AccuWeatherItemCurrent> {
         let (id, geo) = self.get_id(&address)?;
-        let key = self.key.as_ref()?;
+        let key = self.env_key.as_ref().or(self.key.as_ref())?;
         let url = format!(
-            "https://dataservice.accuweather.com/currentconditions/v1/{}?details=true&apikey={}",
-            id, key
+            "https://dataservice.accuweather.com/currentconditions/v1/{}?details=true&apikey={}&language={}",
+            id, key, Options::get().lang()
         );
-        let json_str = self.get_json(&url)?;
+        let json = self.get_json(&url)?;
 
         // Parse json
-        let json: Vec<Value> = match serde_json::from_str(&json_str) {
+        let json: Vec<Value> = match serde_json::from_value(json) {
             Ok(json) => json,
             Err(e) => {
-                println!(
+                Log::info(&format!(
                     "Unable to recognize json response from server. Error text: {}",
                     e
-                );
+                ));
                 return None;
             }
         };
-        let map = json.get(0)?.as_object()?;
+        let map = json.first()?.as_object()?;
         self.detect_now(map, geo, address)
     }
 
     /// Getting weather forecast for 'date'
     fn get_date(&self, address: String, date: &DateTime<Local>) -> Option<AccuWeatherItemForecast> {
+        let list = self.get_list(address)?;
+        // Daily forecast: find item with the closest date
+        closest_item(list, date, false, |item| item.date)
+    }
+
+    /// Getting all items of weather forecast
+    fn get_list(&self, address: String) -> Option<Vec<AccuWeatherItemForecast>> {
         let (id, geo) = self.get_id(&address)?;
-        let key = self.key.as_ref()?;
+        let key = self.env_key.as_ref().or(self.key.as_ref())?;
         let url = format!(
-            "https://dataservice.accuweather.com/forecasts/v1/daily/5day/{}?details=true&metric=true&apikey={}",
-            id, key
+            "https://dataservice.accuweather.com/forecasts/v1/daily/5day/{}?details=true&metric={}&apikey={}&language={}",
+            id, UNIT_SYSTEM == "Metric", key, Options::get().lang()
         );
-        let json_str = self.get_json(&url)?;
+        let json = self.get_json(&url)?;
 
         // Parse json
-        let items: Map<String, Value> = match serde_json::from_str(&json_str) {
+        let items: Map<String, Value> = match serde_json::from_value(json) {
             Ok(json) => json,
             Err(e) => {
-                println!(
+                Log::info(&format!(
                     "Unable to recognize json response from server. Error text: {}",
                     e
-                );
+                ));
                 return None;
             }
         };
@@ -273,7 +406,7 @@ impl AccuWeather {
             .get("DailyForecasts")
             .and_then(|i| i.as_array())
             .or_else(|| {
-                println!("The AccuWeather server did not provide weather forecast data");
+                Log::info("The AccuWeather server did not provide weather forecast data");
                 None
             })?;
         // Load all AccuWeatherItemForecast to vector
@@ -289,13 +422,66 @@ impl AccuWeather {
         if list.is_empty() {
             return None;
         }
-        // Find item with the closest date
-        list.into_iter().min_by(|item_a, item_b| {
-            let diff_a = item_a.date.signed_duration_since(*date).num_seconds().abs();
-            let diff_b = item_b.date.signed_duration_since(*date).num_seconds().abs();
+        Some(list)
+    }
 
-            diff_a.cmp(&diff_b)
-        })
+    /// Getting weather forecast for `date`
+    fn get_item(&self, address: String, date: &Date) -> Option<AccuWeatherItem> {
+        match date {
+            Date::Now => self.get_now(address).map(AccuWeatherItem::Current),
+            Date::Set(dt) => self.get_date(address, dt).map(AccuWeatherItem::Forecast),
+            Date::Error | Date::Range(..) => None,
+        }
+    }
+
+    /// UTC offset of the location from its local time, e.g. `"Date": "2023-05-11T07:00:00+03:00"`.
+    ///
+    /// * `value: Option<&Value>` - Local time of the location.
+    fn offset(value: Option<&Value>) -> Option<FixedOffset> {
+        value
+            .and_then(|s| s.as_str())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| *t.offset())
+    }
+
+    /// Measure of the current conditions, e.g. `Temperature.Metric.Value`.
+    ///
+    /// The current conditions contain both unit systems, the `UNIT_SYSTEM` one is read.
+    ///
+    /// * `value: Option<&Value>` - Measure object with `Metric` and `Imperial` sub-objects.
+    fn current_value(value: Option<&Value>) -> Option<f32> {
+        value
+            .and_then(|m| m.get(UNIT_SYSTEM))
+            .and_then(|m| m.get("Value"))
+            .and_then(|s| s.as_f64())
+            .map(|s| s as f32)
+    }
+
+    /// Measure of the daily forecast in metric units, e.g. `Temperature.Minimum.Value`.
+    ///
+    /// The daily forecast contains the only unit system chosen by the `metric` parameter of the request,
+    /// so the value is converted by its `Unit` field. A value without `Unit` is considered metric.
+    ///
+    /// * `value: Option<&Value>` - Measure object with `Value` and `Unit` fields.
+    /// * `unit: &str` - Expected metric unit: `C`, `km/h`, `mm` or `cm`.
+    fn forecast_value(value: Option<&Value>, unit: &str) -> Option<f32> {
+        let value = value?;
+        let number = value.get("Value").and_then(|s| s.as_f64())? as f32;
+        let found = value.get("Unit").and_then(|s| s.as_str()).unwrap_or(unit);
+        match (found, unit) {
+            (found, unit) if found == unit => Some(number),
+            ("F", "C") => Some((number - 32.0) * 5.0 / 9.0),
+            ("mi/h", "km/h") => Some(number * 1.609_344),
+            ("in", "mm") => Some(number * 25.4),
+            ("in", "cm") => Some(number * 2.54),
+            (found, unit) => {
+                Log::info(&format!(
+                    "AccuWeather: unexpected unit '{}' instead of '{}'",
+                    found, unit
+                ));
+                None
+            }
+        }
     }
 
     /// Parse json answer from server
@@ -322,37 +508,33 @@ impl AccuWeather {
             .and_then(|s| s.as_i64())
             .and_then(|t| Utc.timestamp_opt(t, 0).single())
             .map(|t| Local.from_utc_datetime(&t.naive_utc()));
-        let temp_min = items
-            .get("Temperature")
-            .and_then(|m| m.get("Minimum"))
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
-        let temp_max = items
-            .get("Temperature")
-            .and_then(|m| m.get("Maximum"))
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
-        let realfeel_min = items
-            .get("RealFeelTemperature")
-            .and_then(|m| m.get("Minimum"))
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
-        let realfeel_max = items
-            .get("RealFeelTemperature")
-            .and_then(|m| m.get("Maximum"))
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
+        let temp_min = AccuWeather::forecast_value(
+            items.get("Temperature").and_then(|m| m.get("Minimum")),
+            "C",
+        );
+        let temp_max = AccuWeather::forecast_value(
+            items.get("Temperature").and_then(|m| m.get("Maximum")),
+            "C",
+        );
+        let realfeel_min = AccuWeather::forecast_value(
+            items
+                .get("RealFeelTemperature")
+                .and_then(|m| m.get("Minimum")),
+            "C",
+        );
+        let realfeel_max = AccuWeather::forecast_value(
+            items
+                .get("RealFeelTemperature")
+                .and_then(|m| m.get("Maximum")),
+            "C",
+        );
 
-        let day = items.get("Day").and_then(|s| s.as_object())?;
-        let day_hasprecipitation = day.get("HasPrecipitation").and_then(|s| s.as_bool());
-        let day_precipitationtype = day
-            .get("PrecipitationType")
-            .and_then(|s| s.as_str())
-            .map(|s| s.to_owned());
+        // Some answers omit `Day` or `Night` for the last day, the item keeps the other values
+        let empty = Map::new();
+        let day = items
+            .get("Day")
+            .and_then(|s| s.as_object())
+            .unwrap_or(&empty);
         let day_longphrase = day
             .get("LongPhrase")
             .and_then(|s| s.as_str())
@@ -365,46 +547,28 @@ impl AccuWeather {
             .get("SnowProbability")
             .and_then(|s| s.as_u64())
             .map(|s| s as u32);
-        let day_speed = day
-            .get("Wind")
-            .and_then(|m| m.get("Speed"))
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
+        let day_speed =
+            AccuWeather::forecast_value(day.get("Wind").and_then(|m| m.get("Speed")), "km/h");
         let day_deg = day
             .get("Wind")
             .and_then(|m| m.get("Direction"))
             .and_then(|m| m.get("Degrees"))
-            .and_then(|s| s.as_u64())
-            .map(|s| s as u16);
-        let day_dir = WindDeg::get(day_deg);
-        let day_gust = day
-            .get("WindGust")
-            .and_then(|m| m.get("Speed"))
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
-        let day_rain = day
-            .get("Rain")
-            .and_then(|m| m.get("Value"))
             .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
-        let day_snow = day
-            .get("Snow")
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
+            .and_then(WindDeg::normalize);
+        let day_dir = WindDeg::get(day_deg);
+        let day_gust =
+            AccuWeather::forecast_value(day.get("WindGust").and_then(|m| m.get("Speed")), "km/h");
+        let day_rain = AccuWeather::forecast_value(day.get("Rain"), "mm");
+        let day_snow = AccuWeather::forecast_value(day.get("Snow"), "cm");
         let day_cloudcover = day
             .get("CloudCover")
             .and_then(|s| s.as_u64())
             .map(|s| s as u32);
 
-        let night = items.get("Night").and_then(|s| s.as_object())?;
-        let night_hasprecipitation = night.get("HasPrecipitation").and_then(|s| s.as_bool());
-        let night_precipitationtype = night
-            .get("PrecipitationType")
-            .and_then(|s| s.as_str())
-            .map(|s| s.to_owned());
+        let night = items
+            .get("Night")
+            .and_then(|s| s.as_object())
+            .unwrap_or(&empty);
         let night_longphrase = night
             .get("LongPhrase")
             .and_then(|s| s.as_str())
@@ -417,35 +581,10 @@ impl AccuWeather {
             .get("SnowProbability")
             .and_then(|s| s.as_u64())
             .map(|s| s as u32);
-        let night_speed = night
-            .get("Wind")
-            .and_then(|m| m.get("Speed"))
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
-        let night_deg = night
-            .get("Wind")
-            .and_then(|m| m.get("Direction"))
-            .and_then(|m| m.get("Degrees"))
-            .and_then(|s| s.as_u64())
-            .map(|s| s as u16);
-        let night_dir = WindDeg::get(night_deg);
-        let night_gust = night
-            .get("WindGust")
-            .and_then(|m| m.get("Speed"))
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
-        let night_rain = night
-            .get("Rain")
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
-        let night_snow = night
-            .get("Snow")
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
+        let night_gust =
+            AccuWeather::forecast_value(night.get("WindGust").and_then(|m| m.get("Speed")), "km/h");
+        let night_rain = AccuWeather::forecast_value(night.get("Rain"), "mm");
+        let night_snow = AccuWeather::forecast_value(night.get("Snow"), "cm");
         let night_cloudcover = night
             .get("CloudCover")
             .and_then(|s| s.as_u64())
@@ -457,12 +596,11 @@ impl AccuWeather {
             geo,
             sunrise,
             sunset,
+            offset: AccuWeather::offset(items.get("Date")),
             temp_min,
             temp_max,
             realfeel_min,
             realfeel_max,
-            day_hasprecipitation,
-            day_precipitationtype,
             day_longphrase,
             day_rainprobability,
             day_snowprobability,
@@ -473,14 +611,9 @@ impl AccuWeather {
             day_rain,
             day_snow,
             day_cloudcover,
-            night_hasprecipitation,
-            night_precipitationtype,
             night_longphrase,
             night_rainprobability,
             night_snowprobability,
-            night_speed,
-            night_deg,
-            night_dir,
             night_gust,
             night_rain,
             night_snow,
@@ -504,84 +637,43 @@ impl AccuWeather {
             .get("WeatherText")
             .and_then(|s| s.as_str())
             .map(|s| s.to_owned());
-        let hasprecipitation = items.get("hasprecipitation").and_then(|s| s.as_bool());
-        let precipitationtype = items
-            .get("precipitationtype")
-            .and_then(|s| s.as_str())
-            .map(|s| s.to_owned());
-        let temperature = items
-            .get("Temperature")
-            .and_then(|m| m.get("Metric"))
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
-        let realfeeltemperature = items
-            .get("RealFeelTemperature")
-            .and_then(|m| m.get("Metric"))
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
+        let temperature = AccuWeather::current_value(items.get("Temperature"));
+        let realfeeltemperature = AccuWeather::current_value(items.get("RealFeelTemperature"));
+        let windchill = AccuWeather::current_value(items.get("WindChillTemperature"));
         let relativehumidity = items
             .get("RelativeHumidity")
             .and_then(|s| s.as_u64())
             .map(|s| s as u32);
-        let dewpoint = items
-            .get("DewPoint")
-            .and_then(|m| m.get("Metric"))
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
+        let dewpoint = AccuWeather::current_value(items.get("DewPoint"));
         let degrees = items
             .get("Wind")
             .and_then(|m| m.get("Direction"))
             .and_then(|m| m.get("Degrees"))
-            .and_then(|s| s.as_u64())
-            .map(|s| s as u16);
-        let dir = WindDeg::get(degrees);
-        let speed = items
-            .get("Wind")
-            .and_then(|m| m.get("Speed"))
-            .and_then(|m| m.get("Metric"))
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
-        let gust = items
-            .get("WindGust")
-            .and_then(|m| m.get("Speed"))
-            .and_then(|m| m.get("Metric"))
-            .and_then(|m| m.get("Value"))
             .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
+            .and_then(WindDeg::normalize);
+        let dir = WindDeg::get(degrees);
+        let speed = AccuWeather::current_value(items.get("Wind").and_then(|m| m.get("Speed")));
+        let gust = AccuWeather::current_value(items.get("WindGust").and_then(|m| m.get("Speed")));
         let uvindex = items
             .get("UVIndex")
             .and_then(|s| s.as_f64())
             .map(|s| s as f32);
-        let visibility = items
-            .get("Visibility")
-            .and_then(|m| m.get("Metric"))
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
+        let visibility = AccuWeather::current_value(items.get("Visibility"));
         let cloudcover = items
             .get("CloudCover")
             .and_then(|s| s.as_u64())
             .map(|s| s as u8);
-        let pressure = items
-            .get("Pressure")
-            .and_then(|m| m.get("Metric"))
-            .and_then(|m| m.get("Value"))
-            .and_then(|s| s.as_f64())
-            .map(|s| s as f32);
+        let pressure = AccuWeather::current_value(items.get("Pressure"));
 
         Some(AccuWeatherItemCurrent {
             date,
             address,
             geo,
+            offset: AccuWeather::offset(items.get("LocalObservationDateTime")),
             weathertext,
-            hasprecipitation,
-            precipitationtype,
             temperature,
             realfeeltemperature,
+            windchill,
             relativehumidity,
             dewpoint,
             degrees,
@@ -595,73 +687,77 @@ impl AccuWeather {
         })
     }
 
-    /// Display result
-    #[rustfmt::skip]
-    fn show_current(&self, item: &AccuWeatherItemCurrent, duration: i64, date: &str) {
-        println!("Weather for '{}'. OpenWeather server. Request time {} ms.", date, duration);
-        println!("Request address: {}.", item.address);
-        println!("Found address: {} ({},{}).", item.geo.address, item.geo.lat, item.geo.lon);
-        println!("Forecast date on the server: {}", item.date.format("%Y-%m-%d %H:%M:%S (%:z)"));
-        println!("{}", "-".repeat(40));
-        println!("Description of weather       : {}", item.weathertext.as_ref().map_or("None".to_owned(), |s| s.to_owned()));
-        println!("Presence of precipitation    : {}", item.hasprecipitation.map_or("None".to_owned(), |s| format!("{}", s)));
-        println!("The type of precipitation    : {}", item.precipitationtype.as_ref().map_or("None".to_owned(), |s| s.to_owned()));
-        println!("Temperature                  : {}", item.temperature.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Real feel temperature        : {}", item.realfeeltemperature.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Humidity                     : {}", item.relativehumidity.map_or("None".to_owned(), |s| s.to_string() + " %"));
-        println!("Atmospheric pressure         : {}", item.pressure.map_or("None".to_owned(), |s| s.to_string() + " hPa"));
-        println!("Dew point temperature        : {}", item.dewpoint.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Wind direction and degrees   : {:?} ({})", item.dir, item.degrees.map_or("None".to_owned(), |s| s.to_string() + "°"));
-        println!("Wind speed                   : {}", item.speed.map_or("None".to_owned(), |s| format!("{:#.1} km/h", s)));
-        println!("Wind gust                    : {}", item.gust.map_or("None".to_owned(), |s| format!("{:#.1} km/h", s)));
-        println!("UV index                     : {}", item.uvindex.map_or("None".to_owned(), |s| format!("{:#.1}", s)));
-        println!("Visibility                   : {}", item.visibility.map_or("None".to_owned(), |s| s.to_string() + " km"));
-        println!("Cloud cover                  : {}", item.cloudcover.map_or("None".to_owned(), |s| s.to_string() + " %"));
-    }
-
-    /// Display result
-    #[rustfmt::skip]
-    fn show_date(&self, item: &AccuWeatherItemForecast, duration: i64, date: &str) {
-        println!("Weather for '{}'. OpenWeather server. Request time {} ms.", date, duration);
-        println!("Request address: {}.", item.address);
-        println!("Found address: {} ({},{}).", item.geo.address, item.geo.lat, item.geo.lon);
-        println!("Forecast date on the server: {}", item.date.format("%Y-%m-%d %H:%M:%S (%:z)"));
-        println!("{}", "-".repeat(40));
-        println!("Sunrise time                 : {}", item.sunrise.map_or("None".to_owned(), |dt| dt.format("%Y-%m-%d %H:%M:%S (%:z)").to_string()));
-        println!("Sunset time                  : {}", item.sunset.map_or("None".to_owned(), |dt| dt.format("%Y-%m-%d %H:%M:%S (%:z)").to_string()));
-        println!("Temperature min              : {}", item.temp_min.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Temperature max              : {}", item.temp_max.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Real feel temperature        : {}", item.realfeel_min.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Real feel temperature        : {}", item.realfeel_max.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("{}", "-".repeat(40));
-        println!("Daytime forecast");
-        println!("{}", "-".repeat(40));
-        println!("Description of weather       : {}", item.day_longphrase.as_ref().map_or("None".to_owned(), |s| s.to_owned()));
-        println!("Presence of precipitation    : {}", item.day_hasprecipitation.map_or("None".to_owned(), |s| format!("{}", s)));
-        println!("The type of precipitation    : {}", item.day_precipitationtype.as_ref().map_or("None".to_owned(), |s| s.to_owned()));
-        println!("Rain probability             : {}", item.day_rainprobability.map_or("None".to_owned(), |s| s.to_string() + " %"));
-        println!("Rain volume                  : {}", item.day_rain.map_or("None".to_owned(), |s| format!("{:#.1} mm", s)));
-        println!("Snow probability             : {}", item.day_snowprobability.map_or("None".to_owned(), |s| s.to_string() + " %"));
-        println!("Snow volume                  : {}", item.day_snow.map_or("None".to_owned(), |s| format!("{:#.1} sm", s)));
-        println!("Wind direction and degrees   : {:?} ({})", item.day_dir, item.day_deg.map_or("None".to_owned(), |s| s.to_string() + "°"));
-        println!("Wind speed                   : {}", item.day_speed.map_or("None".to_owned(), |s| format!("{:#.1} km/h", s)));
-        println!("Wind gust                    : {}", item.day_gust.map_or("None".to_owned(), |s| format!("{:#.1} km/h", s)));
-        println!("Cloud cover                  : {}", item.day_cloudcover.map_or("None".to_owned(), |s| s.to_string() + " %"));
-        println!("{}", "-".repeat(40));
-        println!("Night forecast");
-        println!("{}", "-".repeat(40));
-        println!("Description of weather       : {}", item.night_longphrase.as_ref().map_or("None".to_owned(), |s| s.to_owned()));
-        println!("Presence of precipitation    : {}", item.night_hasprecipitation.map_or("None".to_owned(), |s| format!("{}", s)));
-        println!("The type of precipitation    : {}", item.night_precipitationtype.as_ref().map_or("None".to_owned(), |s| s.to_owned()));
-        println!("Rain probability             : {}", item.night_rainprobability.map_or("None".to_owned(), |s| s.to_string() + " %"));
-        println!("Rain volume                  : {}", item.night_rain.map_or("None".to_owned(), |s| format!("{:#.1} mm", s)));
-        println!("Snow probability             : {}", item.night_snowprobability.map_or("None".to_owned(), |s| s.to_string() + " %"));
-        println!("Snow volume                  : {}", item.night_snow.map_or("None".to_owned(), |s| format!("{:#.1} sm", s)));
-        println!("Wind direction and degrees   : {:?} ({})", item.night_dir, item.night_deg.map_or("None".to_owned(), |s| s.to_string() + "°"));
-        println!("Wind speed                   : {}", item.night_speed.map_or("None".to_owned(), |s| format!("{:#.1} km/h", s)));
-        println!("Wind gust                    : {}", item.night_gust.map_or("None".to_owned(), |s| format!("{:#.1} km/h", s)));
-        println!("Cloud cover                  : {}", item.night_cloudcover.map_or("None".to_owned(), |s| s.to_string() + " %"));
+    /// Convert current result to the normalized weather report
+    fn report_current(&self, item: AccuWeatherItemCurrent) -> WeatherReport {
+        WeatherReport {
+            condition: item.weathertext,
+            temperature: item.temperature,
+            feels_like: item.realfeeltemperature,
+            humidity: item.relativehumidity,
+            pressure: item.pressure,
+            wind_speed: item.speed,
+            wind_deg: item.degrees,
+            wind_dir: item.dir,
+            precipitation: None,
+            wind_chill: item.windchill,
+            dew_point: item.dewpoint,
+            wind_gust: item.gust,
+            cloud_cover: item.cloudcover.map(|s| s as u32),
+            visibility: item.visibility,
+            uv_index: item.uvindex,
+            ..WeatherReport::new(self.name, item.address, item.geo, item.date, item.offset)
+        }
+    }
 
+    /// Convert forecast result to the normalized weather report
+    fn report_date(&self, item: AccuWeatherItemForecast) -> WeatherReport {
+        let temperature = match (item.temp_min, item.temp_max) {
+            (Some(min), Some(max)) => Some((min + max) / 2.0),
+            (min, max) => min.or(max),
+        };
+        let feels_like = match (item.realfeel_min, item.realfeel_max) {
+            (Some(min), Some(max)) => Some((min + max) / 2.0),
+            (min, max) => min.or(max),
+        };
+        // The day and the night parts are joined into the day
+        let sum = |day: Option<f32>, night: Option<f32>| match (day, night) {
+            (Some(day), Some(night)) => Some(day + night),
+            (day, night) => day.or(night),
+        };
+        let cloud_cover = match (item.day_cloudcover, item.night_cloudcover) {
+            (Some(day), Some(night)) => Some((day + night).div_ceil(2)),
+            (day, night) => day.or(night),
+        };
+        WeatherReport {
+            condition: item.day_longphrase,
+            summary: item.night_longphrase.map(|s| format!("Night: {}", s)),
+            temperature,
+            feels_like,
+            wind_speed: item.day_speed,
+            wind_deg: item.day_deg,
+            wind_dir: item.day_dir,
+            precipitation: sum(item.day_rain, item.night_rain),
+            temperature_min: item.temp_min,
+            temperature_max: item.temp_max,
+            wind_gust: match (item.day_gust, item.night_gust) {
+                (Some(day), Some(night)) => Some(day.max(night)),
+                (day, night) => day.or(night),
+            },
+            precipitation_chance: [
+                item.day_rainprobability,
+                item.day_snowprobability,
+                item.night_rainprobability,
+                item.night_snowprobability,
+            ]
+            .into_iter()
+            .flatten()
+            .max(),
+            snow: sum(item.day_snow, item.night_snow),
+            cloud_cover,
+            sunrise: item.sunrise,
+            sunset: item.sunset,
+            ..WeatherReport::new(self.name, item.address, item.geo, item.date, item.offset)
+        }
     }
 }
 
@@ -674,7 +770,7 @@ impl Provider for AccuWeather {
     }
 
     fn deserialize(&mut self, data: &str) -> bool {
-        let mut input = data.split(':');
+        let mut input = data.splitn(2, ':');
         match input.next() {
             Some(name) => {
                 if name != self.name {
@@ -682,14 +778,14 @@ impl Provider for AccuWeather {
                 }
             }
             None => {
-                println!("The data file structure is damaged. The data file will be deleted.");
+                Log::info("The data file structure is damaged. The data file will be deleted.");
                 return false;
             }
         };
         let key = match input.next() {
             Some(key) => key.to_owned(),
             None => {
-                println!("The data file structure is damaged. The data file will be deleted.");
+                Log::info("The data file structure is damaged. The data file will be deleted.");
                 return false;
             }
         };
@@ -701,51 +797,32 @@ impl Provider for AccuWeather {
         true
     }
 
-    fn get_weather(&self, address: String, date: Date) {
-        // https://dataservice.accuweather.com/forecasts/v1/daily/5day/324505?apikey=hHWnLgUfUGzr0KQFbSOcKQYkNPM8GlVL
-        match date {
-            Date::Now => {
-                let start = Local::now();
-                let now = match self.get_now(address) {
-                    Some(now) => now,
-                    None => {
-                        println!("It is not possible to determine the date of the weather forecast sent by the provider");
-                        return;
-                    }
-                };
-                let duration = Local::now() - start;
-                self.show_current(&now, duration.num_milliseconds(), "now");
-            }
-            Date::Set(dt) => {
-                let start = Local::now();
-                let now = match self.get_date(address, &dt) {
-                    Some(now) => now,
-                    None => {
-                        println!("It is not possible to determine the date of the weather forecast sent by the provider");
-                        return;
-                    }
-                };
-                let duration = Local::now() - start;
-                self.show_date(
-                    &now,
-                    duration.num_milliseconds(),
-                    &dt.format("%Y-%m-%d %H:%M:%S (%:z)").to_string(),
-                );
-            }
-            _ => {}
-        }
-    }
-
     fn name(&self) -> &'static str {
         self.name
     }
 
+    fn capabilities(&self) -> &'static [Capability] {
+        &[
+            Capability::Temperature,
+            Capability::FeelsLike,
+            Capability::Uv,
+            Capability::Snow,
+            Capability::DateRange,
+            Capability::Coordinates,
+        ]
+    }
+
+    fn forecast_days(&self) -> Option<i64> {
+        // The free daily forecast is for 5 days including today
+        Some(4)
+    }
+
     fn configure(&mut self) {
         println!("Configure credentials for {}: \n", self.name);
         match &self.key {
             Some(key) => print!(
                 "Please enter the API key to access the weather forecast. Current key={}: ",
-                key
+                Format::secret(key)
             ),
             None => print!("Please enter the API key to access the weather forecast: "),
         }
@@ -770,6 +847,74 @@ impl Provider for AccuWeather {
             self.key = Some(key);
         }
     }
+
+    fn fetch_range(
+        &self,
+        address: String,
+        start: &DateTime<Local>,
+        end: &DateTime<Local>,
+    ) -> Option<Vec<WeatherReport>> {
+        let list = self.get_list(address)?;
+        let reports = list
+            .into_iter()
+            .map(|item| self.report_date(item))
+            .collect();
+        Some(WeatherReport::daily(reports, start, end))
+    }
+
+    fn key_from_env(&mut self) {
+        if let Ok(key) = env::var("WEATHER_ACCUWEATHER_KEY") {
+            let key = key.trim();
+            if !key.is_empty() {
+                self.env_key = Some(key.to_owned());
+            }
+        }
+    }
+
+    fn credentials(&self) -> Vec<(&'static str, String)> {
+        key_rows(self.key.as_deref(), self.env_key.as_deref())
+    }
+
+    fn has_credentials(&self) -> bool {
+        self.key.is_some() || self.env_key.is_some()
+    }
+
+    fn reset(&mut self) {
+        self.key = None;
+    }
+
+    fn check(&self, geo: &Geo) -> Option<Result<(), HttpError>> {
+        let key = self.env_key.as_ref().or(self.key.as_ref())?;
+        // The location search is the cheapest request with the key
+        let url = AccuWeather::location_url(geo, key);
+        Some(Budget::get_json(self.name, self.client.as_ref(), &url, &[]).map(|_| ()))
+    }
+
+    fn supports_astro(&self) -> bool {
+        true
+    }
+
+    fn fetch_astro(&self, address: String, date: Date) -> Option<AstroReport> {
+        // The current conditions have no sun times, the daily forecast of today has them
+        let date = match date {
+            Date::Now => Local::now(),
+            Date::Set(dt) => dt,
+            Date::Error | Date::Range(..) => return None,
+        };
+        let item = self.get_date(address, &date)?;
+        Some(AstroReport {
+            sunrise: item.sunrise,
+            sunset: item.sunset,
+            ..AstroReport::new(self.name, item.address, item.geo, item.date, item.offset)
+        })
+    }
+
+    fn fetch(&self, address: String, date: Date) -> Option<WeatherReport> {
+        match self.get_item(address, &date)? {
+            AccuWeatherItem::Current(item) => Some(self.report_current(item)),
+            AccuWeatherItem::Forecast(item) => Some(self.report_date(item)),
+        }
+    }
 }
 
 impl Default for AccuWeather {
@@ -777,3 +922,274 @@ impl Default for AccuWeather {
         AccuWeather::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+
+    use super::AccuWeather;
+    use crate::{geo::Geo, http::MockHttp, work::Provider};
+
+    #[test]
+    fn test_detect_now() {
+        let json = r#"{
+            "LocalObservationDateTime": "2023-05-11T14:05:00+03:00",
+            "EpochTime": 1683803100,
+            "WeatherText": "Light rain",
+            "HasPrecipitation": true,
+            "PrecipitationType": "Rain",
+            "Temperature": {"Metric": {"Value": 14.4, "Unit": "C", "UnitType": 17}},
+            "RelativeHumidity": 81,
+            "Wind": {
+                "Direction": {"Degrees": 315, "Localized": "NW", "English": "NW"},
+                "Speed": {"Metric": {"Value": 13.0, "Unit": "km/h", "UnitType": 7}}
+            }
+        }"#;
+        let items: Map<String, Value> = serde_json::from_str(json).unwrap();
+        let geo = Geo {
+            lat: "50.45".to_owned(),
+            lon: "30.52".to_owned(),
+            address: "Kyiv, Ukraine".to_owned(),
+            importance: None,
+        };
+        let item = AccuWeather::new()
+            .detect_now(&items, geo, "Kyiv".to_owned())
+            .unwrap();
+        assert_eq!(item.weathertext, Some("Light rain".to_owned()));
+        assert_eq!(item.temperature, Some(14.4));
+        assert_eq!(item.relativehumidity, Some(81));
+    }
+
+    #[test]
+    fn test_get_now() {
+        let json = r#"[{
+            "EpochTime": 1683803100,
+            "WeatherText": "Sunny",
+            "HasPrecipitation": false,
+            "PrecipitationType": null,
+            "Temperature": {"Metric": {"Value": 21.5, "Unit": "C", "UnitType": 17}}
+        }]"#;
+        let provider = AccuWeather {
+            client: Box::new(MockHttp::new(&[("currentconditions/v1/324505", json)])),
+            env_key: Some("test".to_owned()),
+            ..AccuWeather::new()
+        };
+        let item = provider.get_now("key:324505".to_owned()).unwrap();
+        assert_eq!(item.weathertext, Some("Sunny".to_owned()));
+        assert_eq!(item.temperature, Some(21.5));
+        assert_eq!(item.geo.address, "AccuWeather location 324505");
+
+        // The server answer is not an array
+        let provider = AccuWeather {
+            client: Box::new(MockHttp::new(&[("currentconditions", "{}")])),
+            env_key: Some("test".to_owned()),
+            ..AccuWeather::new()
+        };
+        assert!(provider.get_now("key:324505".to_owned()).is_none());
+    }
+
+    #[test]
+    fn test_detect_now_units() {
+        // The current conditions contain both unit systems, the metric one is read
+        let json = r#"{
+            "EpochTime": 1683803100,
+            "LocalObservationDateTime": "2023-05-11T14:05:00+03:00",
+            "Temperature": {
+                "Imperial": {"Value": 58.0, "Unit": "F", "UnitType": 18},
+                "Metric": {"Value": 14.4, "Unit": "C", "UnitType": 17}
+            },
+            "Wind": {
+                "Speed": {
+                    "Imperial": {"Value": 8.1, "Unit": "mi/h", "UnitType": 9},
+                    "Metric": {"Value": 13.0, "Unit": "km/h", "UnitType": 7}
+                }
+            },
+            "Pressure": {
+                "Imperial": {"Value": 29.94, "Unit": "inHg", "UnitType": 12},
+                "Metric": {"Value": 1014.0, "Unit": "mb", "UnitType": 14}
+            }
+        }"#;
+        let items: Map<String, Value> = serde_json::from_str(json).unwrap();
+        let item = AccuWeather::new()
+            .detect_now(&items, geo(), "Kyiv".to_owned())
+            .unwrap();
+        assert_eq!(item.temperature, Some(14.4));
+        assert_eq!(item.speed, Some(13.0));
+        assert_eq!(item.pressure, Some(1014.0));
+        assert_eq!(
+            item.offset.map(|offset| offset.to_string()),
+            Some("+03:00".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_detect_date_units() {
+        // Answer for `metric=true`
+        let metric = r#"{
+            "EpochDate": 1683777600,
+            "Temperature": {
+                "Minimum": {"Value": 10.0, "Unit": "C", "UnitType": 17},
+                "Maximum": {"Value": 20.0, "Unit": "C", "UnitType": 17}
+            },
+            "Day": {
+                "Wind": {
+                    "Speed": {"Value": 16.1, "Unit": "km/h", "UnitType": 7},
+                    "Direction": {"Degrees": 225, "Localized": "SW", "English": "SW"}
+                },
+                "WindGust": {"Speed": {"Value": 40.2, "Unit": "km/h", "UnitType": 7}},
+                "Rain": {"Value": 2.5, "Unit": "mm", "UnitType": 3},
+                "Snow": {"Value": 0.0, "Unit": "cm", "UnitType": 4}
+            },
+            "Night": {
+                "Wind": {"Speed": {"Value": 8.0, "Unit": "km/h", "UnitType": 7}},
+                "WindGust": {"Speed": {"Value": 24.1, "Unit": "km/h", "UnitType": 7}}
+            }
+        }"#;
+        // The same forecast for `metric=false`
+        let imperial = r#"{
+            "EpochDate": 1683777600,
+            "Temperature": {
+                "Minimum": {"Value": 50.0, "Unit": "F", "UnitType": 18},
+                "Maximum": {"Value": 68.0, "Unit": "F", "UnitType": 18}
+            },
+            "Day": {
+                "Wind": {
+                    "Speed": {"Value": 10.0, "Unit": "mi/h", "UnitType": 9},
+                    "Direction": {"Degrees": 225, "Localized": "SW", "English": "SW"}
+                },
+                "WindGust": {"Speed": {"Value": 25.0, "Unit": "mi/h", "UnitType": 9}},
+                "Rain": {"Value": 0.1, "Unit": "in", "UnitType": 1},
+                "Snow": {"Value": 0.0, "Unit": "in", "UnitType": 1}
+            },
+            "Night": {
+                "Wind": {"Speed": {"Value": 5.0, "Unit": "mi/h", "UnitType": 9}},
+                "WindGust": {"Speed": {"Value": 15.0, "Unit": "mi/h", "UnitType": 9}}
+            }
+        }"#;
+        let round = |s: Option<f32>| s.map(|s| (s * 10.0).round() / 10.0);
+        for json in [metric, imperial] {
+            let items: Map<String, Value> = serde_json::from_str(json).unwrap();
+            let item = AccuWeather::new()
+                .detect_date(&items, geo(), "Kyiv".to_owned())
+                .unwrap();
+            assert_eq!(round(item.temp_min), Some(10.0));
+            assert_eq!(round(item.temp_max), Some(20.0));
+            // The wind is in km/h whatever the `metric` parameter was
+            assert_eq!(round(item.day_speed), Some(16.1));
+            assert_eq!(round(item.day_gust), Some(40.2));
+            assert_eq!(item.day_deg, Some(225));
+            assert_eq!(round(item.night_gust), Some(24.1));
+            assert_eq!(round(item.day_rain), Some(2.5));
+            assert_eq!(round(item.day_snow), Some(0.0));
+        }
+    }
+
+    #[test]
+    fn test_detect_date_partial() {
+        let json = r#"{
+            "EpochDate": 1683777600,
+            "Temperature": {"Maximum": {"Value": 20.0, "Unit": "C", "UnitType": 17}},
+            "Night": {"LongPhrase": "Clear", "CloudCover": 5}
+        }"#;
+        let items: Map<String, Value> = serde_json::from_str(json).unwrap();
+        let item = AccuWeather::new()
+            .detect_date(&items, geo(), "Kyiv".to_owned())
+            .unwrap();
+        assert_eq!(item.temp_max, Some(20.0));
+        assert_eq!(item.day_longphrase, None);
+        assert_eq!(item.night_longphrase, Some("Clear".to_owned()));
+        assert_eq!(item.night_cloudcover, Some(5));
+
+        let json = r#"{"EpochDate": 1683777600, "Day": {"CloudCover": 40}}"#;
+        let items: Map<String, Value> = serde_json::from_str(json).unwrap();
+        let item = AccuWeather::new()
+            .detect_date(&items, geo(), "Kyiv".to_owned())
+            .unwrap();
+        assert_eq!(item.day_cloudcover, Some(40));
+        assert_eq!(item.night_gust, None);
+        // The date is required to select the item
+        let items: Map<String, Value> = serde_json::from_str(r#"{"Day": {}}"#).unwrap();
+        assert!(AccuWeather::new()
+            .detect_date(&items, geo(), "Kyiv".to_owned())
+            .is_none());
+    }
+
+    #[test]
+    fn test_forecast_value() {
+        let value: Value = serde_json::from_str(r#"{"Value": 5.0, "Unit": "ft"}"#).unwrap();
+        assert_eq!(AccuWeather::forecast_value(Some(&value), "km/h"), None);
+        let value: Value = serde_json::from_str(r#"{"Value": 5.0}"#).unwrap();
+        assert_eq!(AccuWeather::forecast_value(Some(&value), "C"), Some(5.0));
+        assert_eq!(AccuWeather::forecast_value(None, "C"), None);
+    }
+
+    fn geo() -> Geo {
+        Geo {
+            lat: "50.45".to_owned(),
+            lon: "30.52".to_owned(),
+            address: "Kyiv, Ukraine".to_owned(),
+            importance: None,
+        }
+    }
+
+    #[test]
+    fn test_location_cache() {
+        let file = std::env::temp_dir().join(format!("weather_accu_{}.json", std::process::id()));
+        let file = file.to_str().unwrap();
+        assert_eq!(AccuWeather::from_cache(file, "50.45,30.52"), None);
+        AccuWeather::to_cache(file, "50.45,30.52", "324505");
+        assert_eq!(
+            AccuWeather::from_cache(file, "50.45,30.52"),
+            Some("324505".to_owned())
+        );
+        assert_eq!(AccuWeather::from_cache(file, "48.46,35.04"), None);
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn test_location_key() {
+        let search = r#"{
+            "Version": 1,
+            "Key": "324505",
+            "Type": "City",
+            "Rank": 10,
+            "LocalizedName": "Kyiv",
+            "Country": {"ID": "UA", "LocalizedName": "Ukraine"},
+            "GeoPosition": {"Latitude": 50.45, "Longitude": 30.524}
+        }"#;
+        let json: Value = serde_json::from_str(search).unwrap();
+        assert_eq!(AccuWeather::location_key(&json), Some("324505".to_owned()));
+        let json: Value =
+            serde_json::from_str(r#"[{"Key": "2-324505_1_AL"}, {"Key": "1"}]"#).unwrap();
+        assert_eq!(
+            AccuWeather::location_key(&json),
+            Some("2-324505_1_AL".to_owned())
+        );
+        let json: Value = serde_json::from_str(r#"{"Key": 324505}"#).unwrap();
+        assert_eq!(AccuWeather::location_key(&json), Some("324505".to_owned()));
+        let json: Value = serde_json::from_str(r#"[]"#).unwrap();
+        assert_eq!(AccuWeather::location_key(&json), None);
+        let json: Value = serde_json::from_str(r#"{"Key": "../admin"}"#).unwrap();
+        assert_eq!(AccuWeather::location_key(&json), None);
+        assert!(AccuWeather::is_location_key("324505"));
+        assert!(!AccuWeather::is_location_key(""));
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut provider = AccuWeather::new();
+        assert_eq!(provider.serialize(), "AccuWeather:");
+        assert!(provider.deserialize("AccuWeather:abc123"));
+        assert_eq!(provider.key.as_deref(), Some("abc123"));
+        assert_eq!(provider.serialize(), "AccuWeather:abc123");
+        // The colon is a part of the key
+        assert!(provider.deserialize("AccuWeather:abc:123"));
+        assert_eq!(provider.serialize(), "AccuWeather:abc:123");
+        assert!(provider.deserialize("AccuWeather:"));
+        assert_eq!(provider.key, None);
+        assert_eq!(provider.serialize(), "AccuWeather:");
+        assert!(!provider.deserialize("AccuWeather"));
+        assert!(!provider.deserialize("WeatherAPI:abc123"));
+        assert!(!provider.deserialize(""));
+    }
+}
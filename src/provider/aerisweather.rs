@@ -2,25 +2,45 @@
 //!
 
 use std::{
+    env,
     io::{stdin, stdout, Write},
-    time::Duration,
 };
 
-use chrono::{DateTime, Local, TimeZone, Utc};
-use reqwest::blocking::Client;
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
 use serde_json::{Map, Value};
 
-use crate::{geo::Geo, init::Date, wind::WindDeg, work::Provider};
+use crate::{
+    astro::{Astro, AstroReport},
+    budget::Budget,
+    format::Format,
+    geo::Geo,
+    http::{Http, HttpClient, HttpError},
+    init::Date,
+    log::Log,
+    provider::closest_item,
+    wind::WindDeg,
+    work::{Capability, Provider, WeatherReport},
+};
+
+/// Endpoint of the current conditions.
+const OBSERVATIONS_URL: &str = "https://api.aerisapi.com/observations";
 
 /// Describes 'AerisWeather' credentials
 ///
 /// * `name: &'static str` - Provider name.
+/// * `client: Box<dyn HttpClient>` - Source of the server answers.
 /// * `key: Option<(String, String)>` - Turple of client_id and client_secret.
+/// * `env_key: Option<(String, String)>` - Turple of client_id and client_secret from the
+///   `WEATHER_AERIS_ID` and `WEATHER_AERIS_SECRET` environment variables.
 pub struct AerisWeather {
     /// Provider name.
     name: &'static str,
+    /// Source of the server answers.
+    client: Box<dyn HttpClient>,
     /// Api key.
     key: Option<(String, String)>,
+    /// Api key from the environment variables, takes precedence over `key`.
+    env_key: Option<(String, String)>,
 }
 
 /// Temperature representation
@@ -66,95 +86,113 @@ struct AerisWeatherItem {
     /// RealFeel temperature
     feelslike_c: Option<f32>,
     /// Snow
-    snow_depth_cm: Option<u16>,
+    snow_depth_cm: Option<f32>,
     /// Precipitation
-    precip_mm: Option<u16>,
+    precip_mm: Option<f32>,
     /// Measure of the strength of the ultraviolet radiation from the sun
-    uvi: Option<u16>,
+    uvi: Option<f32>,
     /// Number representing the percentage of the sky that is covered by clouds
     sky: Option<u16>,
     /// Sun rise
     sunrise: Option<DateTime<Local>>,
     /// Sun set
     sunset: Option<DateTime<Local>>,
+    /// Wind chill temperature, °C. Observations only
+    windchill_c: Option<f32>,
+    /// Heat index, °C. Observations only
+    heatindex_c: Option<f32>,
+    /// Moon phase name, if the server provided it
+    moon: Option<String>,
+    /// UTC offset of the location, the system timezone is used when it's unknown
+    offset: Option<FixedOffset>,
 }
 
 impl AerisWeather {
     pub fn new() -> AerisWeather {
         AerisWeather {
             name: "AerisWeather",
+            client: Box::new(Http),
             key: None,
+            env_key: None,
         }
     }
     /// Load data from provider
     fn get_json(&self, url: &str, address: &str) -> Option<(Map<String, Value>, Geo)> {
-        let (id, secret) = match &self.key {
+        let (id, secret) = match self.env_key.as_ref().or(self.key.as_ref()) {
             Some(key) => key,
             None => {
-                println!("AerisWeather server API access key is not set. Please install it first.");
+                Log::error(
+                    "AerisWeather server API access key is not set. Please install it first.",
+                );
                 return None;
             }
         };
         // Find geo coordinates by address
-        let geo = match Geo::get(address) {
-            Some(mut geos) => match geos.pop() {
-                Some(geo) => geo,
-                None => {
-                    println!("Sorry, we couldn't find your address: {}", address);
-                    return None;
-                }
-            },
-            None => return None,
-        };
-        let url = format!(
-            "{}/{},{}?&format=json&client_id={}&client_secret={}",
-            url, geo.lat, geo.lon, id, secret
-        );
-        // Client for url query
-        let client = match Client::builder().timeout(Duration::from_secs(3)).build() {
-            Ok(c) => c,
-            Err(e) => {
-                println!("The following error occurred while requesting coordinates for your address: {}", e);
+        let geo = Geo::find(address)?;
+        let url = AerisWeather::url(url, &geo, id, secret);
+        let json = match Budget::get_json(self.name, self.client.as_ref(), &url, &[]) {
+            Ok(Value::Object(json)) => json,
+            Ok(_) => {
+                Log::info("Unable to recognize json response from server.");
                 return None;
             }
-        };
-
-        let json_str = match client.get(&url).send() {
-            Ok(s) => {
-                let status = s.status();
-                if status != 200 {
-                    println!("Error connecting to {}. Status code: {}", &url, status);
-                    return None;
-                }
-                match s.text() {
-                    Ok(s) => s,
-                    Err(e) => {
-                        println!("Error getting answer from {}. Error text: {}", &url, e);
-                        return None;
-                    }
-                }
-            }
             Err(e) => {
-                println!("Error connecting to {}. Error text: {}", &url, e);
+                e.log(Some(self.name));
                 return None;
             }
         };
-        // Parse json
-        match serde_json::from_str(&json_str) {
-            Ok(json) => Some((json, geo)),
-            Err(e) => {
-                println!(
-                    "Unable to recognize json response from server. Error text: {}",
-                    e
-                );
-                None
-            }
+        if let Some(error) = AerisWeather::error(&json) {
+            Log::error(&format!(
+                "The AerisWeather server returned an error: {}.",
+                error
+            ));
+            return None;
+        }
+        Some((json, geo))
+    }
+
+    /// Url of the request for the place.
+    ///
+    /// * `url: &str` - Endpoint, e.g. `OBSERVATIONS_URL`.
+    /// * `geo: &Geo` - The place.
+    /// * `id: &str` - client_id.
+    /// * `secret: &str` - client_secret.
+    fn url(url: &str, geo: &Geo, id: &str, secret: &str) -> String {
+        format!(
+            "{}/{},{}?&format=json&client_id={}&client_secret={}",
+            url, geo.lat, geo.lon, id, secret
+        )
+    }
+
+    /// The error of the answer: the server answers with status 200 and
+    /// `{"success": false, "error": {"code": ..., "description": ...}}` in the body.
+    ///
+    /// * `json: &Map<String, Value>` - Answer of the server.
+    ///
+    /// Return
+    ///
+    /// `Option<String>` - `code: description` of the error, `None` if the request succeeded.
+    fn error(json: &Map<String, Value>) -> Option<String> {
+        if json.get("success").and_then(|s| s.as_bool()) != Some(false) {
+            return None;
         }
+        let error = json.get("error");
+        let text = |name: &str| {
+            error
+                .and_then(|e| e.get(name))
+                .and_then(|s| s.as_str())
+                .filter(|s| !s.is_empty())
+        };
+        Some(match (text("code"), text("description")) {
+            (Some(code), Some(description)) => format!("{}: {}", code, description),
+            (Some(text), None) | (None, Some(text)) => text.to_owned(),
+            (None, None) => "unknown error".to_owned(),
+        })
     }
 
     /// Getting weather forecast for now
     fn get_now(&self, address: String) -> Option<AerisWeatherItem> {
-        let (items, geo) = self.get_json("https://api.aerisapi.com/observations", &address)?;
+        let (items, geo) = self.get_json(OBSERVATIONS_URL, &address)?;
         let item = items
             .get("response")
             .and_then(|s| s.get("ob"))
@@ -164,6 +202,13 @@ impl AerisWeather {
 
     /// Getting weather forecast for `date`
     fn get_date(&self, address: String, date: &DateTime<Local>) -> Option<AerisWeatherItem> {
+        let list = self.get_list(address)?;
+        // Daily forecast: find item with the closest date
+        closest_item(list, date, false, |item| item.date)
+    }
+
+    /// Getting all items of weather forecast
+    fn get_list(&self, address: String) -> Option<Vec<AerisWeatherItem>> {
         // Load json from provider
         let (items, geo) = self.get_json("https://api.aerisapi.com/forecasts", &address)?;
 
@@ -174,7 +219,7 @@ impl AerisWeather {
             .and_then(|its| its.get("periods"))
             .and_then(|its| its.as_array())
             .or_else(|| {
-                println!("The AerisWeather server did not provide weather forecast data");
+                Log::info("The AerisWeather server did not provide weather forecast data");
                 None
             })?;
         // Load all AerisWeatherItem to vector
@@ -190,13 +235,16 @@ impl AerisWeather {
         if list.is_empty() {
             return None;
         }
-        // Find item with the closest date
-        list.into_iter().min_by(|item_a, item_b| {
-            let diff_a = item_a.date.signed_duration_since(*date).num_seconds().abs();
-            let diff_b = item_b.date.signed_duration_since(*date).num_seconds().abs();
+        Some(list)
+    }
 
-            diff_a.cmp(&diff_b)
-        })
+    /// Getting weather forecast for `date`
+    fn get_item(&self, address: String, date: &Date) -> Option<AerisWeatherItem> {
+        match date {
+            Date::Now => self.get_now(address),
+            Date::Set(dt) => self.get_date(address, dt),
+            Date::Error | Date::Range(..) => None,
+        }
     }
 
     /// Parse json answer from server
@@ -236,22 +284,15 @@ impl AerisWeather {
             .get("dewpointC")
             .and_then(|s| s.as_f64())
             .map(|s| s as f32);
-        let humidity = items
-            .get("humidity")
-            .and_then(|s| s.as_u64())
-            .map(|s| s as u16);
-        let pressure_mb = items
-            .get("pressureMB")
-            .and_then(|s| s.as_u64())
-            .map(|s| s as u32);
+        // The numbers may arrive as integers or as floats, e.g. `"precipMM": 0.5`, so all of them are read as floats
+        let float = |name: &str| items.get(name).and_then(|s| s.as_f64());
+        let humidity = float("humidity").map(|s| s.round() as u16);
+        let pressure_mb = float("pressureMB").map(|s| s.round() as u32);
         let wind_speed_kph = items
             .get("windSpeedKPH")
             .and_then(|s| s.as_f64())
             .map(|s| s as f32);
-        let wind_dir_deg = items
-            .get("windDirDEG")
-            .and_then(|s| s.as_u64())
-            .map(|s| s as u16);
+        let wind_dir_deg = float("windDirDEG").and_then(WindDeg::normalize);
         let dir = WindDeg::get(wind_dir_deg);
         let wind_gust_kph = items
             .get("windGustKPH")
@@ -261,20 +302,16 @@ impl AerisWeather {
             .get("visibilityKM")
             .and_then(|s| s.as_f64())
             .map(|s| s as f32);
+        let windchill_c = float("windchillC").map(|s| s as f32);
+        let heatindex_c = float("heatindexC").map(|s| s as f32);
         let feelslike_c = items
             .get("feelslikeC")
             .and_then(|s| s.as_f64())
             .map(|s| s as f32);
-        let snow_depth_cm = items
-            .get("snowDepthCM")
-            .and_then(|s| s.as_u64())
-            .map(|s| s as u16);
-        let precip_mm = items
-            .get("precipMM")
-            .and_then(|s| s.as_u64())
-            .map(|s| s as u16);
-        let uvi = items.get("uvi").and_then(|s| s.as_u64()).map(|s| s as u16);
-        let sky = items.get("sky").and_then(|s| s.as_u64()).map(|s| s as u16);
+        let snow_depth_cm = float("snowDepthCM").map(|s| s as f32);
+        let precip_mm = float("precipMM").map(|s| s as f32);
+        let uvi = float("uvi").map(|s| s as f32);
+        let sky = float("sky").map(|s| s.round() as u16);
         let sunrise = items
             .get("sunrise")
             .and_then(|s| s.as_i64())
@@ -285,6 +322,18 @@ impl AerisWeather {
             .and_then(|s| s.as_i64())
             .and_then(|t| Utc.timestamp_opt(t, 0).single())
             .map(|t| Local.from_utc_datetime(&t.naive_utc()));
+        let moon = items
+            .get("moon")
+            .and_then(|m| m.get("phase"))
+            .and_then(|p| p.get("name"))
+            .and_then(|s| s.as_str())
+            .map(|s| s.to_owned());
+        // The local time of the location, e.g. `"dateTimeISO": "2023-05-11T14:00:00+09:00"`
+        let offset = items
+            .get("dateTimeISO")
+            .and_then(|s| s.as_str())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| *t.offset());
         Some(AerisWeatherItem {
             date,
             address,
@@ -306,41 +355,46 @@ impl AerisWeather {
             sky,
             sunrise,
             sunset,
+            windchill_c,
+            heatindex_c,
+            moon,
+            offset,
         })
     }
 
-    /// Display result
-    #[rustfmt::skip]
-    fn show(&self, item: &AerisWeatherItem, duration: i64, date: &str) {
-        println!("Weather for '{}'. AerisWeather server. Request time {} ms.", date, duration);
-        println!("Request address: {}.", item.address);
-        println!("Found address: {} ({},{}).", item.geo.address, item.geo.lat, item.geo.lon);
-        println!("Forecast date on the server: {}", item.date.format("%Y-%m-%d %H:%M:%S (%:z)"));
-        println!("{}", "-".repeat(40));
-        println!("Sunrise time                 : {}", item.sunrise.map_or("None".to_owned(), |dt| dt.format("%Y-%m-%d %H:%M:%S (%:z)").to_string()));
-        println!("Sunset time                  : {}", item.sunset.map_or("None".to_owned(), |dt| dt.format("%Y-%m-%d %H:%M:%S (%:z)").to_string()));
-        println!("Weather description          : {}", item.weather.as_ref().map_or("None".to_owned(), |s| s.to_owned()));
-        match item.temp_c {
-            TempView::None =>              println!("Temperature                  : None"),
-            TempView::Single(temp) => println!("Temperature                  : {:#.1}", temp),
-            TempView::MinMax((min, max)) => {
-                                           println!("Temperature min              : {:#.1}", min);
-                                           println!("Temperature max              : {:#.1} °C", max);
-            },
+    /// Convert result to the normalized weather report
+    fn report(&self, item: AerisWeatherItem) -> WeatherReport {
+        let (temperature, min_max) = match item.temp_c {
+            TempView::None => (None, None),
+            TempView::Single(temp) => (Some(temp), None),
+            TempView::MinMax((min, max)) => (Some((min + max) / 2.0), Some((min, max))),
+        };
+        WeatherReport {
+            condition: item.weather,
+            temperature,
+            feels_like: item.feelslike_c,
+            humidity: item.humidity.map(|s| s as u32),
+            // mbar is hPa
+            pressure: item.pressure_mb.map(|s| s as f32),
+            wind_speed: item.wind_speed_kph,
+            wind_deg: item.wind_dir_deg,
+            wind_dir: item.dir,
+            precipitation: item.precip_mm,
+            temperature_min: min_max.map(|(min, _)| min),
+            temperature_max: min_max.map(|(_, max)| max),
+            wind_chill: item.windchill_c,
+            heat_index: item.heatindex_c,
+            dew_point: item.dewpoint_c,
+            wind_gust: item.wind_gust_kph,
+            snow: item.snow_depth_cm,
+            cloud_cover: item.sky.map(|s| s as u32),
+            visibility: item.visibility_km,
+            uv_index: item.uvi,
+            sunrise: item.sunrise,
+            sunset: item.sunset,
+            moon_phase: item.moon,
+            ..WeatherReport::new(self.name, item.address, item.geo, item.date, item.offset)
         }
-        println!("Dew point                    : {}", item.dewpoint_c.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Humidity                     : {}", item.humidity.map_or("None".to_owned(), |s| s.to_string() + " %"));
-        println!("Atmospheric pressure         : {}", item.pressure_mb.map_or("None".to_owned(), |s| format!("{:#.1} mbar", s)));
-        println!("Wind speed                   : {}", item.wind_speed_kph.map_or("None".to_owned(), |s| format!("{:#.1} km/hour", s)));
-        println!("Wind direction and degrees   : {:?} ({})", item.dir, item.wind_dir_deg.map_or("None".to_owned(), |s| s.to_string() + "°"));
-        println!("Wind gust                    : {}", item.wind_gust_kph.map_or("None".to_owned(), |s| format!("{:#.1} km/hou", s)));
-        println!("Visibility                   : {}", item.visibility_km.map_or("None".to_owned(), |s| s.to_string() + " km"));
-        println!("Human perception temperature : {}", item.feelslike_c.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Snow depth                   : {}", item.snow_depth_cm.map_or("None".to_owned(), |s| format!("{:#.1} sm", s)));
-        println!("Precipitation depth          : {}", item.precip_mm.map_or("None".to_owned(), |s| format!("{:#.1} mm", s)));
-        println!("UV Index                     : {}", item.uvi.map_or("None".to_owned(), |s| format!("{:#.1}", s)));
-        println!("Cloud cover                  : {}", item.sky.map_or("None".to_owned(), |s| s.to_string() + " %"));
-
     }
 }
 
@@ -353,7 +407,8 @@ impl Provider for AerisWeather {
     }
 
     fn deserialize(&mut self, data: &str) -> bool {
-        let mut input = data.split(':');
+        // The client_id is separated by the colon, the client_secret can contain it
+        let mut input = data.splitn(3, ':');
         match input.next() {
             Some(name) => {
                 if name != self.name {
@@ -361,28 +416,24 @@ impl Provider for AerisWeather {
                 }
             }
             None => {
-                println!("The data file structure is damaged. The data file will be deleted.");
+                Log::info("The data file structure is damaged. The data file will be deleted.");
                 return false;
             }
         };
         let id = match input.next() {
             Some(id) => id.to_owned(),
             None => {
-                println!("The data file structure is damaged. The data file will be deleted.");
+                Log::info("The data file structure is damaged. The data file will be deleted.");
                 return false;
             }
         };
         let key = match input.next() {
             Some(key) => key.to_owned(),
             None => {
-                println!("The data file structure is damaged. The data file will be deleted.");
+                Log::info("The data file structure is damaged. The data file will be deleted.");
                 return false;
             }
         };
-        if input.next().is_some() {
-            println!("The data file structure is damaged. The data file will be deleted.");
-            return false;
-        }
         if id.is_empty() && key.is_empty() {
             self.key = None;
             return true;
@@ -390,57 +441,37 @@ impl Provider for AerisWeather {
             self.key = Some((id, key));
             return true;
         }
-        println!("The data file structure is damaged. The data file will be deleted.");
+        Log::info("The data file structure is damaged. The data file will be deleted.");
         false
     }
 
-    fn get_weather(&self, address: String, date: Date) {
-        // https://api.aerisapi.com/observations/50.468071,30.484137576584864?client_id=MoWpgnVwCeEqjy9bSFf2P&client_secret=n1KUHGW0i7ncFRw638p1ewsskPpA6c1GKi9G9SYT&format=json
-        // https://api.aerisapi.com/forecasts/50.468071,30.484137576584864?client_id=MoWpgnVwCeEqjy9bSFf2P&client_secret=n1KUHGW0i7ncFRw638p1ewsskPpA6c1GKi9G9SYT&format=json
-        match date {
-            Date::Now => {
-                let start = Local::now();
-                let now = match self.get_now(address) {
-                    Some(now) => now,
-                    None => {
-                        println!("It is not possible to determine the date of the weather forecast sent by the provider");
-                        return;
-                    }
-                };
-                let duration = Local::now() - start;
-                self.show(&now, duration.num_milliseconds(), "now");
-            }
-            Date::Set(dt) => {
-                let start = Local::now();
-                let now = match self.get_date(address, &dt) {
-                    Some(now) => now,
-                    None => {
-                        println!("It is not possible to determine the date of the weather forecast sent by the provider");
-                        return;
-                    }
-                };
-                let duration = Local::now() - start;
-                self.show(
-                    &now,
-                    duration.num_milliseconds(),
-                    &dt.format("%Y-%m-%d %H:%M:%S (%:z)").to_string(),
-                );
-            }
-            _ => {}
-        }
-    }
-
     fn name(&self) -> &'static str {
         self.name
     }
 
+    fn capabilities(&self) -> &'static [Capability] {
+        &[
+            Capability::Temperature,
+            Capability::FeelsLike,
+            Capability::Uv,
+            Capability::Snow,
+            Capability::DateRange,
+            Capability::Coordinates,
+        ]
+    }
+
+    fn forecast_days(&self) -> Option<i64> {
+        // The forecasts are for 15 days including today
+        Some(14)
+    }
+
     fn configure(&mut self) {
         println!("Configure credentials for {}: \n", self.name);
         // get client_id
         match &self.key {
             Some((client_id, _)) => print!(
                 "Please enter the client_id to access the weather forecast. Current client_id={}: ",
-                client_id
+                Format::secret(client_id)
             ),
             None => print!("Please enter the client_id to access the weather forecast: "),
         }
@@ -462,10 +493,14 @@ impl Provider for AerisWeather {
             self.key = None;
             return;
         }
+        if client_id.contains(':') {
+            print!("The client_id can't contain ':'.");
+            return;
+        }
 
         // get client_secret
         match &self.key {
-            Some((_, client_secret)) => print!("Please enter the client_secret to access the weather forecast. Current client_secret={}: ", client_secret),
+            Some((_, client_secret)) => print!("Please enter the client_secret to access the weather forecast. Current client_secret={}: ", Format::secret(client_secret)),
             None => print!("Please enter the client_secret to access the weather forecast: "),
         }
         if let Err(e) = stdout().flush() {
@@ -488,6 +523,105 @@ impl Provider for AerisWeather {
         );
         self.key = Some((client_id, client_secret))
     }
+
+    fn fetch_range(
+        &self,
+        address: String,
+        start: &DateTime<Local>,
+        end: &DateTime<Local>,
+    ) -> Option<Vec<WeatherReport>> {
+        let list = self.get_list(address)?;
+        let reports = list.into_iter().map(|item| self.report(item)).collect();
+        Some(WeatherReport::daily(reports, start, end))
+    }
+
+    fn key_from_env(&mut self) {
+        if let (Ok(id), Ok(secret)) = (
+            env::var("WEATHER_AERIS_ID"),
+            env::var("WEATHER_AERIS_SECRET"),
+        ) {
+            let (id, secret) = (id.trim(), secret.trim());
+            if !id.is_empty() && !secret.is_empty() {
+                self.env_key = Some((id.to_owned(), secret.to_owned()));
+            }
+        }
+    }
+
+    fn credentials(&self) -> Vec<(&'static str, String)> {
+        let id = |key: &Option<(String, String)>| {
+            key.as_ref()
+                .map_or("not set".to_owned(), |(id, _)| id.to_owned())
+        };
+        let secret = |key: &Option<(String, String)>| {
+            key.as_ref()
+                .map_or("not set".to_owned(), |(_, secret)| Format::secret(secret))
+        };
+        let mut rows = vec![
+            ("client_id", id(&self.key)),
+            ("client_secret", secret(&self.key)),
+        ];
+        if self.env_key.is_some() {
+            rows.push(("client_id from the environment (used)", id(&self.env_key)));
+            rows.push((
+                "client_secret from the environment (used)",
+                secret(&self.env_key),
+            ));
+        }
+        rows
+    }
+
+    fn has_credentials(&self) -> bool {
+        self.key.is_some() || self.env_key.is_some()
+    }
+
+    fn reset(&mut self) {
+        self.key = None;
+    }
+
+    fn check(&self, geo: &Geo) -> Option<Result<(), HttpError>> {
+        let (id, secret) = self.env_key.as_ref().or(self.key.as_ref())?;
+        let url = AerisWeather::url(OBSERVATIONS_URL, geo, id, secret);
+        let json = match Budget::get_json(self.name, self.client.as_ref(), &url, &[]) {
+            Ok(Value::Object(json)) => json,
+            Ok(_) => return Some(Ok(())),
+            Err(e) => return Some(Err(e)),
+        };
+        // The rejected credentials are answered with status 200 and the error in the body
+        Some(match AerisWeather::error(&json) {
+            Some(error) if error.starts_with("invalid_client") => Err(HttpError::Denied {
+                url,
+                status: 200,
+                body: error,
+            }),
+            Some(error) => Err(HttpError::Status {
+                url,
+                status: 200,
+                body: error,
+            }),
+            None => Ok(()),
+        })
+    }
+
+    fn supports_astro(&self) -> bool {
+        true
+    }
+
+    fn fetch_astro(&self, address: String, date: Date) -> Option<AstroReport> {
+        let item = self.get_item(address, &date)?;
+        Some(AstroReport {
+            sunrise: item.sunrise,
+            sunset: item.sunset,
+            moon_phase: item
+                .moon
+                .clone()
+                .unwrap_or_else(|| Astro::moon_phase(&item.date).to_owned()),
+            ..AstroReport::new(self.name, item.address, item.geo, item.date, item.offset)
+        })
+    }
+
+    fn fetch(&self, address: String, date: Date) -> Option<WeatherReport> {
+        self.get_item(address, &date).map(|item| self.report(item))
+    }
 }
 
 impl Default for AerisWeather {
@@ -495,3 +629,167 @@ impl Default for AerisWeather {
         AerisWeather::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map, Value};
+
+    use super::AerisWeather;
+    use crate::{
+        geo::Geo,
+        http::{HttpError, MockHttp},
+        work::Provider,
+    };
+
+    #[test]
+    fn test_credentials() {
+        let provider = AerisWeather {
+            key: Some(("my_client_id".to_owned(), "0123456789abcdef".to_owned())),
+            ..AerisWeather::new()
+        };
+        assert_eq!(
+            provider.credentials(),
+            vec![
+                ("client_id", "my_client_id".to_owned()),
+                ("client_secret", "***cdef".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error() {
+        let error = |json: Value| AerisWeather::error(json.as_object().unwrap());
+        assert_eq!(
+            error(json!({
+                "success": false,
+                "error": {"code": "invalid_client", "description": "The client_id/secret is invalid."},
+                "response": []
+            })),
+            Some("invalid_client: The client_id/secret is invalid.".to_owned())
+        );
+        assert_eq!(
+            error(json!({"success": false, "error": {"code": "invalid_location"}})),
+            Some("invalid_location".to_owned())
+        );
+        assert_eq!(
+            error(json!({"success": false})),
+            Some("unknown error".to_owned())
+        );
+        // The warning of the successful answer is not an error
+        assert_eq!(
+            error(json!({
+                "success": true,
+                "error": {"code": "warn_no_data", "description": "No data available."},
+                "response": []
+            })),
+            None
+        );
+        assert_eq!(error(json!({"response": {"ob": {}}})), None);
+    }
+
+    #[test]
+    fn test_detect_floats() {
+        let json = r#"{
+            "timestamp": 1683803100,
+            "dateTimeISO": "2023-05-11T20:05:00+09:00",
+            "tempC": 18,
+            "humidity": 57.6,
+            "pressureMB": 1014,
+            "windDirDEG": 359.7,
+            "snowDepthCM": 1.5,
+            "precipMM": 0.5,
+            "uvi": 2.4,
+            "sky": 40
+        }"#;
+        let items: Map<String, Value> = serde_json::from_str(json).unwrap();
+        let geo = Geo {
+            lat: "50.45".to_owned(),
+            lon: "30.52".to_owned(),
+            address: "Kyiv, Ukraine".to_owned(),
+            importance: None,
+        };
+        let item = AerisWeather::new()
+            .detect(&items, geo, "Kyiv".to_owned())
+            .unwrap();
+        assert_eq!(item.humidity, Some(58));
+        assert_eq!(item.pressure_mb, Some(1014));
+        assert_eq!(item.wind_dir_deg, Some(0));
+        assert_eq!(item.snow_depth_cm, Some(1.5));
+        assert_eq!(item.precip_mm, Some(0.5));
+        assert_eq!(item.uvi, Some(2.4));
+        assert_eq!(item.sky, Some(40));
+        assert_eq!(
+            item.offset.map(|offset| offset.to_string()),
+            Some("+09:00".to_owned())
+        );
+        let report = AerisWeather::new().report(item);
+        assert_eq!(report.precipitation, Some(0.5));
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut provider = AerisWeather::new();
+        assert_eq!(provider.serialize(), "AerisWeather::");
+        assert!(provider.deserialize("AerisWeather:id1:secret2"));
+        assert_eq!(provider.key, Some(("id1".to_owned(), "secret2".to_owned())));
+        assert_eq!(provider.serialize(), "AerisWeather:id1:secret2");
+        assert!(provider.deserialize("AerisWeather::"));
+        assert_eq!(provider.key, None);
+        assert_eq!(provider.serialize(), "AerisWeather::");
+        // Both fields are required, the partial credentials are damaged
+        assert!(!provider.deserialize("AerisWeather:id1:"));
+        assert!(!provider.deserialize("AerisWeather::secret2"));
+        assert!(!provider.deserialize("AerisWeather:id1"));
+        assert!(!provider.deserialize("OpenWeather:id1:secret2"));
+        assert_eq!(provider.key, None);
+        // The colon is a part of the client_secret
+        assert!(provider.deserialize("AerisWeather:id1:secret2:extra"));
+        assert_eq!(
+            provider.key,
+            Some(("id1".to_owned(), "secret2:extra".to_owned()))
+        );
+        assert_eq!(provider.serialize(), "AerisWeather:id1:secret2:extra");
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut provider = AerisWeather::new();
+        assert!(provider.deserialize("AerisWeather:id1:secret2"));
+        provider.reset();
+        assert!(!provider.has_credentials());
+        assert_eq!(provider.serialize(), "AerisWeather::");
+    }
+
+    #[test]
+    fn test_check() {
+        let geo = Geo {
+            lat: "50.4501".to_owned(),
+            lon: "30.5234".to_owned(),
+            address: "Kyiv".to_owned(),
+            importance: None,
+        };
+        let provider = |json: &str| AerisWeather {
+            name: "AerisWeather",
+            client: Box::new(MockHttp::new(&[("observations/50.4501,30.5234", json)])),
+            key: Some(("id1".to_owned(), "secret2".to_owned())),
+            env_key: None,
+        };
+        assert_eq!(
+            provider(r#"{"success": true, "response": {}}"#).check(&geo),
+            Some(Ok(()))
+        );
+        let rejected = r#"{"success": false, "error": {"code": "invalid_client", "description": "Invalid client_id"}}"#;
+        assert_eq!(
+            provider(rejected)
+                .check(&geo)
+                .map(|r| r.map_err(|e| e.kind())),
+            Some(Err("auth-failed"))
+        );
+        let failed = r#"{"success": false, "error": {"code": "internal_error"}}"#;
+        assert!(matches!(
+            provider(failed).check(&geo),
+            Some(Err(HttpError::Status { status: 200, .. }))
+        ));
+        assert_eq!(AerisWeather::new().check(&geo), None);
+    }
+}
@@ -0,0 +1,388 @@
+//! Weather provider [MET Norway](https://api.met.no) (Yr).
+//!
+//! The Locationforecast API is free and keyless, but requires a descriptive `User-Agent`.
+
+use chrono::{DateTime, Local};
+use serde_json::{Map, Value};
+
+use crate::{
+    budget::Budget,
+    geo::Geo,
+    http::{Http, HttpClient, HttpError},
+    init::{Date, Options},
+    log::Log,
+    provider::{average_item, closest_item, mean, Trend},
+    wind::WindDeg,
+    work::{Capability, Provider, WeatherReport},
+};
+
+/// Describes 'MetNorway' provider
+///
+/// * `name: &'static str` - Provider name.
+/// * `client: Box<dyn HttpClient>` - Source of the server answers.
+pub struct MetNorway {
+    /// Provider name.
+    name: &'static str,
+    /// Source of the server answers.
+    client: Box<dyn HttpClient>,
+}
+
+/// MetNorway data format for one item
+#[derive(Debug, Clone)]
+struct MetNorwayItem {
+    /// Time of the forecast. Local
+    date: DateTime<Local>,
+    /// Request Address
+    address: String,
+    /// Geo position
+    geo: Geo,
+    /// Weather symbol for the next hours, e.g. "partlycloudy_day"
+    symbol: Option<String>,
+    /// Air temperature. Metric: Celsius
+    temp: Option<f32>,
+    /// Relative humidity, %
+    humidity: Option<f32>,
+    /// Air pressure at sea level, hPa
+    pressure: Option<f32>,
+    /// Wind speed, meter/sec
+    speed: Option<f32>,
+    /// Wind degrees (meteorological)
+    deg: Option<u16>,
+    /// Wind direction (meteorological)
+    dir: WindDeg,
+    /// Cloud area fraction, %
+    cloud: Option<f32>,
+    /// Precipitation amount for the next hour (or 6 hours), mm
+    precip: Option<f32>,
+    /// Number of the items averaged for the `--average` flag, 0 - the closest item only
+    average: usize,
+    /// Trend of the temperature, pressure and humidity versus the adjacent items of the forecast
+    trend: Trend,
+}
+
+impl MetNorway {
+    /// Create new provider
+    pub fn new() -> MetNorway {
+        MetNorway {
+            name: "MetNorway",
+            client: Box::new(Http),
+        }
+    }
+
+    /// Request the forecast, the server accepts at most 4 decimals of the coordinates.
+    ///
+    /// * `lat: f64` - Latitude.
+    /// * `lon: f64` - Longitude.
+    fn request(&self, lat: f64, lon: f64) -> Result<Value, HttpError> {
+        let url = format!(
+            "https://api.met.no/weatherapi/locationforecast/2.0/compact?lat={:.4}&lon={:.4}",
+            lat, lon
+        );
+        Budget::get_json(
+            self.name,
+            self.client.as_ref(),
+            &url,
+            &[("User-Agent", Options::get().user_agent())],
+        )
+    }
+
+    /// Load data from provider
+    fn get_json(&self, address: &str) -> Option<(Map<String, Value>, Geo)> {
+        // Find geo coordinates by address
+        let geo = Geo::find(address)?;
+        // The server accepts at most 4 decimals of the coordinates
+        let (lat, lon) = match (geo.lat.parse::<f64>(), geo.lon.parse::<f64>()) {
+            (Ok(lat), Ok(lon)) => (lat, lon),
+            _ => {
+                Log::error("Sorry, we couldn't find your address");
+                return None;
+            }
+        };
+        let json = match self.request(lat, lon) {
+            Ok(Value::Object(json)) => json,
+            Ok(_) => {
+                Log::info("Unable to recognize json response from server.");
+                return None;
+            }
+            Err(e) => {
+                e.log(Some(self.name));
+                return None;
+            }
+        };
+        Some((json, geo))
+    }
+
+    /// Getting weather forecast for `date`
+    fn get_date(&self, address: String, date: &DateTime<Local>) -> Option<MetNorwayItem> {
+        let list = self.get_list(address)?;
+        let trend = Trend::around(
+            &list,
+            date,
+            true,
+            |item| item.date,
+            |item| [item.temp, item.pressure, item.humidity],
+        );
+        // Hourly forecast: the upcoming item is preferred, `--average` takes the nearest items
+        let mut item = match Options::get().average {
+            Some(count) => average_item(
+                list,
+                date,
+                count,
+                |item| item.date,
+                |item, items| {
+                    item.temp = mean(items, |item| item.temp);
+                    item.speed = mean(items, |item| item.speed);
+                    item.humidity = mean(items, |item| item.humidity);
+                    item.pressure = mean(items, |item| item.pressure);
+                    item.average = items.len();
+                },
+            )?,
+            None => closest_item(list, date, true, |item| item.date)?,
+        };
+        item.trend = trend;
+        Some(item)
+    }
+
+    /// Getting all items of weather forecast
+    fn get_list(&self, address: String) -> Option<Vec<MetNorwayItem>> {
+        // Load json from provider
+        let (items, geo) = self.get_json(&address)?;
+        let its = items
+            .get("properties")
+            .and_then(|p| p.get("timeseries"))
+            .and_then(|t| t.as_array())
+            .or_else(|| {
+                Log::info("The MetNorway server did not provide weather forecast data");
+                None
+            })?;
+        // Load all MetNorwayItem to vector
+        let mut list = Vec::with_capacity(its.len());
+        for item in its {
+            if let Value::Object(map) = item {
+                let res = self.detect(map, geo.clone(), address.clone());
+                if let Some(item) = res {
+                    list.push(item);
+                }
+            }
+        }
+        if list.is_empty() {
+            return None;
+        }
+        Some(list)
+    }
+
+    /// Getting weather forecast for `date`
+    fn get_item(&self, address: String, date: &Date) -> Option<MetNorwayItem> {
+        match date {
+            // The current slot, not the upcoming one
+            Date::Now => closest_item(self.get_list(address)?, &Local::now(), false, |item| {
+                item.date
+            }),
+            Date::Set(dt) => self.get_date(address, dt),
+            Date::Error | Date::Range(..) => None,
+        }
+    }
+
+    /// Parse json answer from server
+    fn detect(
+        &self,
+        items: &Map<String, Value>,
+        geo: Geo,
+        address: String,
+    ) -> Option<MetNorwayItem> {
+        let date = items
+            .get("time")
+            .and_then(|s| s.as_str())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&Local))?;
+        let data = items.get("data")?;
+        let details = data.get("instant").and_then(|i| i.get("details"));
+        let float = |name: &str| {
+            details
+                .and_then(|d| d.get(name))
+                .and_then(|s| s.as_f64())
+                .map(|s| s as f32)
+        };
+        let next = data
+            .get("next_1_hours")
+            .or_else(|| data.get("next_6_hours"));
+        let symbol = next
+            .and_then(|n| n.get("summary"))
+            .and_then(|s| s.get("symbol_code"))
+            .and_then(|s| s.as_str())
+            .map(|s| s.to_owned());
+        let precip = next
+            .and_then(|n| n.get("details"))
+            .and_then(|d| d.get("precipitation_amount"))
+            .and_then(|s| s.as_f64())
+            .map(|s| s as f32);
+        let deg = float("wind_from_direction").and_then(|s| WindDeg::normalize(s as f64));
+        let dir = WindDeg::get(deg);
+
+        Some(MetNorwayItem {
+            date,
+            address,
+            geo,
+            symbol,
+            temp: float("air_temperature"),
+            humidity: float("relative_humidity"),
+            pressure: float("air_pressure_at_sea_level"),
+            speed: float("wind_speed"),
+            deg,
+            dir,
+            cloud: float("cloud_area_fraction"),
+            precip,
+            average: 0,
+            trend: Trend::default(),
+        })
+    }
+
+    /// Convert result to the normalized weather report
+    fn report(&self, item: MetNorwayItem) -> WeatherReport {
+        WeatherReport {
+            condition: item.symbol,
+            temperature: item.temp,
+            humidity: item.humidity.map(|s| s.round() as u32),
+            pressure: item.pressure,
+            // meter/sec to km/hour
+            wind_speed: item.speed.map(|s| s * 3.6),
+            wind_deg: item.deg,
+            wind_dir: item.dir,
+            precipitation: item.precip,
+            cloud_cover: item.cloud.map(|s| s.round() as u32),
+            average: item.average,
+            trend: item.trend,
+            ..WeatherReport::new(self.name, item.address, item.geo, item.date, None)
+        }
+    }
+}
+
+impl Provider for MetNorway {
+    fn serialize(&self) -> String {
+        format!("{}:", self.name)
+    }
+
+    fn deserialize(&mut self, data: &str) -> bool {
+        // The provider is keyless, only the name is stored
+        data.split(':').next() == Some(self.name)
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[
+            Capability::Temperature,
+            Capability::Hourly,
+            Capability::DateRange,
+            Capability::Coordinates,
+        ]
+    }
+
+    fn forecast_days(&self) -> Option<i64> {
+        // The forecast is for about 10 days including today
+        Some(9)
+    }
+
+    fn check(&self, geo: &Geo) -> Option<Result<(), HttpError>> {
+        let (lat, lon) = (geo.lat.parse::<f64>().ok()?, geo.lon.parse::<f64>().ok()?);
+        Some(self.request(lat, lon).map(|_| ()))
+    }
+
+    fn configure(&mut self) {
+        print!(
+            "The {} provider is free and does not require an API key.",
+            self.name
+        );
+    }
+
+    fn fetch_range(
+        &self,
+        address: String,
+        start: &DateTime<Local>,
+        end: &DateTime<Local>,
+    ) -> Option<Vec<WeatherReport>> {
+        let list = self.get_list(address)?;
+        let reports = list.into_iter().map(|item| self.report(item)).collect();
+        Some(WeatherReport::daily(reports, start, end))
+    }
+
+    fn key_from_env(&mut self) {}
+
+    fn fetch(&self, address: String, date: Date) -> Option<WeatherReport> {
+        self.get_item(address, &date).map(|item| self.report(item))
+    }
+}
+
+impl Default for MetNorway {
+    fn default() -> MetNorway {
+        MetNorway::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+
+    use super::MetNorway;
+    use crate::{geo::Geo, work::Provider};
+
+    #[test]
+    fn test_detect() {
+        let json = r#"{
+            "time": "2023-05-11T12:00:00Z",
+            "data": {
+                "instant": {"details": {
+                    "air_pressure_at_sea_level": 1012.3,
+                    "air_temperature": 17.8,
+                    "cloud_area_fraction": 42.0,
+                    "relative_humidity": 55.1,
+                    "wind_from_direction": 201.4,
+                    "wind_speed": 3.2
+                }},
+                "next_1_hours": {
+                    "summary": {"symbol_code": "rain"},
+                    "details": {"precipitation_amount": 0.4}
+                }
+            }
+        }"#;
+        let items: Map<String, Value> = serde_json::from_str(json).unwrap();
+        let geo = Geo {
+            lat: "59.9133".to_owned(),
+            lon: "10.7389".to_owned(),
+            address: "Oslo, Norway".to_owned(),
+            importance: None,
+        };
+        let item = MetNorway::new()
+            .detect(&items, geo, "Oslo".to_owned())
+            .unwrap();
+        assert_eq!(item.symbol, Some("rain".to_owned()));
+        assert_eq!(item.temp, Some(17.8));
+        assert_eq!(item.deg, Some(201));
+        assert_eq!(item.precip, Some(0.4));
+
+        // The time of the forecast is required
+        let items: Map<String, Value> = serde_json::from_str(r#"{"data": {}}"#).unwrap();
+        let geo = Geo {
+            lat: String::new(),
+            lon: String::new(),
+            address: String::new(),
+            importance: None,
+        };
+        assert!(MetNorway::new()
+            .detect(&items, geo, String::new())
+            .is_none());
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut provider = MetNorway::new();
+        assert_eq!(provider.serialize(), "MetNorway:");
+        assert!(provider.deserialize("MetNorway:"));
+        // The old files could have a key, it's ignored
+        assert!(provider.deserialize("MetNorway:abc123"));
+        assert_eq!(provider.serialize(), "MetNorway:");
+        assert!(!provider.deserialize("OpenWeather:abc123"));
+    }
+}
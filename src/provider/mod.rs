@@ -1,7 +1,758 @@
 //! Weather providers.
 //!
 
+use chrono::{DateTime, FixedOffset, Local};
+use serde::Serialize;
+
+use crate::{
+    format::Format,
+    init::{Date, Options, PressureUnit, Units},
+    log::Log,
+    out::Out,
+    table::Table,
+};
+
 pub mod accuweather;
 pub mod aerisweather;
+pub mod metno;
+pub mod onecall;
 pub mod openweather;
+pub mod tomorrowio;
+pub mod visualcrossing;
 pub mod weatherapi;
+
+/// The largest gap between the requested date and the selected forecast item without a warning, hours.
+pub const MAX_GAP: i64 = 24;
+
+/// Air quality for the `--aqi` flag.
+///
+/// * `pm2_5: Option<f32>` - Fine particulate matter PM2.5, μg/m³.
+/// * `pm10: Option<f32>` - Coarse particulate matter PM10, μg/m³.
+/// * `index: Option<u32>` - Overall air quality index.
+/// * `scale: &'static str` - Scale of the index, it differs between the providers.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AirQuality {
+    /// Fine particulate matter PM2.5, μg/m³.
+    pub pm2_5: Option<f32>,
+    /// Coarse particulate matter PM10, μg/m³.
+    pub pm10: Option<f32>,
+    /// Overall air quality index.
+    pub index: Option<u32>,
+    /// Scale of the index, it differs between the providers.
+    pub scale: &'static str,
+}
+
+impl AirQuality {
+    /// Field names and values for displaying.
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (
+                "PM2.5",
+                self.pm2_5
+                    .map_or("None".to_owned(), |s| format!("{} μg/m³", Format::num(s))),
+            ),
+            (
+                "PM10",
+                self.pm10
+                    .map_or("None".to_owned(), |s| format!("{} μg/m³", Format::num(s))),
+            ),
+            (
+                "Air quality index",
+                self.index
+                    .map_or("None".to_owned(), |s| format!("{} ({})", s, self.scale)),
+            ),
+        ]
+    }
+
+    /// Displays the air quality section of the forecast.
+    ///
+    /// * `air: Option<&AirQuality>` - Air quality, `None` if the server did not provide it.
+    pub fn show(air: Option<&AirQuality>) {
+        match air {
+            Some(air) => Table::show(Some("Air quality"), &air.rows()),
+            None => Out::line("Air quality: None"),
+        }
+    }
+}
+
+/// Trend arrows of the selected forecast item versus the adjacent items of the series:
+/// `↑` - rising, `↓` - falling, `→` - steady, `None` - unknown.
+///
+/// * `temp: Option<char>` - Temperature trend.
+/// * `pressure: Option<char>` - Atmospheric pressure trend.
+/// * `humidity: Option<char>` - Humidity trend.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Trend {
+    /// Temperature trend.
+    pub temp: Option<char>,
+    /// Atmospheric pressure trend.
+    pub pressure: Option<char>,
+    /// Humidity trend.
+    pub humidity: Option<char>,
+}
+
+impl Trend {
+    /// Trend around the item that `closest_item` selects for the same arguments.
+    ///
+    /// * `items: &[T]` - Forecast items.
+    /// * `date: &DateTime<Local>` - Requested date.
+    /// * `prefer_future: bool` - See `closest_item`.
+    /// * `item_date: impl Fn(&T) -> DateTime<Local>` - Date of the item.
+    /// * `values: impl Fn(&T) -> [Option<f32>; 3]` - Temperature, pressure and humidity of the item.
+    pub fn around<T>(
+        items: &[T],
+        date: &DateTime<Local>,
+        prefer_future: bool,
+        item_date: impl Fn(&T) -> DateTime<Local>,
+        values: impl Fn(&T) -> [Option<f32>; 3],
+    ) -> Trend {
+        let index = match closest_index(items, date, prefer_future, &item_date) {
+            Some(index) => index,
+            None => return Trend::default(),
+        };
+        let value = |index: Option<usize>, field: usize| {
+            index
+                .and_then(|index| items.get(index))
+                .and_then(|item| values(item)[field])
+        };
+        let arrow = |field: usize, step: f32| {
+            Trend::arrow(
+                value(index.checked_sub(1), field),
+                value(Some(index), field),
+                value(Some(index + 1), field),
+                step,
+            )
+        };
+        Trend {
+            temp: arrow(0, 0.5),
+            pressure: arrow(1, 1.0),
+            humidity: arrow(2, 3.0),
+        }
+    }
+
+    /// Arrow of the change from the previous to the next value,
+    /// the selected value stands for the missing neighbour at the ends of the series.
+    ///
+    /// * `before: Option<f32>` - Value of the previous item.
+    /// * `current: Option<f32>` - Value of the selected item.
+    /// * `after: Option<f32>` - Value of the next item.
+    /// * `step: f32` - The smallest change that isn't steady.
+    pub fn arrow(
+        before: Option<f32>,
+        current: Option<f32>,
+        after: Option<f32>,
+        step: f32,
+    ) -> Option<char> {
+        let (from, to) = match (before, current?, after) {
+            (Some(before), _, Some(after)) => (before, after),
+            (Some(before), current, None) => (before, current),
+            (None, current, Some(after)) => (current, after),
+            (None, _, None) => return None,
+        };
+        Some(match to - from {
+            delta if delta >= step => '↑',
+            delta if delta <= -step => '↓',
+            _ => '→',
+        })
+    }
+
+    /// Displayed value with the arrow, e.g. `18.5 °C ↑`.
+    ///
+    /// * `text: String` - Displayed value.
+    /// * `arrow: Option<char>` - Trend of the value.
+    pub fn mark(text: String, arrow: Option<char>) -> String {
+        match arrow {
+            Some(arrow) if text != "None" => format!("{} {}", text, arrow),
+            _ => text,
+        }
+    }
+}
+
+/// Displays the active weather alerts, nothing if there are no alerts.
+///
+/// The alerts are printed before the forecast, they matter more than the exact temperature.
+///
+/// * `alerts: &[String]` - Alert texts.
+pub fn show_alerts(alerts: &[String]) {
+    if alerts.is_empty() {
+        return;
+    }
+    Out::line("⚠ Alerts:");
+    for alert in alerts {
+        Out::line(&format!("  ⚠ {}", alert));
+    }
+}
+
+/// Displays the `Units: ...` line at the top of the forecast.
+///
+/// It's shown for the imperial units, the `--pressure-unit` flag or the `--units-in-header` flag,
+/// the metric forecast in the units of the provider is displayed without it.
+///
+/// * `speed: &str` - Metric unit of the wind speed of the provider, `km/h` or `meter/sec`.
+/// * `pressure: &str` - Unit name of the pressure of the provider, `hPa` or `mbar`.
+pub fn show_units(speed: &str, pressure: &str) {
+    let options = Options::get();
+    let units = options.units();
+    if options.units_in_header || units == Units::Imperial || options.pressure_unit.is_some() {
+        Out::line(&units_line(units, speed, pressure, options.pressure_unit));
+    }
+}
+
+/// Summary of the displayed units, e.g. `Units: metric (°C, km/h, hPa)`.
+///
+/// * `units: Units` - Unit system of the displayed values.
+/// * `speed: &str` - Metric unit of the wind speed of the provider.
+/// * `pressure: &str` - Unit name of the pressure of the provider.
+/// * `pressure_unit: Option<PressureUnit>` - Unit of `--pressure-unit`.
+pub fn units_line(
+    units: Units,
+    speed: &str,
+    pressure: &str,
+    pressure_unit: Option<PressureUnit>,
+) -> String {
+    let (_, temp) = Format::temp_in(0.0, units);
+    let speed = match units {
+        Units::Metric => speed,
+        Units::Imperial => Format::speed_in(0.0, units).1,
+    };
+    let (_, pressure) = Format::pressure_in(0.0, pressure, pressure_unit);
+    format!("Units: {} ({}, {}, {})", units, temp, speed, pressure)
+}
+
+/// Rows of `configure <provider> --show` for the api key, e.g. `("API key", "***cdef")`.
+///
+/// * `key: Option<&str>` - Saved api key.
+/// * `env_key: Option<&str>` - Api key from the environment variable, it's used instead of the saved one.
+pub fn key_rows(key: Option<&str>, env_key: Option<&str>) -> Vec<(&'static str, String)> {
+    let mut rows = vec![("API key", key.map_or("not set".to_owned(), Format::secret))];
+    if let Some(env_key) = env_key {
+        rows.push((
+            "API key from the environment (used)",
+            Format::secret(env_key),
+        ));
+    }
+    rows
+}
+
+/// Report that the provider did not return the forecast.
+///
+/// Nothing is reported for `--dry-run`, the requests are not sent by design.
+pub fn no_forecast() {
+    if !Options::get().dry_run {
+        Log::error(
+            "It is not possible to determine the date of the weather forecast sent by the provider",
+        );
+    }
+}
+
+/// Select the forecast item for the requested date.
+///
+/// * `items: Vec<T>` - Forecast items.
+/// * `date: &DateTime<Local>` - Requested date.
+/// * `prefer_future: bool` - True: the nearest item at or after `date` is selected,
+///   the closest item is used only if there are no such items. Suitable for hourly forecasts.
+///   False: the closest item is selected. Suitable for daily forecasts.
+/// * `item_date: impl Fn(&T) -> DateTime<Local>` - Date of the item.
+///
+/// If the selected item is too far from `date`, e.g. the date is beyond the forecast of the provider,
+/// a warning is reported.
+pub fn closest_item<T>(
+    items: Vec<T>,
+    date: &DateTime<Local>,
+    prefer_future: bool,
+    item_date: impl Fn(&T) -> DateTime<Local>,
+) -> Option<T> {
+    let index = closest_index(&items, date, prefer_future, &item_date)?;
+    let count = items.len();
+    let item = items.into_iter().nth(index)?;
+    if Options::get().explain {
+        Log::info(&explain(count, date, &item_date(&item), prefer_future));
+    }
+    if let Some(warning) = gap_warning(date, &item_date(&item)) {
+        Log::info(&warning);
+    }
+    Some(item)
+}
+
+/// Index of the forecast item for the requested date, see `closest_item`.
+fn closest_index<T>(
+    items: &[T],
+    date: &DateTime<Local>,
+    prefer_future: bool,
+    item_date: &impl Fn(&T) -> DateTime<Local>,
+) -> Option<usize> {
+    let diff = |item: &T| item_date(item).signed_duration_since(*date).num_seconds();
+    let future = match prefer_future {
+        true => items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| diff(item) >= 0)
+            .min_by_key(|(_, item)| diff(item))
+            .map(|(index, _)| index),
+        false => None,
+    };
+    future.or_else(|| {
+        items
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, item)| diff(item).abs())
+            .map(|(index, _)| index)
+    })
+}
+
+/// Average of the items closest to `date` for the `--average` flag.
+///
+/// * `items: Vec<T>` - Forecast items.
+/// * `date: &DateTime<Local>` - Requested date.
+/// * `count: usize` - Number of the averaged items.
+/// * `item_date: impl Fn(&T) -> DateTime<Local>` - Date of the item.
+/// * `merge: impl FnOnce(&mut T, &[T])` - Sets the numeric fields of the closest item to the means of the items.
+///
+/// The other fields, e.g. the condition text, are taken from the closest item.
+pub fn average_item<T: Clone>(
+    items: Vec<T>,
+    date: &DateTime<Local>,
+    count: usize,
+    item_date: impl Fn(&T) -> DateTime<Local>,
+    merge: impl FnOnce(&mut T, &[T]),
+) -> Option<T> {
+    let received = items.len();
+    let mut items = items;
+    items.sort_by_key(|item| {
+        item_date(item)
+            .signed_duration_since(*date)
+            .num_seconds()
+            .abs()
+    });
+    items.truncate(count);
+    let mut item = items.first()?.clone();
+    merge(&mut item, &items);
+    if Options::get().explain {
+        Log::info(&format!(
+            "Explain: {} items were received, the {} closest to {} were averaged.",
+            received,
+            items.len(),
+            date.format("%Y-%m-%d %H:%M")
+        ));
+    }
+    if let Some(warning) = gap_warning(date, &item_date(&item)) {
+        Log::info(&warning);
+    }
+    Some(item)
+}
+
+/// Mean of the values of the items, the missing values are skipped.
+///
+/// * `items: &[T]` - Forecast items.
+/// * `value: impl Fn(&T) -> Option<f32>` - Value of the item.
+///
+/// Return
+///
+/// `Option<f32>` - `None` if no item has the value.
+pub fn mean<T>(items: &[T], value: impl Fn(&T) -> Option<f32>) -> Option<f32> {
+    let values: Vec<f32> = items.iter().filter_map(value).collect();
+    match values.len() {
+        0 => None,
+        len => Some(values.iter().sum::<f32>() / len as f32),
+    }
+}
+
+/// Displays the warning if the current conditions are older than `--max-age`.
+///
+/// * `found: &DateTime<Local>` - Time the data was calculated by the provider.
+/// * `date: &str` - The requested date, the warning is displayed for `now` only.
+pub fn show_age(found: &DateTime<Local>, date: &str) {
+    // The forecast for a date is calculated in advance, only the current conditions get old
+    if date != Date::Now.to_string() {
+        return;
+    }
+    if let Some(warning) = age_warning(found, &Local::now(), Options::get().max_age()) {
+        Out::line(&warning);
+    }
+}
+
+/// Warning about the old current conditions.
+///
+/// * `found: &DateTime<Local>` - Time the data was calculated by the provider.
+/// * `now: &DateTime<Local>` - Current time.
+/// * `max_age: u64` - The largest age without a warning, minutes, 0 - no warning.
+///
+/// Return
+///
+/// `Option<String>` - Warning text, e.g. `⚠ The observation is 2 h old.`
+pub fn age_warning(found: &DateTime<Local>, now: &DateTime<Local>, max_age: u64) -> Option<String> {
+    let minutes = now.signed_duration_since(*found).num_minutes();
+    if max_age == 0 || minutes <= max_age as i64 {
+        return None;
+    }
+    let age = match minutes {
+        minutes if minutes < 120 => format!("{} min", minutes),
+        minutes => format!("{} h", minutes / 60),
+    };
+    Some(format!("⚠ The observation is {} old.", age))
+}
+
+/// Displays the note that the values are averaged, nothing for a single item.
+///
+/// * `count: usize` - Number of the averaged items.
+pub fn show_average(count: usize) {
+    if count > 1 {
+        Out::line(&format!(
+            "Average of {} forecast items around the requested time: temperature, wind, humidity and pressure.",
+            count
+        ));
+    }
+}
+
+/// Temperature curve of the day of `date` for the `--graph` flag.
+///
+/// * `items: &[T]` - Forecast items.
+/// * `date: &DateTime<Local>` - Requested date, the day is taken in the timezone of the location.
+/// * `offset: Option<FixedOffset>` - UTC offset of the location, the system timezone is used when it's unknown.
+/// * `point: impl Fn(&T) -> (DateTime<Local>, Option<f32>)` - Date and temperature of the item.
+pub fn day_curve<T>(
+    items: &[T],
+    date: &DateTime<Local>,
+    offset: Option<FixedOffset>,
+    point: impl Fn(&T) -> (DateTime<Local>, Option<f32>),
+) -> Vec<(DateTime<Local>, f32)> {
+    let day = |dt: &DateTime<Local>| match offset {
+        Some(offset) => dt.with_timezone(&offset).date_naive(),
+        None => dt.date_naive(),
+    };
+    items
+        .iter()
+        .map(point)
+        .filter(|(dt, _)| day(dt) == day(date))
+        .filter_map(|(dt, temp)| Some((dt, temp?)))
+        .collect()
+}
+
+/// Provenance note of the selected forecast item for the `--explain` flag.
+///
+/// * `count: usize` - Number of the candidate items in the answer of the server.
+/// * `date: &DateTime<Local>` - Requested date.
+/// * `found: &DateTime<Local>` - Date of the selected item.
+/// * `prefer_future: bool` - The item at or after `date` was preferred, see `closest_item`.
+pub fn explain(
+    count: usize,
+    date: &DateTime<Local>,
+    found: &DateTime<Local>,
+    prefer_future: bool,
+) -> String {
+    let minutes = found.signed_duration_since(*date).num_minutes();
+    let rule = match (prefer_future, minutes < 0) {
+        (true, false) => "the nearest item at or after the requested time",
+        (true, true) => "the closest item as there are no later items",
+        (false, _) => "the closest item",
+    };
+    let delta = match (minutes.abs() / 60, minutes.abs() % 60) {
+        (0, 0) => "the exact time".to_owned(),
+        (0, min) => format!("{} min", min),
+        (hours, 0) => format!("{} h", hours),
+        (hours, min) => format!("{} h {} min", hours, min),
+    };
+    let direction = match minutes {
+        m if m > 0 => " later",
+        m if m < 0 => " earlier",
+        _ => "",
+    };
+    format!(
+        "Explain: {} items were received, selected {}. Requested {}, selected {}, {}{}.",
+        count,
+        rule,
+        date.format("%Y-%m-%d %H:%M"),
+        found.format("%Y-%m-%d %H:%M"),
+        delta,
+        direction
+    )
+}
+
+/// Warning about the forecast item that is not for the requested date.
+///
+/// * `date: &DateTime<Local>` - Requested date.
+/// * `found: &DateTime<Local>` - Date of the selected item.
+///
+/// Return
+///
+/// `Option<String>` - Warning text if the gap exceeds `MAX_GAP` hours.
+pub fn gap_warning(date: &DateTime<Local>, found: &DateTime<Local>) -> Option<String> {
+    let hours = found.signed_duration_since(*date).num_hours().abs();
+    if hours <= MAX_GAP {
+        return None;
+    }
+    Some(format!(
+        "Warning: the provider has no forecast for {}. The closest forecast is for {}, {} hours away.",
+        date.format("%Y-%m-%d %H:%M"),
+        found.format("%Y-%m-%d %H:%M"),
+        hours
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+
+    use super::{
+        age_warning, average_item, closest_item, day_curve, explain, gap_warning, key_rows, mean,
+        units_line, AirQuality, Trend,
+    };
+    use crate::init::{PressureUnit, Units};
+
+    fn date(value: &str) -> DateTime<Local> {
+        let dt = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap();
+        Local.from_local_datetime(&dt).unwrap()
+    }
+
+    #[test]
+    fn test_closest_item() {
+        let items = vec![
+            date("2023-05-02 09:00"),
+            date("2023-05-02 12:00"),
+            date("2023-05-02 15:00"),
+        ];
+        let pick = |at: &str, future: bool| closest_item(items.clone(), &date(at), future, |d| *d);
+        assert_eq!(
+            pick("2023-05-02 10:00", false),
+            Some(date("2023-05-02 09:00"))
+        );
+        assert_eq!(
+            pick("2023-05-02 10:00", true),
+            Some(date("2023-05-02 12:00"))
+        );
+        assert_eq!(
+            pick("2023-05-02 12:00", true),
+            Some(date("2023-05-02 12:00"))
+        );
+        // No future items
+        assert_eq!(
+            pick("2023-05-03 10:00", true),
+            Some(date("2023-05-02 15:00"))
+        );
+        assert_eq!(
+            closest_item(Vec::new(), &date("2023-05-02 10:00"), true, |d| *d),
+            None
+        );
+    }
+
+    #[test]
+    fn test_trend() {
+        assert_eq!(
+            Trend::arrow(Some(10.0), Some(11.0), Some(12.0), 0.5),
+            Some('↑')
+        );
+        assert_eq!(
+            Trend::arrow(Some(12.0), Some(11.0), Some(10.0), 0.5),
+            Some('↓')
+        );
+        assert_eq!(
+            Trend::arrow(Some(10.0), Some(12.0), Some(10.2), 0.5),
+            Some('→')
+        );
+        // The ends of the series
+        assert_eq!(Trend::arrow(None, Some(10.0), Some(12.0), 0.5), Some('↑'));
+        assert_eq!(
+            Trend::arrow(Some(1015.0), Some(1012.0), None, 1.0),
+            Some('↓')
+        );
+        assert_eq!(Trend::arrow(None, Some(10.0), None, 0.5), None);
+        assert_eq!(Trend::arrow(Some(10.0), None, Some(12.0), 0.5), None);
+        let items = vec![
+            (date("2023-05-02 09:00"), 14.0, 1016.0, 60.0),
+            (date("2023-05-02 12:00"), 18.0, 1014.0, 50.0),
+            (date("2023-05-02 15:00"), 20.0, 1012.0, 49.0),
+        ];
+        let trend = |at: &str, future: bool| {
+            Trend::around(
+                &items,
+                &date(at),
+                future,
+                |item| item.0,
+                |item| [Some(item.1), Some(item.2), Some(item.3)],
+            )
+        };
+        assert_eq!(
+            trend("2023-05-02 10:00", true),
+            Trend {
+                temp: Some('↑'),
+                pressure: Some('↓'),
+                humidity: Some('↓')
+            }
+        );
+        assert_eq!(trend("2023-05-02 16:00", false).humidity, Some('→'));
+        assert_eq!(
+            Trend::around(
+                &[] as &[i32],
+                &date("2023-05-02 10:00"),
+                true,
+                |_| date("2023-05-02 10:00"),
+                |_| [None; 3]
+            ),
+            Trend::default()
+        );
+        assert_eq!(Trend::mark("18.5 °C".to_owned(), Some('↑')), "18.5 °C ↑");
+        assert_eq!(Trend::mark("None".to_owned(), Some('↑')), "None");
+        assert_eq!(Trend::mark("70 %".to_owned(), None), "70 %");
+    }
+
+    #[test]
+    fn test_average_item() {
+        let items = vec![
+            (date("2023-05-02 06:00"), Some(10.0)),
+            (date("2023-05-02 09:00"), Some(14.0)),
+            (date("2023-05-02 12:00"), None),
+            (date("2023-05-02 15:00"), Some(20.0)),
+        ];
+        let item = average_item(
+            items.clone(),
+            &date("2023-05-02 10:00"),
+            3,
+            |item| item.0,
+            |item, items| item.1 = mean(items, |item| item.1),
+        );
+        // The closest item is 09:00, the missing value of 12:00 is skipped
+        assert_eq!(item, Some((date("2023-05-02 09:00"), Some(12.0))));
+        let item = average_item(
+            items,
+            &date("2023-05-02 16:00"),
+            10,
+            |item| item.0,
+            |_, _| {},
+        );
+        assert_eq!(item, Some((date("2023-05-02 15:00"), Some(20.0))));
+        assert_eq!(
+            average_item(
+                Vec::<DateTime<Local>>::new(),
+                &date("2023-05-02 10:00"),
+                3,
+                |d| *d,
+                |_, _| {}
+            ),
+            None
+        );
+        assert_eq!(mean(&[None::<f32>], |v| *v), None);
+    }
+
+    #[test]
+    fn test_day_curve() {
+        let items = [
+            (date("2023-05-01 21:00"), Some(9.0)),
+            (date("2023-05-02 00:00"), Some(8.0)),
+            (date("2023-05-02 03:00"), None),
+            (date("2023-05-02 12:00"), Some(15.0)),
+            (date("2023-05-03 00:00"), Some(7.0)),
+        ];
+        assert_eq!(
+            day_curve(&items, &date("2023-05-02 10:00"), None, |item| *item),
+            vec![
+                (date("2023-05-02 00:00"), 8.0),
+                (date("2023-05-02 12:00"), 15.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        assert_eq!(
+            explain(40, &date("2023-05-11 12:00"), &date("2023-05-11 15:00"), true),
+            "Explain: 40 items were received, selected the nearest item at or after the requested time. Requested 2023-05-11 12:00, selected 2023-05-11 15:00, 3 h later."
+        );
+        assert_eq!(
+            explain(5, &date("2023-05-11 12:00"), &date("2023-05-11 07:30"), false),
+            "Explain: 5 items were received, selected the closest item. Requested 2023-05-11 12:00, selected 2023-05-11 07:30, 4 h 30 min earlier."
+        );
+        assert_eq!(
+            explain(40, &date("2023-05-16 12:00"), &date("2023-05-16 11:45"), true),
+            "Explain: 40 items were received, selected the closest item as there are no later items. Requested 2023-05-16 12:00, selected 2023-05-16 11:45, 15 min earlier."
+        );
+        assert!(explain(
+            1,
+            &date("2023-05-11 12:00"),
+            &date("2023-05-11 12:00"),
+            false
+        )
+        .ends_with("the exact time."));
+    }
+
+    #[test]
+    fn test_age_warning() {
+        let now = date("2023-05-11 12:00");
+        assert_eq!(age_warning(&date("2023-05-11 11:30"), &now, 60), None);
+        assert_eq!(
+            age_warning(&date("2023-05-11 10:50"), &now, 60),
+            Some("⚠ The observation is 70 min old.".to_owned())
+        );
+        assert_eq!(
+            age_warning(&date("2023-05-11 09:40"), &now, 60),
+            Some("⚠ The observation is 2 h old.".to_owned())
+        );
+        assert_eq!(age_warning(&date("2023-05-11 09:40"), &now, 0), None);
+        // The forecast item after the current time
+        assert_eq!(age_warning(&date("2023-05-11 13:00"), &now, 60), None);
+    }
+
+    #[test]
+    fn test_gap_warning() {
+        assert_eq!(
+            gap_warning(&date("2023-05-02 10:00"), &date("2023-05-03 10:00")),
+            None
+        );
+        assert_eq!(
+            gap_warning(&date("2023-05-12 10:00"), &date("2023-05-07 09:00")),
+            Some(
+                "Warning: the provider has no forecast for 2023-05-12 10:00. The closest forecast is for 2023-05-07 09:00, 121 hours away."
+                    .to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn test_air_quality_rows() {
+        let air = AirQuality {
+            pm2_5: Some(12.34),
+            pm10: None,
+            index: Some(2),
+            scale: "1 good - 5 very poor",
+        };
+        assert_eq!(
+            air.rows(),
+            vec![
+                ("PM2.5", "12.3 μg/m³".to_owned()),
+                ("PM10", "None".to_owned()),
+                ("Air quality index", "2 (1 good - 5 very poor)".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_units_line() {
+        assert_eq!(
+            units_line(Units::Metric, "km/h", "mbar", None),
+            "Units: metric (°C, km/h, mbar)"
+        );
+        assert_eq!(
+            units_line(
+                Units::Imperial,
+                "meter/sec",
+                "hPa",
+                Some(PressureUnit::Inhg)
+            ),
+            "Units: imperial (°F, mph, inHg)"
+        );
+    }
+
+    #[test]
+    fn test_key_rows() {
+        assert_eq!(
+            key_rows(None, None),
+            vec![("API key", "not set".to_owned())]
+        );
+        assert_eq!(
+            key_rows(Some("0123456789abcdef"), Some("fedcba9876543210")),
+            vec![
+                ("API key", "***cdef".to_owned()),
+                ("API key from the environment (used)", "***3210".to_owned()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,655 @@
+//! Weather provider [OpenWeather One Call API 3.0](https://openweathermap.org/api/one-call-3).
+//!
+//! One request returns the current weather, the daily forecast for 8 days and the active weather alerts.
+//! The API requires the "One Call by Call" subscription, the free key of the `OpenWeather` provider is rejected.
+
+use std::{
+    env,
+    io::{stdin, stdout, Write},
+};
+
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
+use serde_json::{Map, Value};
+
+use crate::{
+    astro::{Astro, AstroReport},
+    budget::Budget,
+    format::Format,
+    geo::Geo,
+    http::{Http, HttpClient, HttpError},
+    init::{Date, Options},
+    log::Log,
+    provider::{closest_item, key_rows},
+    wind::WindDeg,
+    work::{Capability, Provider, WeatherReport},
+};
+
+/// Describes 'OpenWeatherOneCall' credentials
+///
+/// * `name: &'static str` - Provider name.
+/// * `client: Box<dyn HttpClient>` - Source of the server answers.
+/// * `key: Option<String>` - Api key with the One Call subscription.
+/// * `env_key: Option<String>` - Api key from the `WEATHER_ONECALL_KEY` environment variable.
+pub struct OpenWeatherOneCall {
+    /// Provider name.
+    name: &'static str,
+    /// Source of the server answers.
+    client: Box<dyn HttpClient>,
+    /// Api key with the One Call subscription.
+    key: Option<String>,
+    /// Api key from the environment variable, takes precedence over `key`.
+    env_key: Option<String>,
+}
+
+/// OpenWeatherOneCall data format for the current weather or one day of the forecast
+#[derive(Debug)]
+struct OneCallItem {
+    /// Time of data calculation from provider. Local
+    date: DateTime<Local>,
+    /// Request Address
+    address: String,
+    /// Geo position
+    geo: Geo,
+    /// Weather condition within the group, in the requested language
+    description: Option<String>,
+    /// Human-readable description of the day, daily forecast only
+    summary: Option<String>,
+    /// Temperature, the day temperature for the daily forecast. Metric: Celsius
+    temp: Option<f32>,
+    /// Min daily temperature. Metric: Celsius
+    temp_min: Option<f32>,
+    /// Max daily temperature. Metric: Celsius
+    temp_max: Option<f32>,
+    /// Temperature accounting for the human perception of weather. Metric: Celsius
+    feels_like: Option<f32>,
+    /// Atmospheric pressure on the sea level, hPa
+    pressure: Option<u32>,
+    /// Humidity, %
+    humidity: Option<u32>,
+    /// Dew point. Metric: Celsius
+    dew_point: Option<f32>,
+    /// UV index, the maximum of the day for the daily forecast
+    uvi: Option<f32>,
+    /// Cloudiness, %
+    clouds: Option<u32>,
+    /// Visibility, meter. Current weather only
+    visibility: Option<u32>,
+    /// Wind speed. Metric: meter/sec
+    speed: Option<f32>,
+    /// Wind degrees (meteorological)
+    deg: Option<u16>,
+    /// Wind direction (meteorological)
+    dir: WindDeg,
+    /// Wind gust. Metric: meter/sec
+    gust: Option<f32>,
+    /// Probability of precipitation, 0..1. Daily forecast only
+    pop: Option<f32>,
+    /// Rain volume: for the last hour or for the day, mm
+    rain: Option<f32>,
+    /// Snow volume: for the last hour or for the day, mm
+    snow: Option<f32>,
+    /// Sunrise time. Local
+    sunrise: Option<DateTime<Local>>,
+    /// Sunset time. Local
+    sunset: Option<DateTime<Local>>,
+    /// UTC offset of the location, the system timezone is used when it's unknown
+    offset: Option<FixedOffset>,
+    /// Moonrise time. Local. Daily forecast only
+    moonrise: Option<DateTime<Local>>,
+    /// Moonset time. Local. Daily forecast only
+    moonset: Option<DateTime<Local>>,
+    /// Moon phase, 0..1. Daily forecast only
+    moon_phase: Option<f64>,
+    /// Active weather alerts for the location
+    alerts: Vec<String>,
+}
+
+impl OpenWeatherOneCall {
+    /// Create new empty provider
+    pub fn new() -> OpenWeatherOneCall {
+        OpenWeatherOneCall {
+            name: "OpenWeatherOneCall",
+            client: Box::new(Http),
+            key: None,
+            env_key: None,
+        }
+    }
+
+    /// Url of the request for the place, the minutely and hourly forecasts are excluded.
+    ///
+    /// * `geo: &Geo` - The place.
+    /// * `key: &str` - API key.
+    fn url(geo: &Geo, key: &str) -> String {
+        format!(
+            "https://api.openweathermap.org/data/3.0/onecall?lat={}&lon={}&exclude=minutely,hourly&appid={}&units=metric&lang={}",
+            geo.lat, geo.lon, key, Options::get().lang()
+        )
+    }
+
+    /// Load data from provider
+    ///
+    /// The minutely and hourly forecasts are excluded, they are not displayed.
+    fn get_json(&self, address: &str) -> Option<(Map<String, Value>, Geo)> {
+        let key = match self.env_key.as_ref().or(self.key.as_ref()) {
+            Some(key) => key,
+            None => {
+                Log::error(
+                    "OpenWeatherOneCall server API access key is not set. Please install it first.",
+                );
+                return None;
+            }
+        };
+        // Find geo coordinates by address
+        let geo = Geo::find(address)?;
+        let url = OpenWeatherOneCall::url(&geo, key);
+        let json = match Budget::get_json(self.name, self.client.as_ref(), &url, &[]) {
+            Ok(Value::Object(json)) => json,
+            Ok(_) => {
+                Log::info("Unable to recognize json response from server.");
+                return None;
+            }
+            Err(e) => {
+                e.log(Some(self.name));
+                return None;
+            }
+        };
+        Some((json, geo))
+    }
+
+    /// Getting weather forecast for now
+    fn get_now(&self, address: String) -> Option<OneCallItem> {
+        let (json, geo) = self.get_json(&address)?;
+        let current = json
+            .get("current")
+            .and_then(|c| c.as_object())
+            .or_else(|| {
+                Log::info("The OpenWeatherOneCall server did not provide the current weather");
+                None
+            })?;
+        let mut item = self.detect(current, geo, address, false)?;
+        item.alerts = OpenWeatherOneCall::alerts(&json);
+        item.offset = OpenWeatherOneCall::offset(&json);
+        Some(item)
+    }
+
+    /// Getting weather forecast for `date`
+    fn get_date(&self, address: String, date: &DateTime<Local>) -> Option<OneCallItem> {
+        let list = self.get_list(address)?;
+        // Daily forecast: find item with the closest date
+        closest_item(list, date, false, |item| item.date)
+    }
+
+    /// Getting all days of weather forecast
+    fn get_list(&self, address: String) -> Option<Vec<OneCallItem>> {
+        let (json, geo) = self.get_json(&address)?;
+        let days = json.get("daily").and_then(|d| d.as_array()).or_else(|| {
+            Log::info("The OpenWeatherOneCall server did not provide weather forecast data");
+            None
+        })?;
+        let alerts = OpenWeatherOneCall::alerts(&json);
+        let offset = OpenWeatherOneCall::offset(&json);
+        // Load all OneCallItem to vector
+        let mut list = Vec::with_capacity(days.len());
+        for day in days {
+            if let Value::Object(map) = day {
+                if let Some(mut item) = self.detect(map, geo.clone(), address.clone(), true) {
+                    item.alerts = alerts.clone();
+                    item.offset = offset;
+                    list.push(item);
+                }
+            }
+        }
+        if list.is_empty() {
+            return None;
+        }
+        Some(list)
+    }
+
+    /// Getting weather forecast for `date`
+    fn get_item(&self, address: String, date: &Date) -> Option<OneCallItem> {
+        match date {
+            Date::Now => self.get_now(address),
+            Date::Set(dt) => self.get_date(address, dt),
+            Date::Error | Date::Range(..) => None,
+        }
+    }
+
+    /// Active weather alerts of the answer, e.g. `Flood warning, 2023-05-11 12:00 - 2023-05-12 06:00 (UHMC): ...`
+    fn alerts(json: &Map<String, Value>) -> Vec<String> {
+        let alerts = match json.get("alerts").and_then(|a| a.as_array()) {
+            Some(alerts) => alerts,
+            None => return Vec::new(),
+        };
+        alerts
+            .iter()
+            .filter_map(|alert| {
+                let event = alert.get("event").and_then(|s| s.as_str())?;
+                let time = |name: &str| {
+                    alert
+                        .get(name)
+                        .and_then(|s| s.as_i64())
+                        .and_then(|t| Utc.timestamp_opt(t, 0).single())
+                        .map(|t| {
+                            Local
+                                .from_utc_datetime(&t.naive_utc())
+                                .format("%Y-%m-%d %H:%M")
+                                .to_string()
+                        })
+                        .unwrap_or_else(|| "?".to_owned())
+                };
+                let mut text = format!("{}, {} - {}", event, time("start"), time("end"));
+                if let Some(sender) = alert.get("sender_name").and_then(|s| s.as_str()) {
+                    text += &format!(" ({})", sender);
+                }
+                if let Some(description) = alert.get("description").and_then(|s| s.as_str()) {
+                    let description = description
+                        .split_whitespace()
+                        .collect::<Vec<&str>>()
+                        .join(" ");
+                    if !description.is_empty() {
+                        text += &format!(": {}", description);
+                    }
+                }
+                Some(text)
+            })
+            .collect()
+    }
+
+    /// UTC offset of the location, e.g. `10800` seconds for Kyiv in summer
+    fn offset(json: &Map<String, Value>) -> Option<FixedOffset> {
+        json.get("timezone_offset")
+            .and_then(|s| s.as_f64())
+            .and_then(Format::offset)
+    }
+
+    /// Parse the current weather or one day of the forecast
+    ///
+    /// * `daily: bool` - True: `items` is a day of the forecast, the temperatures are objects like `{"day": 20.1, "min": 12.5}`.
+    fn detect(
+        &self,
+        items: &Map<String, Value>,
+        geo: Geo,
+        address: String,
+        daily: bool,
+    ) -> Option<OneCallItem> {
+        let date = items
+            .get("dt")
+            .and_then(|s| s.as_i64())
+            .and_then(|t| Utc.timestamp_opt(t, 0).single())
+            .map(|t| Local.from_utc_datetime(&t.naive_utc()))?;
+        let time = |name: &str| {
+            items
+                .get(name)
+                .and_then(|s| s.as_i64())
+                .and_then(|t| Utc.timestamp_opt(t, 0).single())
+                .map(|t| Local.from_utc_datetime(&t.naive_utc()))
+        };
+        let float = |value: Option<&Value>| value.and_then(|s| s.as_f64()).map(|s| s as f32);
+        let int = |name: &str| items.get(name).and_then(|s| s.as_u64()).map(|s| s as u32);
+        // The temperatures of the day are objects, the current ones are numbers
+        let temp = |name: &str, part: &str| match daily {
+            true => float(items.get(name).and_then(|t| t.get(part))),
+            false => float(items.get(name)),
+        };
+        // The daily volume is a number, the current one is an object like `{"1h": 0.5}`
+        let volume = |name: &str| match daily {
+            true => float(items.get(name)),
+            false => float(items.get(name).and_then(|v| v.get("1h"))),
+        };
+        let description = items
+            .get("weather")
+            .and_then(|a| a.get(0))
+            .and_then(|m| m.get("description"))
+            .and_then(|s| s.as_str())
+            .map(|s| s.to_owned());
+        let summary = items
+            .get("summary")
+            .and_then(|s| s.as_str())
+            .map(|s| s.to_owned());
+        let deg = items
+            .get("wind_deg")
+            .and_then(|s| s.as_f64())
+            .and_then(WindDeg::normalize);
+
+        Some(OneCallItem {
+            date,
+            address,
+            geo,
+            description,
+            summary,
+            temp: temp("temp", "day"),
+            temp_min: daily.then(|| temp("temp", "min")).flatten(),
+            temp_max: daily.then(|| temp("temp", "max")).flatten(),
+            feels_like: temp("feels_like", "day"),
+            pressure: int("pressure"),
+            humidity: int("humidity"),
+            dew_point: float(items.get("dew_point")),
+            uvi: float(items.get("uvi")),
+            clouds: int("clouds"),
+            visibility: int("visibility"),
+            speed: float(items.get("wind_speed")),
+            deg,
+            dir: WindDeg::get(deg),
+            gust: float(items.get("wind_gust")),
+            pop: float(items.get("pop")),
+            rain: volume("rain"),
+            snow: volume("snow"),
+            sunrise: time("sunrise"),
+            sunset: time("sunset"),
+            offset: None,
+            // 0 - the moon doesn't rise or set this day
+            moonrise: time("moonrise").filter(|t| t.timestamp() > 0),
+            moonset: time("moonset").filter(|t| t.timestamp() > 0),
+            moon_phase: items.get("moon_phase").and_then(|s| s.as_f64()),
+            alerts: Vec::new(),
+        })
+    }
+
+    /// Convert result to the normalized weather report
+    fn report(&self, item: OneCallItem) -> WeatherReport {
+        let precipitation = match (item.rain, item.snow) {
+            (Some(rain), Some(snow)) => Some(rain + snow),
+            (rain, snow) => rain.or(snow),
+        };
+        WeatherReport {
+            condition: item.description,
+            summary: item.summary,
+            temperature: item.temp,
+            feels_like: item.feels_like,
+            humidity: item.humidity,
+            pressure: item.pressure.map(|s| s as f32),
+            // meter/sec to km/hour
+            wind_speed: item.speed.map(|s| s * 3.6),
+            wind_deg: item.deg,
+            wind_dir: item.dir,
+            precipitation,
+            alerts: item.alerts,
+            temperature_min: item.temp_min,
+            temperature_max: item.temp_max,
+            dew_point: item.dew_point,
+            wind_gust: item.gust.map(|s| s * 3.6),
+            // 0..1 to %
+            precipitation_chance: item.pop.map(|s| (s * 100.0).round() as u32),
+            // mm to cm
+            snow: item.snow.map(|s| s / 10.0),
+            cloud_cover: item.clouds,
+            // meter to km
+            visibility: item.visibility.map(|s| s as f32 / 1000.0),
+            uv_index: item.uvi,
+            sunrise: item.sunrise,
+            sunset: item.sunset,
+            moon_phase: item.moon_phase.map(|s| Astro::phase_name(s).to_owned()),
+            ..WeatherReport::new(self.name, item.address, item.geo, item.date, item.offset)
+        }
+    }
+}
+
+impl Provider for OpenWeatherOneCall {
+    fn serialize(&self) -> String {
+        match &self.key {
+            Some(key) => format!("{}:{}", self.name, key),
+            None => format!("{}:", self.name),
+        }
+    }
+
+    fn deserialize(&mut self, data: &str) -> bool {
+        let mut input = data.splitn(2, ':');
+        match input.next() {
+            Some(name) => {
+                if name != self.name {
+                    return false;
+                }
+            }
+            None => {
+                Log::info("The data file structure is damaged. The data file will be deleted.");
+                return false;
+            }
+        };
+        let key = match input.next() {
+            Some(key) => key.to_owned(),
+            None => {
+                Log::info("The data file structure is damaged. The data file will be deleted.");
+                return false;
+            }
+        };
+        if key.is_empty() {
+            self.key = None;
+            return true;
+        }
+        self.key = Some(key);
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[
+            Capability::Temperature,
+            Capability::FeelsLike,
+            Capability::Uv,
+            Capability::Alerts,
+            Capability::DateRange,
+            Capability::Coordinates,
+        ]
+    }
+
+    fn forecast_days(&self) -> Option<i64> {
+        // The daily forecast is for 8 days including today
+        Some(7)
+    }
+
+    fn configure(&mut self) {
+        println!("Configure credentials for {}: \n", self.name);
+        println!(
+            "The One Call API 3.0 requires the \"One Call by Call\" subscription of OpenWeather."
+        );
+        println!("The key of the free plan is rejected, use the OpenWeather provider with it.\n");
+        match &self.key {
+            Some(key) => print!(
+                "Please enter the API key to access the weather forecast. Current key={}: ",
+                Format::secret(key)
+            ),
+            None => print!("Please enter the API key to access the weather forecast: "),
+        }
+        if let Err(e) = stdout().flush() {
+            print!("System error: {}\n\nFailed to set key.", e);
+            return;
+        };
+        let mut input = String::new();
+        if let Err(e) = stdin().read_line(&mut input) {
+            print!(
+                "The key must be only printed characters. Error: {}\n\nFailed to set key.",
+                e
+            );
+            return;
+        }
+        let key = input.trim().to_string();
+        if key.is_empty() {
+            print!("The key was removed successfully.");
+            self.key = None;
+        } else {
+            print!("The key '{}' was setted successfully.", key);
+            self.key = Some(key);
+        }
+    }
+
+    fn fetch_range(
+        &self,
+        address: String,
+        start: &DateTime<Local>,
+        end: &DateTime<Local>,
+    ) -> Option<Vec<WeatherReport>> {
+        let list = self.get_list(address)?;
+        let reports = list.into_iter().map(|item| self.report(item)).collect();
+        Some(WeatherReport::daily(reports, start, end))
+    }
+
+    fn key_from_env(&mut self) {
+        if let Ok(key) = env::var("WEATHER_ONECALL_KEY") {
+            let key = key.trim();
+            if !key.is_empty() {
+                self.env_key = Some(key.to_owned());
+            }
+        }
+    }
+
+    fn credentials(&self) -> Vec<(&'static str, String)> {
+        key_rows(self.key.as_deref(), self.env_key.as_deref())
+    }
+
+    fn has_credentials(&self) -> bool {
+        self.key.is_some() || self.env_key.is_some()
+    }
+
+    fn reset(&mut self) {
+        self.key = None;
+    }
+
+    fn check(&self, geo: &Geo) -> Option<Result<(), HttpError>> {
+        let key = self.env_key.as_ref().or(self.key.as_ref())?;
+        let url = OpenWeatherOneCall::url(geo, key);
+        Some(Budget::get_json(self.name, self.client.as_ref(), &url, &[]).map(|_| ()))
+    }
+
+    fn supports_astro(&self) -> bool {
+        true
+    }
+
+    fn fetch_astro(&self, address: String, date: Date) -> Option<AstroReport> {
+        let item = self.get_item(address, &date)?;
+        Some(AstroReport {
+            sunrise: item.sunrise,
+            sunset: item.sunset,
+            moonrise: item.moonrise,
+            moonset: item.moonset,
+            moon_phase: item
+                .moon_phase
+                .map_or_else(|| Astro::moon_phase(&item.date), Astro::phase_name)
+                .to_owned(),
+            ..AstroReport::new(self.name, item.address, item.geo, item.date, item.offset)
+        })
+    }
+
+    fn fetch(&self, address: String, date: Date) -> Option<WeatherReport> {
+        self.get_item(address, &date).map(|item| self.report(item))
+    }
+}
+
+impl Default for OpenWeatherOneCall {
+    fn default() -> OpenWeatherOneCall {
+        OpenWeatherOneCall::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+
+    use super::OpenWeatherOneCall;
+    use crate::{geo::Geo, work::Provider};
+
+    const ANSWER: &str = r#"{
+        "lat": 50.45, "lon": 30.52, "timezone": "Europe/Kyiv", "timezone_offset": 10800,
+        "current": {
+            "dt": 1683803100, "sunrise": 1683771300, "sunset": 1683826800,
+            "temp": 18.3, "feels_like": 17.6, "pressure": 1015, "humidity": 55,
+            "dew_point": 9.1, "uvi": 4.2, "clouds": 40, "visibility": 10000,
+            "wind_speed": 4.0, "wind_deg": 90,
+            "rain": {"1h": 0.3},
+            "weather": [{"id": 500, "main": "Rain", "description": "light rain"}]
+        },
+        "daily": [{
+            "dt": 1683799200, "sunrise": 1683771300, "sunset": 1683826800, "moon_phase": 0.5,
+            "summary": "Expect a day of partly cloudy with rain",
+            "temp": {"day": 20.5, "min": 10.2, "max": 22.1, "night": 12.0, "eve": 18.0, "morn": 11.0},
+            "feels_like": {"day": 20.0, "night": 11.5, "eve": 17.5, "morn": 10.5},
+            "pressure": 1014, "humidity": 50, "wind_speed": 5.0, "wind_deg": 180,
+            "weather": [{"id": 500, "main": "Rain", "description": "light rain"}],
+            "clouds": 60, "pop": 0.45, "rain": 1.2, "uvi": 5.1
+        }],
+        "alerts": [{
+            "sender_name": "UHMC", "event": "Thunderstorm warning",
+            "start": 1683799200, "end": 1683842400,
+            "description": "Thunderstorms,\n  hail and squalls 15-20 m/s.",
+            "tags": ["Thunderstorm"]
+        }]
+    }"#;
+
+    fn geo() -> Geo {
+        Geo {
+            lat: "50.45".to_owned(),
+            lon: "30.52".to_owned(),
+            address: "Kyiv, Ukraine".to_owned(),
+            importance: None,
+        }
+    }
+
+    #[test]
+    fn test_detect_current() {
+        let json: Map<String, Value> = serde_json::from_str(ANSWER).unwrap();
+        let current = json["current"].as_object().unwrap();
+        let provider = OpenWeatherOneCall::new();
+        let item = provider
+            .detect(current, geo(), "Kyiv".to_owned(), false)
+            .unwrap();
+        assert_eq!(item.temp, Some(18.3));
+        assert_eq!(item.temp_min, None);
+        assert_eq!(item.uvi, Some(4.2));
+        assert_eq!(item.rain, Some(0.3));
+        assert_eq!(item.visibility, Some(10000));
+        let report = provider.report(item);
+        assert_eq!(report.condition, Some("light rain".to_owned()));
+        assert_eq!(report.wind_speed, Some(14.4));
+    }
+
+    #[test]
+    fn test_detect_daily() {
+        let json: Map<String, Value> = serde_json::from_str(ANSWER).unwrap();
+        let day = json["daily"][0].as_object().unwrap();
+        let item = OpenWeatherOneCall::new()
+            .detect(day, geo(), "Kyiv".to_owned(), true)
+            .unwrap();
+        assert_eq!(item.temp, Some(20.5));
+        assert_eq!(item.temp_min, Some(10.2));
+        assert_eq!(item.temp_max, Some(22.1));
+        assert_eq!(item.feels_like, Some(20.0));
+        assert_eq!(item.pop, Some(0.45));
+        assert_eq!(item.rain, Some(1.2));
+        assert_eq!(item.moon_phase, Some(0.5));
+        let report = OpenWeatherOneCall::new().report(item);
+        assert_eq!(report.precipitation_chance, Some(45));
+        assert_eq!(report.moon_phase, Some("Full Moon".to_owned()));
+        assert!(report.summary.is_some());
+    }
+
+    #[test]
+    fn test_alerts() {
+        let json = serde_json::from_str(ANSWER).unwrap();
+        let alerts = OpenWeatherOneCall::alerts(&json);
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].starts_with("Thunderstorm warning, "));
+        assert!(alerts[0].ends_with(" (UHMC): Thunderstorms, hail and squalls 15-20 m/s."));
+        let json = serde_json::from_str(r#"{"current": {}}"#).unwrap();
+        assert!(OpenWeatherOneCall::alerts(&json).is_empty());
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut provider = OpenWeatherOneCall::new();
+        assert_eq!(provider.serialize(), "OpenWeatherOneCall:");
+        assert!(provider.deserialize("OpenWeatherOneCall:abc123"));
+        assert_eq!(provider.key.as_deref(), Some("abc123"));
+        assert_eq!(provider.serialize(), "OpenWeatherOneCall:abc123");
+        // The colon is a part of the key
+        assert!(provider.deserialize("OpenWeatherOneCall:abc:123"));
+        assert_eq!(provider.serialize(), "OpenWeatherOneCall:abc:123");
+        assert!(provider.deserialize("OpenWeatherOneCall:"));
+        assert_eq!(provider.key, None);
+        assert_eq!(provider.serialize(), "OpenWeatherOneCall:");
+        assert!(!provider.deserialize("OpenWeatherOneCall"));
+        assert!(!provider.deserialize("WeatherAPI:abc123"));
+        assert!(!provider.deserialize(""));
+    }
+}
@@ -2,29 +2,48 @@
 //!
 
 use std::{
+    env,
     io::{stdin, stdout, Write},
-    time::Duration,
 };
 
-use chrono::{DateTime, Local, TimeZone, Utc};
-use reqwest::blocking::Client;
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
 use serde_json::{Map, Value};
 
-use crate::{geo::Geo, init::Date, wind::WindDeg, work::Provider};
+use crate::{
+    astro::AstroReport,
+    budget::Budget,
+    format::Format,
+    geo::Geo,
+    http::{Http, HttpClient, HttpError},
+    init::{Date, Options, Units},
+    log::Log,
+    provider::{average_item, closest_item, day_curve, key_rows, mean, AirQuality, Trend},
+    wind::WindDeg,
+    work::{Capability, Provider, WeatherReport},
+};
+
+/// Endpoint of the current weather.
+const CURRENT_URL: &str = "https://api.openweathermap.org/data/2.5/weather";
 
 /// Describes 'OpenWeather' credentials
 ///
 /// * `name: &'static str` - Provider name.
+/// * `client: Box<dyn HttpClient>` - Source of the server answers.
 /// * `key: Option<String>` - Api key.
+/// * `env_key: Option<String>` - Api key from the `WEATHER_OPENWEATHER_KEY` environment variable.
 pub struct OpenWeather {
     /// Provider name.
     name: &'static str,
+    /// Source of the server answers.
+    client: Box<dyn HttpClient>,
     /// Api key.
     key: Option<String>,
+    /// Api key from the environment variable, takes precedence over `key`.
+    env_key: Option<String>,
 }
 
 /// OpenWeather data format for one item
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct OpenWeatherItem {
     /// Time of data calculation from provider. Local
     date: DateTime<Local>,
@@ -64,6 +83,18 @@ struct OpenWeatherItem {
     sunrise: Option<DateTime<Local>>,
     /// Sunset time. Local
     sunset: Option<DateTime<Local>>,
+    /// UTC offset of the location, the system timezone is used when it's unknown
+    offset: Option<FixedOffset>,
+    /// Air quality, loaded by a separate request for the `--aqi` flag
+    air: Option<AirQuality>,
+    /// Units of the values requested from the server. Metric: Celsius, meter/sec. Imperial: Fahrenheit, miles/hour
+    units: Units,
+    /// Temperature of the items of the requested day for the `--graph` flag
+    curve: Vec<(DateTime<Local>, f32)>,
+    /// Number of the items averaged for the `--average` flag, 0 - the closest item only
+    average: usize,
+    /// Trend of the temperature, pressure and humidity versus the adjacent items of the forecast
+    trend: Trend,
 }
 
 impl OpenWeather {
@@ -71,88 +102,127 @@ impl OpenWeather {
     pub fn new() -> OpenWeather {
         OpenWeather {
             name: "OpenWeather",
+            client: Box::new(Http),
             key: None,
+            env_key: None,
         }
     }
 
     /// Load data from provider
-    fn get_json(&self, url: &str, address: &str) -> Option<(Map<String, Value>, Geo)> {
-        let key = match &self.key {
+    ///
+    /// * `units: Units` - Units of the values requested from the server.
+    fn get_json(
+        &self,
+        url: &str,
+        address: &str,
+        units: Units,
+    ) -> Option<(Map<String, Value>, Geo)> {
+        let key = match self.env_key.as_ref().or(self.key.as_ref()) {
             Some(key) => key,
             None => {
-                println!("OpenWeather server API access key is not set. Please install it first.");
+                Log::error(
+                    "OpenWeather server API access key is not set. Please install it first.",
+                );
                 return None;
             }
         };
         // Find geo coordinates by address
-        let geo = match Geo::get(address) {
-            Some(mut geos) => match geos.pop() {
-                Some(geo) => geo,
-                None => {
-                    println!("Sorry, we couldn't find your address: {}", address);
-                    return None;
-                }
-            },
-            None => return None,
-        };
-        let url = format!(
-            "{}?lat={}&lon={}&appid={}&units=metric",
-            url, geo.lat, geo.lon, key
-        );
-        // Client for url query
-        let client = match Client::builder().timeout(Duration::from_secs(3)).build() {
-            Ok(c) => c,
-            Err(e) => {
-                println!("The following error occurred while requesting coordinates for your address: {}", e);
+        let geo = Geo::find(address)?;
+        let url = OpenWeather::url(url, &geo, key, units);
+        let json = match Budget::get_json(self.name, self.client.as_ref(), &url, &[]) {
+            Ok(Value::Object(json)) => json,
+            Ok(_) => {
+                Log::info("Unable to recognize json response from server.");
                 return None;
             }
-        };
-
-        let json_str = match client.get(&url).send() {
-            Ok(s) => {
-                let status = s.status();
-                if status != 200 {
-                    println!("Error connecting to {}. Status code: {}", &url, status);
-                    return None;
-                }
-                match s.text() {
-                    Ok(s) => s,
-                    Err(e) => {
-                        println!("Error getting answer from {}. Error text: {}", &url, e);
-                        return None;
-                    }
-                }
-            }
             Err(e) => {
-                println!("Error connecting to {}. Error text: {}", &url, e);
+                e.log(Some(self.name));
                 return None;
             }
         };
-        // Parse json
-        match serde_json::from_str(&json_str) {
-            Ok(json) => Some((json, geo)),
-            Err(e) => {
-                println!(
-                    "Unable to recognize json response from server. Error text: {}",
-                    e
-                );
-                None
-            }
-        }
+        Some((json, geo))
+    }
+
+    /// Url of the request for the place.
+    ///
+    /// * `url: &str` - Endpoint, e.g. `CURRENT_URL`.
+    /// * `geo: &Geo` - The place.
+    /// * `key: &str` - API key.
+    /// * `units: Units` - Units of the answer.
+    fn url(url: &str, geo: &Geo, key: &str, units: Units) -> String {
+        format!(
+            "{}?lat={}&lon={}&appid={}&units={}&lang={}",
+            url,
+            geo.lat,
+            geo.lon,
+            key,
+            units,
+            Options::get().lang()
+        )
     }
 
     /// Getting weather forecast for now
-    fn get_now(&self, address: String) -> Option<OpenWeatherItem> {
-        let (items, geo) =
-            self.get_json("https://api.openweathermap.org/data/2.5/weather", &address)?;
-        self.detect(&items, geo, address, None, None)
+    fn get_now(&self, address: String, units: Units) -> Option<OpenWeatherItem> {
+        let (items, geo) = self.get_json(CURRENT_URL, &address, units)?;
+        self.detect(&items, geo, address, None, None, units)
     }
 
     /// Getting weather forecast for `date`
-    fn get_date(&self, address: String, date: &DateTime<Local>) -> Option<OpenWeatherItem> {
+    fn get_date(
+        &self,
+        address: String,
+        date: &DateTime<Local>,
+        units: Units,
+    ) -> Option<OpenWeatherItem> {
+        let list = self.get_list(address, units)?;
+        let offset = list.first().and_then(|item| item.offset);
+        let curve = day_curve(&list, date, offset, |item| (item.date, item.temp));
+        let trend = Trend::around(
+            &list,
+            date,
+            true,
+            |item| item.date,
+            |item| {
+                [
+                    item.temp,
+                    item.pressure.map(|p| p as f32),
+                    item.humidity.map(|h| h as f32),
+                ]
+            },
+        );
+        // Hourly forecast: the upcoming item is preferred, `--average` takes the nearest items
+        let mut item = match Options::get().average {
+            Some(count) => average_item(
+                list,
+                date,
+                count,
+                |item| item.date,
+                |item, items| {
+                    item.temp = mean(items, |item| item.temp);
+                    item.feels_like = mean(items, |item| item.feels_like);
+                    item.speed = mean(items, |item| item.speed);
+                    item.humidity = mean(items, |item| item.humidity.map(|h| h as f32))
+                        .map(|h| h.round() as u32);
+                    item.pressure = mean(items, |item| item.pressure.map(|p| p as f32))
+                        .map(|p| p.round() as u32);
+                    item.average = items.len();
+                },
+            )?,
+            None => closest_item(list, date, true, |item| item.date)?,
+        };
+        item.curve = curve;
+        item.trend = trend;
+        Some(item)
+    }
+
+    /// Getting all items of weather forecast
+    fn get_list(&self, address: String, units: Units) -> Option<Vec<OpenWeatherItem>> {
         // Load json from provider
-        let (items, geo) =
-            self.get_json("https://api.openweathermap.org/data/2.5/forecast", &address)?;
+        let (items, geo) = self.get_json(
+            "https://api.openweathermap.org/data/2.5/forecast",
+            &address,
+            units,
+        )?;
         // Detect sunrise and sunset, because provider returns different jsons for 'now' and 'date'
         let sunrise = items
             .get("city")
@@ -167,20 +237,28 @@ impl OpenWeather {
             .and_then(|t| Utc.timestamp_opt(t, 0).single())
             .map(|t| Local.from_utc_datetime(&t.naive_utc()));
 
+        // The forecast keeps the offset of the location in the city, the current weather at the top level
+        let offset = items
+            .get("city")
+            .and_then(|m| m.get("timezone"))
+            .and_then(|s| s.as_f64())
+            .and_then(Format::offset);
+
         // Get list of OpenWeatherItem
         let its = items
             .get("list")
             .and_then(|its| its.as_array())
             .or_else(|| {
-                println!("The OpenWeather server did not provide weather forecast data");
+                Log::info("The OpenWeather server did not provide weather forecast data");
                 None
             })?;
         // Load all OpenWeatherItem to vector
         let mut list = Vec::with_capacity(40);
         for item in its {
             if let Value::Object(map) = item {
-                let res = self.detect(map, geo.clone(), address.clone(), sunset, sunrise);
-                if let Some(item) = res {
+                let res = self.detect(map, geo.clone(), address.clone(), sunset, sunrise, units);
+                if let Some(mut item) = res {
+                    item.offset = item.offset.or(offset);
                     list.push(item);
                 }
             }
@@ -188,15 +266,76 @@ impl OpenWeather {
         if list.is_empty() {
             return None;
         }
-        // Find item with the closest date
-        list.into_iter().min_by(|item_a, item_b| {
-            let diff_a = item_a.date.signed_duration_since(*date).num_seconds().abs();
-            let diff_b = item_b.date.signed_duration_since(*date).num_seconds().abs();
+        Some(list)
+    }
 
-            diff_a.cmp(&diff_b)
+    /// Getting air quality for `date` from the Air Pollution API
+    ///
+    /// * `geo: &Geo` - Geo position of the forecast.
+    /// * `date: &Date` - The current or the forecast air quality.
+    fn get_air(&self, geo: &Geo, date: &Date) -> Option<AirQuality> {
+        let key = self.env_key.as_ref().or(self.key.as_ref())?;
+        let (url, date) = match date {
+            Date::Set(dt) => (
+                "https://api.openweathermap.org/data/2.5/air_pollution/forecast",
+                *dt,
+            ),
+            _ => (
+                "https://api.openweathermap.org/data/2.5/air_pollution",
+                Local::now(),
+            ),
+        };
+        let url = format!("{}?lat={}&lon={}&appid={}", url, geo.lat, geo.lon, key);
+        let json = match Budget::get_json(self.name, self.client.as_ref(), &url, &[]) {
+            Ok(json) => json,
+            Err(e) => {
+                e.log(Some(self.name));
+                return None;
+            }
+        };
+        let list = json
+            .get("list")
+            .and_then(|l| l.as_array())?
+            .iter()
+            .filter_map(|item| {
+                let dt = item
+                    .get("dt")
+                    .and_then(|s| s.as_i64())
+                    .and_then(|t| Utc.timestamp_opt(t, 0).single())
+                    .map(|t| Local.from_utc_datetime(&t.naive_utc()))?;
+                Some((dt, item))
+            })
+            .collect();
+        let (_, item) = closest_item(list, &date, false, |(dt, _)| *dt)?;
+        let component = |name: &str| {
+            item.get("components")
+                .and_then(|c| c.get(name))
+                .and_then(|s| s.as_f64())
+                .map(|s| s as f32)
+        };
+        Some(AirQuality {
+            pm2_5: component("pm2_5"),
+            pm10: component("pm10"),
+            index: item
+                .get("main")
+                .and_then(|m| m.get("aqi"))
+                .and_then(|s| s.as_u64())
+                .map(|s| s as u32),
+            scale: "1 good - 5 very poor",
         })
     }
 
+    /// Getting weather forecast for `date`
+    ///
+    /// * `units: Units` - Units of the values requested from the server.
+    fn get_item(&self, address: String, date: &Date, units: Units) -> Option<OpenWeatherItem> {
+        match date {
+            Date::Now => self.get_now(address, units),
+            Date::Set(dt) => self.get_date(address, dt, units),
+            Date::Error | Date::Range(..) => None,
+        }
+    }
+
     /// Parse json answer from server
     fn detect(
         &self,
@@ -205,6 +344,7 @@ impl OpenWeather {
         address: String,
         sunrise: Option<DateTime<Local>>,
         sunset: Option<DateTime<Local>>,
+        units: Units,
     ) -> Option<OpenWeatherItem> {
         let group = items
             .get("weather")
@@ -244,8 +384,8 @@ impl OpenWeather {
         let deg = items
             .get("wind")
             .and_then(|m| m.get("deg"))
-            .and_then(|s| s.as_u64())
-            .map(|s| s as u16);
+            .and_then(|s| s.as_f64())
+            .and_then(WindDeg::normalize);
         let dir = WindDeg::get(deg);
         let gust = items
             .get("wind")
@@ -293,6 +433,10 @@ impl OpenWeather {
                 .and_then(|t| Utc.timestamp_opt(t, 0).single())
                 .map(|t| Local.from_utc_datetime(&t.naive_utc()))
         });
+        let offset = items
+            .get("timezone")
+            .and_then(|s| s.as_f64())
+            .and_then(Format::offset);
 
         Some(OpenWeatherItem {
             date,
@@ -314,32 +458,53 @@ impl OpenWeather {
             snow3,
             sunrise,
             sunset,
+            offset,
+            air: None,
+            units,
+            curve: Vec::new(),
+            average: 0,
+            trend: Trend::default(),
         })
     }
 
-    /// Display result
-    #[rustfmt::skip]
-    fn show(&self, item: &OpenWeatherItem, duration: i64, date: &str) {
-        println!("Weather for '{}'. OpenWeather server. Request time {} ms.", date, duration);
-        println!("Request address: {}.", item.address);
-        println!("Found address: {} ({},{}).", item.geo.address, item.geo.lat, item.geo.lon);
-        println!("Forecast date on the server: {}", item.date.format("%Y-%m-%d %H:%M:%S (%:z)"));
-        println!("{}", "-".repeat(40));
-        println!("Group of weather parameters  : {}", item.group.as_ref().map_or("None".to_owned(), |s| s.to_owned()));
-        println!("Temperature                  : {}", item.temp.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Human perception temperature : {}", item.feels_like.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Atmospheric pressure         : {}", item.pressure.map_or("None".to_owned(), |s| s.to_string() + " hPa"));
-        println!("Humidity                     : {}", item.humidity.map_or("None".to_owned(), |s| s.to_string() + " %"));
-        println!("Wind speed                   : {}", item.speed.map_or("None".to_owned(), |s| format!("{:#.1} meter/sec", s)));
-        println!("Wind direction and degrees   : {:?} ({})", item.dir, item.deg.map_or("None".to_owned(), |s| s.to_string() + "°"));
-        println!("Wind gust                    : {}", item.gust.map_or("None".to_owned(), |s| format!("{:#.1} meter/sec", s)));
-        println!("Rain volume (last 1 hour)    : {}", item.rain1.map_or("None".to_owned(), |s| format!("{:#.1} mm", s)));
-        println!("Rain volume (last 3 hour)    : {}", item.rain3.map_or("None".to_owned(), |s| format!("{:#.1} mm", s)));
-        println!("Snow volume (last 1 hour)    : {}", item.snow1.map_or("None".to_owned(), |s| format!("{:#.1} mm", s)));
-        println!("Snow volume (last 3 hour)    : {}", item.snow3.map_or("None".to_owned(), |s| format!("{:#.1} mm", s)));
-        println!("Visibility                   : {}", item.visibility.map_or("None".to_owned(), |s| s.to_string() + " meter"));
-        println!("Sunrise time                 : {}", item.sunrise.map_or("None".to_owned(), |dt| dt.format("%Y-%m-%d %H:%M:%S (%:z)").to_string()));
-        println!("Sunset time                  : {}", item.sunset.map_or("None".to_owned(), |dt| dt.format("%Y-%m-%d %H:%M:%S (%:z)").to_string()));
+    /// Convert result to the normalized weather report
+    fn report(&self, item: OpenWeatherItem) -> WeatherReport {
+        let precipitation = match (item.rain1.or(item.rain3), item.snow1.or(item.snow3)) {
+            (Some(rain), Some(snow)) => Some(rain + snow),
+            (rain, snow) => rain.or(snow),
+        };
+        // The normalized report is metric: Fahrenheit to Celsius, miles/hour to km/hour
+        let temp = |s: f32| match item.units {
+            Units::Metric => s,
+            Units::Imperial => (s - 32.0) * 5.0 / 9.0,
+        };
+        let speed = |s: f32| match item.units {
+            Units::Metric => s * 3.6,
+            Units::Imperial => s * 1.609_344,
+        };
+        WeatherReport {
+            condition: item.group,
+            temperature: item.temp.map(temp),
+            feels_like: item.feels_like.map(temp),
+            humidity: item.humidity,
+            pressure: item.pressure.map(|s| s as f32),
+            wind_speed: item.speed.map(speed),
+            wind_deg: item.deg,
+            wind_dir: item.dir,
+            precipitation,
+            wind_gust: item.gust.map(speed),
+            // mm to cm
+            snow: item.snow1.or(item.snow3).map(|s| s / 10.0),
+            // meter to km
+            visibility: item.visibility.map(|s| s as f32 / 1000.0),
+            sunrise: item.sunrise,
+            sunset: item.sunset,
+            air: item.air,
+            curve: item.curve,
+            average: item.average,
+            trend: item.trend,
+            ..WeatherReport::new(self.name, item.address, item.geo, item.date, item.offset)
+        }
     }
 }
 
@@ -352,7 +517,8 @@ impl Provider for OpenWeather {
     }
 
     fn deserialize(&mut self, data: &str) -> bool {
-        let mut input = data.split(':');
+        // The key is the rest of the line, it's kept even if it contains a colon
+        let mut input = data.splitn(2, ':');
         match input.next() {
             Some(name) => {
                 if name != self.name {
@@ -360,14 +526,14 @@ impl Provider for OpenWeather {
                 }
             }
             None => {
-                println!("The data file structure is damaged. The data file will be deleted.");
+                Log::info("The data file structure is damaged. The data file will be deleted.");
                 return false;
             }
         };
         let key = match input.next() {
             Some(key) => key.to_owned(),
             None => {
-                println!("The data file structure is damaged. The data file will be deleted.");
+                Log::info("The data file structure is damaged. The data file will be deleted.");
                 return false;
             }
         };
@@ -379,50 +545,32 @@ impl Provider for OpenWeather {
         true
     }
 
-    fn get_weather(&self, address: String, date: Date) {
-        match date {
-            Date::Now => {
-                let start = Local::now();
-                let now = match self.get_now(address) {
-                    Some(now) => now,
-                    None => {
-                        println!("It is not possible to determine the date of the weather forecast sent by the provider");
-                        return;
-                    }
-                };
-                let duration = Local::now() - start;
-                self.show(&now, duration.num_milliseconds(), "now");
-            }
-            Date::Set(dt) => {
-                let start = Local::now();
-                let now = match self.get_date(address, &dt) {
-                    Some(now) => now,
-                    None => {
-                        println!("It is not possible to determine the date of the weather forecast sent by the provider");
-                        return;
-                    }
-                };
-                let duration = Local::now() - start;
-                self.show(
-                    &now,
-                    duration.num_milliseconds(),
-                    &dt.format("%Y-%m-%d %H:%M:%S (%:z)").to_string(),
-                );
-            }
-            _ => {}
-        }
-    }
-
     fn name(&self) -> &'static str {
         self.name
     }
 
+    fn capabilities(&self) -> &'static [Capability] {
+        &[
+            Capability::Temperature,
+            Capability::FeelsLike,
+            Capability::AirQuality,
+            Capability::Hourly,
+            Capability::DateRange,
+            Capability::Coordinates,
+        ]
+    }
+
+    fn forecast_days(&self) -> Option<i64> {
+        // The free forecast has the 3-hour steps for 5 days
+        Some(5)
+    }
+
     fn configure(&mut self) {
         println!("Configure credentials for {}: \n", self.name);
         match &self.key {
             Some(key) => print!(
                 "Please enter the API key to access the weather forecast. Current key={}: ",
-                key
+                Format::secret(key)
             ),
             None => print!("Please enter the API key to access the weather forecast: "),
         }
@@ -447,6 +595,73 @@ impl Provider for OpenWeather {
             self.key = Some(key);
         }
     }
+
+    fn fetch_range(
+        &self,
+        address: String,
+        start: &DateTime<Local>,
+        end: &DateTime<Local>,
+    ) -> Option<Vec<WeatherReport>> {
+        let list = self.get_list(address, Units::Metric)?;
+        let reports = list.into_iter().map(|item| self.report(item)).collect();
+        Some(WeatherReport::daily(reports, start, end))
+    }
+
+    fn key_from_env(&mut self) {
+        if let Ok(key) = env::var("WEATHER_OPENWEATHER_KEY") {
+            let key = key.trim();
+            if !key.is_empty() {
+                self.env_key = Some(key.to_owned());
+            }
+        }
+    }
+
+    fn credentials(&self) -> Vec<(&'static str, String)> {
+        key_rows(self.key.as_deref(), self.env_key.as_deref())
+    }
+
+    fn has_credentials(&self) -> bool {
+        self.key.is_some() || self.env_key.is_some()
+    }
+
+    fn reset(&mut self) {
+        self.key = None;
+    }
+
+    fn check(&self, geo: &Geo) -> Option<Result<(), HttpError>> {
+        let key = self.env_key.as_ref().or(self.key.as_ref())?;
+        let url = OpenWeather::url(CURRENT_URL, geo, key, Units::Metric);
+        Some(Budget::get_json(self.name, self.client.as_ref(), &url, &[]).map(|_| ()))
+    }
+
+    fn supports_aqi(&self) -> bool {
+        true
+    }
+
+    fn supports_graph(&self) -> bool {
+        true
+    }
+
+    fn supports_astro(&self) -> bool {
+        true
+    }
+
+    fn fetch_astro(&self, address: String, date: Date) -> Option<AstroReport> {
+        let item = self.get_item(address, &date, Options::get().units())?;
+        Some(AstroReport {
+            sunrise: item.sunrise,
+            sunset: item.sunset,
+            ..AstroReport::new(self.name, item.address, item.geo, item.date, item.offset)
+        })
+    }
+
+    fn fetch(&self, address: String, date: Date) -> Option<WeatherReport> {
+        let mut item = self.get_item(address, &date, Units::Metric)?;
+        if Options::get().aqi {
+            item.air = self.get_air(&item.geo, &date);
+        }
+        Some(self.report(item))
+    }
 }
 
 impl Default for OpenWeather {
@@ -454,3 +669,56 @@ impl Default for OpenWeather {
         OpenWeather::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+
+    use super::OpenWeather;
+    use crate::{geo::Geo, init::Units, work::Provider};
+
+    #[test]
+    fn test_report_units() {
+        let json = r#"{
+            "dt": 1683803100,
+            "weather": [{"main": "Clear"}],
+            "main": {"temp": 68.0, "feels_like": 50.0, "pressure": 1012, "humidity": 40},
+            "wind": {"speed": 10.0, "deg": 90}
+        }"#;
+        let items: Map<String, Value> = serde_json::from_str(json).unwrap();
+        let geo = Geo {
+            lat: "50.45".to_owned(),
+            lon: "30.52".to_owned(),
+            address: "Kyiv, Ukraine".to_owned(),
+            importance: None,
+        };
+        let provider = OpenWeather::new();
+        let item = provider
+            .detect(&items, geo, "Kyiv".to_owned(), None, None, Units::Imperial)
+            .unwrap();
+        assert_eq!(item.temp, Some(68.0));
+        let report = provider.report(item);
+        assert_eq!(report.temperature, Some(20.0));
+        assert_eq!(report.feels_like, Some(10.0));
+        // 10 miles/hour is 16.09344 km/hour
+        assert!((report.wind_speed.unwrap() - 16.093_44).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut provider = OpenWeather::new();
+        assert_eq!(provider.serialize(), "OpenWeather:");
+        assert!(provider.deserialize("OpenWeather:abc123"));
+        assert_eq!(provider.key.as_deref(), Some("abc123"));
+        assert_eq!(provider.serialize(), "OpenWeather:abc123");
+        // The colon is a part of the key
+        assert!(provider.deserialize("OpenWeather:abc:123"));
+        assert_eq!(provider.serialize(), "OpenWeather:abc:123");
+        assert!(provider.deserialize("OpenWeather:"));
+        assert_eq!(provider.key, None);
+        assert_eq!(provider.serialize(), "OpenWeather:");
+        assert!(!provider.deserialize("OpenWeather"));
+        assert!(!provider.deserialize("WeatherAPI:abc123"));
+        assert!(!provider.deserialize(""));
+    }
+}
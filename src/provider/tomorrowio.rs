@@ -0,0 +1,525 @@
+//! Weather provider [Tomorrow.io](https://www.tomorrow.io).
+//!
+//! The Timelines API returns the numeric weather codes, they are converted to phrases by `TomorrowIO::condition`.
+
+use std::{
+    env,
+    io::{stdin, stdout, Write},
+};
+
+use chrono::{DateTime, Local};
+use serde_json::{Map, Value};
+
+use crate::{
+    budget::Budget,
+    format::Format,
+    geo::Geo,
+    http::{Http, HttpClient, HttpError},
+    init::{Date, Options},
+    log::Log,
+    provider::{average_item, closest_item, key_rows, mean, Trend},
+    wind::WindDeg,
+    work::{Capability, Provider, WeatherReport},
+};
+
+/// Requested fields of the intervals.
+const FIELDS: &str = "temperature,temperatureApparent,humidity,pressureSurfaceLevel,windSpeed,windDirection,windGust,precipitationIntensity,cloudCover,visibility,uvIndex,weatherCode";
+
+/// Describes 'TomorrowIO' credentials
+///
+/// * `name: &'static str` - Provider name.
+/// * `client: Box<dyn HttpClient>` - Source of the server answers.
+/// * `key: Option<String>` - Api key.
+/// * `env_key: Option<String>` - Api key from the `WEATHER_TOMORROWIO_KEY` environment variable.
+pub struct TomorrowIO {
+    /// Provider name.
+    name: &'static str,
+    /// Source of the server answers.
+    client: Box<dyn HttpClient>,
+    /// Api key.
+    key: Option<String>,
+    /// Api key from the environment variable, takes precedence over `key`.
+    env_key: Option<String>,
+}
+
+/// TomorrowIO data format for one item
+#[derive(Debug, Clone)]
+struct TomorrowIOItem {
+    /// Start time of the interval. Local
+    date: DateTime<Local>,
+    /// Request Address
+    address: String,
+    /// Geo position
+    geo: Geo,
+    /// Weather code, e.g. 1000 - Clear
+    code: Option<u32>,
+    /// Temperature. Metric: Celsius
+    temp: Option<f32>,
+    /// Apparent temperature. Metric: Celsius
+    feelslike: Option<f32>,
+    /// Relative humidity, %
+    humidity: Option<f32>,
+    /// Surface level pressure, hPa
+    pressure: Option<f32>,
+    /// Wind speed, meter/sec
+    speed: Option<f32>,
+    /// Wind gust, meter/sec
+    gust: Option<f32>,
+    /// Wind degrees (meteorological)
+    deg: Option<u16>,
+    /// Wind direction (meteorological)
+    dir: WindDeg,
+    /// Precipitation intensity, mm/hr
+    precip: Option<f32>,
+    /// Cloud cover, %
+    cloud: Option<f32>,
+    /// Visibility, km
+    visibility: Option<f32>,
+    /// UV Index
+    uvindex: Option<f32>,
+    /// Number of the items averaged for the `--average` flag, 0 - the closest item only
+    average: usize,
+    /// Trend of the temperature, pressure and humidity versus the adjacent items of the forecast
+    trend: Trend,
+}
+
+impl TomorrowIO {
+    /// Create new empty provider
+    pub fn new() -> TomorrowIO {
+        TomorrowIO {
+            name: "TomorrowIO",
+            client: Box::new(Http),
+            key: None,
+            env_key: None,
+        }
+    }
+
+    /// Phrase of the weather code.
+    ///
+    /// * `code: u32` - Weather code of the server.
+    pub fn condition(code: u32) -> Option<&'static str> {
+        let text = match code {
+            1000 => "Clear",
+            1100 => "Mostly Clear",
+            1101 => "Partly Cloudy",
+            1102 => "Mostly Cloudy",
+            1001 => "Cloudy",
+            2000 => "Fog",
+            2100 => "Light Fog",
+            4000 => "Drizzle",
+            4001 => "Rain",
+            4200 => "Light Rain",
+            4201 => "Heavy Rain",
+            5000 => "Snow",
+            5001 => "Flurries",
+            5100 => "Light Snow",
+            5101 => "Heavy Snow",
+            6000 => "Freezing Drizzle",
+            6001 => "Freezing Rain",
+            6200 => "Light Freezing Rain",
+            6201 => "Heavy Freezing Rain",
+            7000 => "Ice Pellets",
+            7101 => "Heavy Ice Pellets",
+            7102 => "Light Ice Pellets",
+            8000 => "Thunderstorm",
+            _ => return None,
+        };
+        Some(text)
+    }
+
+    /// Url of the timeline request for the place.
+    ///
+    /// * `geo: &Geo` - The place.
+    /// * `timestep: &str` - Timeline step: `current` or `1h`.
+    /// * `key: &str` - API key.
+    fn url(geo: &Geo, timestep: &str, key: &str) -> String {
+        format!(
+            "https://api.tomorrow.io/v4/timelines?location={},{}&fields={}&timesteps={}&units=metric&apikey={}",
+            geo.lat, geo.lon, FIELDS, timestep, key
+        )
+    }
+
+    /// Load data from provider
+    ///
+    /// * `address: &str` - The provided address.
+    /// * `timestep: &str` - Timeline step: `current` or `1h`.
+    fn get_json(&self, address: &str, timestep: &str) -> Option<(Vec<Value>, Geo)> {
+        let key = match self.env_key.as_ref().or(self.key.as_ref()) {
+            Some(key) => key,
+            None => {
+                Log::error("TomorrowIO server API access key is not set. Please install it first.");
+                return None;
+            }
+        };
+        // Find geo coordinates by address
+        let geo = Geo::find(address)?;
+        let url = TomorrowIO::url(&geo, timestep, key);
+        let json = match Budget::get_json(self.name, self.client.as_ref(), &url, &[]) {
+            Ok(Value::Object(json)) => json,
+            Ok(_) => {
+                Log::info("Unable to recognize json response from server.");
+                return None;
+            }
+            Err(e) => {
+                e.log(Some(self.name));
+                return None;
+            }
+        };
+        let intervals = TomorrowIO::intervals(&json).or_else(|| {
+            Log::info("The TomorrowIO server did not provide weather forecast data");
+            None
+        })?;
+        Some((intervals, geo))
+    }
+
+    /// Intervals of the first timeline of the answer
+    fn intervals(json: &Map<String, Value>) -> Option<Vec<Value>> {
+        json.get("data")
+            .and_then(|d| d.get("timelines"))
+            .and_then(|t| t.get(0))
+            .and_then(|t| t.get("intervals"))
+            .and_then(|i| i.as_array())
+            .cloned()
+    }
+
+    /// Getting weather forecast for now
+    fn get_now(&self, address: String) -> Option<TomorrowIOItem> {
+        let (intervals, geo) = self.get_json(&address, "current")?;
+        let item = intervals.first()?.as_object()?;
+        self.detect(item, geo, address)
+    }
+
+    /// Getting weather forecast for `date`
+    fn get_date(&self, address: String, date: &DateTime<Local>) -> Option<TomorrowIOItem> {
+        let list = self.get_list(address)?;
+        let trend = Trend::around(
+            &list,
+            date,
+            true,
+            |item| item.date,
+            |item| [item.temp, item.pressure, item.humidity],
+        );
+        // Hourly forecast: the upcoming item is preferred, `--average` takes the nearest items
+        let mut item = match Options::get().average {
+            Some(count) => average_item(
+                list,
+                date,
+                count,
+                |item| item.date,
+                |item, items| {
+                    item.temp = mean(items, |item| item.temp);
+                    item.feelslike = mean(items, |item| item.feelslike);
+                    item.speed = mean(items, |item| item.speed);
+                    item.humidity = mean(items, |item| item.humidity);
+                    item.pressure = mean(items, |item| item.pressure);
+                    item.average = items.len();
+                },
+            )?,
+            None => closest_item(list, date, true, |item| item.date)?,
+        };
+        item.trend = trend;
+        Some(item)
+    }
+
+    /// Getting all hourly items of weather forecast
+    fn get_list(&self, address: String) -> Option<Vec<TomorrowIOItem>> {
+        let (intervals, geo) = self.get_json(&address, "1h")?;
+        // Load all TomorrowIOItem to vector
+        let mut list = Vec::with_capacity(intervals.len());
+        for item in &intervals {
+            if let Value::Object(map) = item {
+                let res = self.detect(map, geo.clone(), address.clone());
+                if let Some(item) = res {
+                    list.push(item);
+                }
+            }
+        }
+        if list.is_empty() {
+            return None;
+        }
+        Some(list)
+    }
+
+    /// Getting weather forecast for `date`
+    fn get_item(&self, address: String, date: &Date) -> Option<TomorrowIOItem> {
+        match date {
+            Date::Now => self.get_now(address),
+            Date::Set(dt) => self.get_date(address, dt),
+            Date::Error | Date::Range(..) => None,
+        }
+    }
+
+    /// Parse one interval of the timeline
+    fn detect(
+        &self,
+        items: &Map<String, Value>,
+        geo: Geo,
+        address: String,
+    ) -> Option<TomorrowIOItem> {
+        let date = items
+            .get("startTime")
+            .and_then(|s| s.as_str())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&Local))?;
+        let values = items.get("values")?;
+        let float = |name: &str| values.get(name).and_then(|s| s.as_f64()).map(|s| s as f32);
+        let code = values
+            .get("weatherCode")
+            .and_then(|s| s.as_u64())
+            .map(|s| s as u32);
+        let deg = float("windDirection").and_then(|s| WindDeg::normalize(s as f64));
+        let dir = WindDeg::get(deg);
+
+        Some(TomorrowIOItem {
+            date,
+            address,
+            geo,
+            code,
+            temp: float("temperature"),
+            feelslike: float("temperatureApparent"),
+            humidity: float("humidity"),
+            pressure: float("pressureSurfaceLevel"),
+            speed: float("windSpeed"),
+            gust: float("windGust"),
+            deg,
+            dir,
+            precip: float("precipitationIntensity"),
+            cloud: float("cloudCover"),
+            visibility: float("visibility"),
+            uvindex: float("uvIndex"),
+            average: 0,
+            trend: Trend::default(),
+        })
+    }
+
+    /// Phrase of the weather code of the item
+    fn item_condition(item: &TomorrowIOItem) -> Option<String> {
+        item.code.map(|code| {
+            TomorrowIO::condition(code).map_or_else(|| format!("Code {}", code), |s| s.to_owned())
+        })
+    }
+
+    /// Convert result to the normalized weather report
+    fn report(&self, item: TomorrowIOItem) -> WeatherReport {
+        WeatherReport {
+            condition: TomorrowIO::item_condition(&item),
+            temperature: item.temp,
+            feels_like: item.feelslike,
+            humidity: item.humidity.map(|s| s.round() as u32),
+            pressure: item.pressure,
+            // meter/sec to km/hour
+            wind_speed: item.speed.map(|s| s * 3.6),
+            wind_deg: item.deg,
+            wind_dir: item.dir,
+            precipitation: item.precip,
+            wind_gust: item.gust.map(|s| s * 3.6),
+            cloud_cover: item.cloud.map(|s| s.round() as u32),
+            visibility: item.visibility,
+            uv_index: item.uvindex,
+            average: item.average,
+            trend: item.trend,
+            ..WeatherReport::new(self.name, item.address, item.geo, item.date, None)
+        }
+    }
+}
+
+impl Provider for TomorrowIO {
+    fn serialize(&self) -> String {
+        match &self.key {
+            Some(key) => format!("{}:{}", self.name, key),
+            None => format!("{}:", self.name),
+        }
+    }
+
+    fn deserialize(&mut self, data: &str) -> bool {
+        let mut input = data.splitn(2, ':');
+        match input.next() {
+            Some(name) => {
+                if name != self.name {
+                    return false;
+                }
+            }
+            None => {
+                Log::info("The data file structure is damaged. The data file will be deleted.");
+                return false;
+            }
+        };
+        let key = match input.next() {
+            Some(key) => key.to_owned(),
+            None => {
+                Log::info("The data file structure is damaged. The data file will be deleted.");
+                return false;
+            }
+        };
+        if key.is_empty() {
+            self.key = None;
+            return true;
+        }
+        self.key = Some(key);
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[
+            Capability::Temperature,
+            Capability::FeelsLike,
+            Capability::Uv,
+            Capability::Hourly,
+            Capability::DateRange,
+            Capability::Coordinates,
+        ]
+    }
+
+    fn forecast_days(&self) -> Option<i64> {
+        // The timelines of the free plan are for 5 days
+        Some(5)
+    }
+
+    fn configure(&mut self) {
+        println!("Configure credentials for {}: \n", self.name);
+        match &self.key {
+            Some(key) => print!(
+                "Please enter the API key to access the weather forecast. Current key={}: ",
+                Format::secret(key)
+            ),
+            None => print!("Please enter the API key to access the weather forecast: "),
+        }
+        if let Err(e) = stdout().flush() {
+            print!("System error: {}\n\nFailed to set key.", e);
+            return;
+        };
+        let mut input = String::new();
+        if let Err(e) = stdin().read_line(&mut input) {
+            print!(
+                "The key must be only printed characters. Error: {}\n\nFailed to set key.",
+                e
+            );
+            return;
+        }
+        let key = input.trim().to_string();
+        if key.is_empty() {
+            print!("The key was removed successfully.");
+            self.key = None;
+        } else {
+            print!("The key '{}' was setted successfully.", key);
+            self.key = Some(key);
+        }
+    }
+
+    fn fetch_range(
+        &self,
+        address: String,
+        start: &DateTime<Local>,
+        end: &DateTime<Local>,
+    ) -> Option<Vec<WeatherReport>> {
+        let list = self.get_list(address)?;
+        let reports = list.into_iter().map(|item| self.report(item)).collect();
+        Some(WeatherReport::daily(reports, start, end))
+    }
+
+    fn key_from_env(&mut self) {
+        if let Ok(key) = env::var("WEATHER_TOMORROWIO_KEY") {
+            let key = key.trim();
+            if !key.is_empty() {
+                self.env_key = Some(key.to_owned());
+            }
+        }
+    }
+
+    fn credentials(&self) -> Vec<(&'static str, String)> {
+        key_rows(self.key.as_deref(), self.env_key.as_deref())
+    }
+
+    fn has_credentials(&self) -> bool {
+        self.key.is_some() || self.env_key.is_some()
+    }
+
+    fn reset(&mut self) {
+        self.key = None;
+    }
+
+    fn check(&self, geo: &Geo) -> Option<Result<(), HttpError>> {
+        let key = self.env_key.as_ref().or(self.key.as_ref())?;
+        let url = TomorrowIO::url(geo, "current", key);
+        Some(Budget::get_json(self.name, self.client.as_ref(), &url, &[]).map(|_| ()))
+    }
+
+    fn fetch(&self, address: String, date: Date) -> Option<WeatherReport> {
+        self.get_item(address, &date).map(|item| self.report(item))
+    }
+}
+
+impl Default for TomorrowIO {
+    fn default() -> TomorrowIO {
+        TomorrowIO::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+
+    use super::TomorrowIO;
+    use crate::{geo::Geo, work::Provider};
+
+    #[test]
+    fn test_condition() {
+        assert_eq!(TomorrowIO::condition(1000), Some("Clear"));
+        assert_eq!(TomorrowIO::condition(4201), Some("Heavy Rain"));
+        assert_eq!(TomorrowIO::condition(8000), Some("Thunderstorm"));
+        assert_eq!(TomorrowIO::condition(0), None);
+    }
+
+    #[test]
+    fn test_intervals() {
+        let json = r#"{"data": {"timelines": [{
+            "timestep": "1h",
+            "intervals": [{
+                "startTime": "2023-05-11T12:00:00Z",
+                "values": {"temperature": 18.3, "windSpeed": 4.1, "windDirection": 92.6, "humidity": 60, "weatherCode": 1101}
+            }]
+        }]}}"#;
+        let json: Map<String, Value> = serde_json::from_str(json).unwrap();
+        let intervals = TomorrowIO::intervals(&json).unwrap();
+        let geo = Geo {
+            lat: "50.45".to_owned(),
+            lon: "30.52".to_owned(),
+            address: "Kyiv, Ukraine".to_owned(),
+            importance: None,
+        };
+        let provider = TomorrowIO::new();
+        let item = provider
+            .detect(intervals[0].as_object().unwrap(), geo, "Kyiv".to_owned())
+            .unwrap();
+        assert_eq!(item.temp, Some(18.3));
+        assert_eq!(item.deg, Some(93));
+        assert_eq!(item.humidity, Some(60.0));
+        let report = provider.report(item);
+        assert_eq!(report.condition, Some("Partly Cloudy".to_owned()));
+
+        let json: Map<String, Value> = serde_json::from_str(r#"{"data": {}}"#).unwrap();
+        assert_eq!(TomorrowIO::intervals(&json), None);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut provider = TomorrowIO::new();
+        assert_eq!(provider.serialize(), "TomorrowIO:");
+        assert!(provider.deserialize("TomorrowIO:abc123"));
+        assert_eq!(provider.key.as_deref(), Some("abc123"));
+        assert_eq!(provider.serialize(), "TomorrowIO:abc123");
+        // The colon is a part of the key
+        assert!(provider.deserialize("TomorrowIO:abc:123"));
+        assert_eq!(provider.serialize(), "TomorrowIO:abc:123");
+        assert!(provider.deserialize("TomorrowIO:"));
+        assert_eq!(provider.key, None);
+        assert_eq!(provider.serialize(), "TomorrowIO:");
+        assert!(!provider.deserialize("TomorrowIO"));
+        assert!(!provider.deserialize("WeatherAPI:abc123"));
+        assert!(!provider.deserialize(""));
+    }
+}
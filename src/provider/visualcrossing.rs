@@ -0,0 +1,644 @@
+//! Weather provider [Visual Crossing](https://www.visualcrossing.com).
+//!
+//! The Timeline API accepts the address directly, so the Nominatim geocoding is not used.
+
+use std::{
+    env,
+    io::{stdin, stdout, Write},
+};
+
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
+use serde_json::{Map, Value};
+use urlencoding::encode;
+
+use crate::{
+    astro::AstroReport,
+    budget::Budget,
+    format::Format,
+    geo::Geo,
+    http::{Http, HttpClient, HttpError},
+    init::{Date, Options, DATE_WINDOW},
+    log::Log,
+    provider::{average_item, closest_item, key_rows, mean, Trend},
+    wind::WindDeg,
+    work::{Capability, Provider, WeatherReport},
+};
+
+/// Describes 'VisualCrossing' credentials
+///
+/// * `name: &'static str` - Provider name.
+/// * `client: Box<dyn HttpClient>` - Source of the server answers.
+/// * `key: Option<String>` - Api key.
+/// * `env_key: Option<String>` - Api key from the `WEATHER_VISUALCROSSING_KEY` environment variable.
+pub struct VisualCrossing {
+    /// Provider name.
+    name: &'static str,
+    /// Source of the server answers.
+    client: Box<dyn HttpClient>,
+    /// Api key.
+    key: Option<String>,
+    /// Api key from the environment variable, takes precedence over `key`.
+    env_key: Option<String>,
+}
+
+/// VisualCrossing data format for one item
+#[derive(Debug, Clone)]
+struct VisualCrossingItem {
+    /// Time of data calculation from provider. Local
+    date: DateTime<Local>,
+    /// Request Address
+    address: String,
+    /// Geo position (resolved by the provider)
+    geo: Geo,
+    /// Weather conditions text
+    conditions: Option<String>,
+    /// Temperature. Metric: Celsius
+    temp: Option<f32>,
+    /// Feels like temperature. Metric: Celsius
+    feelslike: Option<f32>,
+    /// Dew point. Metric: Celsius
+    dew: Option<f32>,
+    /// Humidity, %
+    humidity: Option<f32>,
+    /// Precipitation amount, mm
+    precip: Option<f32>,
+    /// Chance of precipitation, %
+    precipprob: Option<f32>,
+    /// Snow amount, cm
+    snow: Option<f32>,
+    /// Wind speed, km/hour
+    windspeed: Option<f32>,
+    /// Wind gust, km/hour
+    windgust: Option<f32>,
+    /// Wind degrees (meteorological)
+    winddir: Option<u16>,
+    /// Wind direction (meteorological)
+    dir: WindDeg,
+    /// Sea level pressure, mbar
+    pressure: Option<f32>,
+    /// Visibility, km
+    visibility: Option<f32>,
+    /// Cloud cover, %
+    cloudcover: Option<f32>,
+    /// UV Index
+    uvindex: Option<f32>,
+    /// Sunrise time. Local
+    sunrise: Option<DateTime<Local>>,
+    /// Sunset time. Local
+    sunset: Option<DateTime<Local>>,
+    /// UTC offset of the location, the system timezone is used when it's unknown
+    offset: Option<FixedOffset>,
+    /// Number of the items averaged for the `--average` flag, 0 - the closest item only
+    average: usize,
+    /// Trend of the temperature, pressure and humidity versus the adjacent items of the forecast
+    trend: Trend,
+}
+
+impl VisualCrossing {
+    /// Create new empty provider
+    pub fn new() -> VisualCrossing {
+        VisualCrossing {
+            name: "VisualCrossing",
+            client: Box::new(Http),
+            key: None,
+            env_key: None,
+        }
+    }
+
+    /// Url of the Timeline request.
+    ///
+    /// * `location: &str` - Encoded place name or `lat,lon`.
+    /// * `path: Option<&str>` - Timeline date path, e.g. `2023-05-01` or `today`.
+    /// * `include: &str` - Sections of the answer.
+    /// * `key: &str` - API key.
+    fn url(location: &str, path: Option<&str>, include: &str, key: &str) -> String {
+        format!(
+            "https://weather.visualcrossing.com/VisualCrossingWebServices/rest/services/timeline/{}{}?unitGroup=metric&include={}&contentType=json&key={}&lang={}",
+            location,
+            path.map_or(String::new(), |p| format!("/{}", p)),
+            include,
+            key,
+            Options::get().lang()
+        )
+    }
+
+    /// Load data from provider
+    ///
+    /// * `address: &str` - The provided address, passed to the server as is.
+    /// * `path: Option<&str>` - Timeline date path, e.g. `2023-05-01` or `2023-05-01/2023-05-05`.
+    /// * `include: &str` - Sections of the answer.
+    fn get_json(
+        &self,
+        address: &str,
+        path: Option<&str>,
+        include: &str,
+    ) -> Option<(Map<String, Value>, Geo)> {
+        let key = match self.env_key.as_ref().or(self.key.as_ref()) {
+            Some(key) => key,
+            None => {
+                Log::error(
+                    "VisualCrossing server API access key is not set. Please install it first.",
+                );
+                return None;
+            }
+        };
+        // The server understands both place names and coordinates
+        let location = match Geo::parse_coords(address) {
+            Some((lat, lon)) => format!("{},{}", lat, lon),
+            None => encode(address).into_owned(),
+        };
+        let url = VisualCrossing::url(&location, path, include, key);
+        let json = match Budget::get_json(self.name, self.client.as_ref(), &url, &[]) {
+            Ok(Value::Object(json)) => json,
+            Ok(_) => {
+                Log::info("Unable to recognize json response from server.");
+                return None;
+            }
+            Err(e) => {
+                e.log(Some(self.name));
+                return None;
+            }
+        };
+        let geo = VisualCrossing::geo(&json).or_else(|| {
+            Log::error("Sorry, we couldn't find your address");
+            None
+        })?;
+        Some((json, geo))
+    }
+
+    /// Geo position resolved by the server
+    fn geo(json: &Map<String, Value>) -> Option<Geo> {
+        let lat = json.get("latitude").and_then(|s| s.as_f64())?;
+        let lon = json.get("longitude").and_then(|s| s.as_f64())?;
+        let address = json
+            .get("resolvedAddress")
+            .and_then(|s| s.as_str())
+            .map_or_else(|| format!("{},{}", lat, lon), |s| s.to_owned());
+        Some(Geo {
+            lat: lat.to_string(),
+            lon: lon.to_string(),
+            address,
+            importance: None,
+        })
+    }
+
+    /// Getting weather forecast for now
+    fn get_now(&self, address: String) -> Option<VisualCrossingItem> {
+        let (items, geo) = self.get_json(&address, None, "current,days")?;
+        let current = items
+            .get("currentConditions")
+            .and_then(|its| its.as_object())
+            .or_else(|| {
+                Log::info("The VisualCrossing server did not provide weather forecast data");
+                None
+            })?;
+        // Sunrise and sunset of the current day
+        let day = items
+            .get("days")
+            .and_then(|d| d.get(0))
+            .and_then(|d| d.as_object());
+        let mut item = self.detect(current, day, geo, address)?;
+        item.offset = VisualCrossing::offset(&items);
+        Some(item)
+    }
+
+    /// Getting weather forecast for `date`
+    fn get_date(&self, address: String, date: &DateTime<Local>) -> Option<VisualCrossingItem> {
+        let list = self.get_list(address, &date.format("%Y-%m-%d").to_string())?;
+        let trend = Trend::around(
+            &list,
+            date,
+            true,
+            |item| item.date,
+            |item| [item.temp, item.pressure, item.humidity],
+        );
+        // Hourly forecast: the upcoming item is preferred, `--average` takes the nearest items
+        let mut item = match Options::get().average {
+            Some(count) => average_item(
+                list,
+                date,
+                count,
+                |item| item.date,
+                |item, items| {
+                    item.temp = mean(items, |item| item.temp);
+                    item.feelslike = mean(items, |item| item.feelslike);
+                    item.windspeed = mean(items, |item| item.windspeed);
+                    item.humidity = mean(items, |item| item.humidity);
+                    item.pressure = mean(items, |item| item.pressure);
+                    item.average = items.len();
+                },
+            )?,
+            None => closest_item(list, date, true, |item| item.date)?,
+        };
+        item.trend = trend;
+        Some(item)
+    }
+
+    /// Getting all hourly items of weather forecast for the timeline `path`
+    fn get_list(&self, address: String, path: &str) -> Option<Vec<VisualCrossingItem>> {
+        // Load json from provider
+        let (items, geo) = self.get_json(&address, Some(path), "days,hours")?;
+        let days = items.get("days").and_then(|i| i.as_array()).or_else(|| {
+            Log::info("The VisualCrossing server did not provide weather forecast data");
+            None
+        })?;
+        let offset = VisualCrossing::offset(&items);
+        // Load all VisualCrossingItem to vector
+        let mut list = Vec::with_capacity(24 * days.len());
+        for day in days.iter().filter_map(|d| d.as_object()) {
+            let hours = match day.get("hours").and_then(|h| h.as_array()) {
+                Some(hours) => hours,
+                None => continue,
+            };
+            for item in hours {
+                if let Value::Object(map) = item {
+                    let res = self.detect(map, Some(day), geo.clone(), address.clone());
+                    if let Some(mut item) = res {
+                        // The hours of a daylight saving time change have their own offset
+                        item.offset = item.offset.or(offset);
+                        list.push(item);
+                    }
+                }
+            }
+        }
+        if list.is_empty() {
+            return None;
+        }
+        Some(list)
+    }
+
+    /// Getting weather forecast for `date`
+    fn get_item(&self, address: String, date: &Date) -> Option<VisualCrossingItem> {
+        match date {
+            Date::Now => self.get_now(address),
+            Date::Set(dt) => self.get_date(address, dt),
+            Date::Error | Date::Range(..) => None,
+        }
+    }
+
+    /// UTC offset of the location, e.g. `"tzoffset": 3.0` hours
+    fn offset(map: &Map<String, Value>) -> Option<FixedOffset> {
+        map.get("tzoffset")
+            .and_then(|s| s.as_f64())
+            .and_then(|s| Format::offset(s * 3600.0))
+    }
+
+    /// Parse json answer from server
+    ///
+    /// * `items: &Map<String, Value>` - Hour or current conditions.
+    /// * `day: Option<&Map<String, Value>>` - Day of the item, source of sunrise and sunset.
+    fn detect(
+        &self,
+        items: &Map<String, Value>,
+        day: Option<&Map<String, Value>>,
+        geo: Geo,
+        address: String,
+    ) -> Option<VisualCrossingItem> {
+        let float = |name: &str| items.get(name).and_then(|s| s.as_f64()).map(|s| s as f32);
+        let time = |map: &Map<String, Value>, name: &str| {
+            map.get(name)
+                .and_then(|s| s.as_i64())
+                .and_then(|t| Utc.timestamp_opt(t, 0).single())
+                .map(|t| Local.from_utc_datetime(&t.naive_utc()))
+        };
+        let date = time(items, "datetimeEpoch")?;
+        let conditions = items
+            .get("conditions")
+            .and_then(|s| s.as_str())
+            .map(|s| s.to_owned());
+        let winddir = items
+            .get("winddir")
+            .and_then(|s| s.as_f64())
+            .and_then(WindDeg::normalize);
+        let dir = WindDeg::get(winddir);
+        let sunrise =
+            time(items, "sunriseEpoch").or_else(|| day.and_then(|d| time(d, "sunriseEpoch")));
+        let sunset =
+            time(items, "sunsetEpoch").or_else(|| day.and_then(|d| time(d, "sunsetEpoch")));
+
+        Some(VisualCrossingItem {
+            date,
+            address,
+            geo,
+            conditions,
+            temp: float("temp"),
+            feelslike: float("feelslike"),
+            dew: float("dew"),
+            humidity: float("humidity"),
+            precip: float("precip"),
+            precipprob: float("precipprob"),
+            snow: float("snow"),
+            windspeed: float("windspeed"),
+            windgust: float("windgust"),
+            winddir,
+            dir,
+            pressure: float("pressure"),
+            visibility: float("visibility"),
+            cloudcover: float("cloudcover"),
+            uvindex: float("uvindex"),
+            sunrise,
+            sunset,
+            offset: VisualCrossing::offset(items),
+            average: 0,
+            trend: Trend::default(),
+        })
+    }
+
+    /// Convert result to the normalized weather report
+    fn report(&self, item: VisualCrossingItem) -> WeatherReport {
+        WeatherReport {
+            condition: item.conditions,
+            temperature: item.temp,
+            feels_like: item.feelslike,
+            humidity: item.humidity.map(|s| s.round() as u32),
+            // mbar is hPa
+            pressure: item.pressure,
+            wind_speed: item.windspeed,
+            wind_deg: item.winddir,
+            wind_dir: item.dir,
+            precipitation: item.precip,
+            dew_point: item.dew,
+            wind_gust: item.windgust,
+            precipitation_chance: item.precipprob.map(|s| s.round() as u32),
+            snow: item.snow,
+            cloud_cover: item.cloudcover.map(|s| s.round() as u32),
+            visibility: item.visibility,
+            uv_index: item.uvindex,
+            sunrise: item.sunrise,
+            sunset: item.sunset,
+            average: item.average,
+            trend: item.trend,
+            ..WeatherReport::new(self.name, item.address, item.geo, item.date, item.offset)
+        }
+    }
+}
+
+impl Provider for VisualCrossing {
+    fn serialize(&self) -> String {
+        match &self.key {
+            Some(key) => format!("{}:{}", self.name, key),
+            None => format!("{}:", self.name),
+        }
+    }
+
+    fn deserialize(&mut self, data: &str) -> bool {
+        let mut input = data.splitn(2, ':');
+        match input.next() {
+            Some(name) => {
+                if name != self.name {
+                    return false;
+                }
+            }
+            None => {
+                Log::info("The data file structure is damaged. The data file will be deleted.");
+                return false;
+            }
+        };
+        let key = match input.next() {
+            Some(key) => key.to_owned(),
+            None => {
+                Log::info("The data file structure is damaged. The data file will be deleted.");
+                return false;
+            }
+        };
+        if key.is_empty() {
+            self.key = None;
+            return true;
+        }
+        self.key = Some(key);
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[
+            Capability::Temperature,
+            Capability::FeelsLike,
+            Capability::Uv,
+            Capability::Snow,
+            Capability::Hourly,
+            Capability::DateRange,
+        ]
+    }
+
+    fn configure(&mut self) {
+        println!("Configure credentials for {}: \n", self.name);
+        match &self.key {
+            Some(key) => print!(
+                "Please enter the API key to access the weather forecast. Current key={}: ",
+                Format::secret(key)
+            ),
+            None => print!("Please enter the API key to access the weather forecast: "),
+        }
+        if let Err(e) = stdout().flush() {
+            print!("System error: {}\n\nFailed to set key.", e);
+            return;
+        };
+        let mut input = String::new();
+        if let Err(e) = stdin().read_line(&mut input) {
+            print!(
+                "The key must be only printed characters. Error: {}\n\nFailed to set key.",
+                e
+            );
+            return;
+        }
+        let key = input.trim().to_string();
+        if key.is_empty() {
+            print!("The key was removed successfully.");
+            self.key = None;
+        } else {
+            print!("The key '{}' was setted successfully.", key);
+            self.key = Some(key);
+        }
+    }
+
+    fn fetch_range(
+        &self,
+        address: String,
+        start: &DateTime<Local>,
+        end: &DateTime<Local>,
+    ) -> Option<Vec<WeatherReport>> {
+        // The timeline returns the whole range in a single request
+        let path = format!("{}/{}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"));
+        let list = self.get_list(address, &path)?;
+        let reports = list.into_iter().map(|item| self.report(item)).collect();
+        Some(WeatherReport::daily(reports, start, end))
+    }
+
+    fn date_window(&self) -> (i64, i64) {
+        // The timeline has the historical data since 1970, the days of the unix time
+        (Utc::now().timestamp() / 86_400, DATE_WINDOW)
+    }
+
+    fn key_from_env(&mut self) {
+        if let Ok(key) = env::var("WEATHER_VISUALCROSSING_KEY") {
+            let key = key.trim();
+            if !key.is_empty() {
+                self.env_key = Some(key.to_owned());
+            }
+        }
+    }
+
+    fn credentials(&self) -> Vec<(&'static str, String)> {
+        key_rows(self.key.as_deref(), self.env_key.as_deref())
+    }
+
+    fn has_credentials(&self) -> bool {
+        self.key.is_some() || self.env_key.is_some()
+    }
+
+    fn reset(&mut self) {
+        self.key = None;
+    }
+
+    fn check(&self, geo: &Geo) -> Option<Result<(), HttpError>> {
+        let key = self.env_key.as_ref().or(self.key.as_ref())?;
+        let location = format!("{},{}", geo.lat, geo.lon);
+        let url = VisualCrossing::url(&location, Some("today"), "current", key);
+        Some(Budget::get_json(self.name, self.client.as_ref(), &url, &[]).map(|_| ()))
+    }
+
+    fn supports_astro(&self) -> bool {
+        true
+    }
+
+    fn fetch_astro(&self, address: String, date: Date) -> Option<AstroReport> {
+        let item = self.get_item(address, &date)?;
+        Some(AstroReport {
+            sunrise: item.sunrise,
+            sunset: item.sunset,
+            ..AstroReport::new(self.name, item.address, item.geo, item.date, item.offset)
+        })
+    }
+
+    fn fetch(&self, address: String, date: Date) -> Option<WeatherReport> {
+        self.get_item(address, &date).map(|item| self.report(item))
+    }
+}
+
+impl Default for VisualCrossing {
+    fn default() -> VisualCrossing {
+        VisualCrossing::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VisualCrossing;
+    use crate::{http::MockHttp, wind::WindDeg, work::Provider};
+
+    /// Timeline answer for Kyiv on 2023-05-11, the last hour has its own offset
+    const TIMELINE: &str = r#"{
+        "latitude": 50.4501,
+        "longitude": 30.5234,
+        "resolvedAddress": "Київ, Україна",
+        "tzoffset": 3.0,
+        "currentConditions": {
+            "datetimeEpoch": 1683803100,
+            "conditions": "Partially cloudy",
+            "temp": 18.5,
+            "humidity": 55.0,
+            "windspeed": 12.2,
+            "winddir": 20.0,
+            "pressure": 1016.0
+        },
+        "days": [
+            {
+                "datetimeEpoch": 1683752400,
+                "sunriseEpoch": 1683771540,
+                "sunsetEpoch": 1683826560,
+                "hours": [
+                    {"datetimeEpoch": 1683802800, "temp": 18.0, "conditions": "Clear"},
+                    {"datetimeEpoch": 1683806400, "temp": 19.0, "conditions": "Overcast", "tzoffset": 2.0}
+                ]
+            },
+            {"datetimeEpoch": 1683838800}
+        ]
+    }"#;
+
+    /// Provider with the canned answer for the url pattern
+    fn provider(pattern: &str, json: &str) -> VisualCrossing {
+        VisualCrossing {
+            client: Box::new(MockHttp::new(&[(pattern, json)])),
+            env_key: Some("test".to_owned()),
+            ..VisualCrossing::new()
+        }
+    }
+
+    #[test]
+    fn test_get_now() {
+        let provider = provider("timeline/Kyiv?", TIMELINE);
+        let item = provider.get_now("Kyiv".to_owned()).unwrap();
+        assert_eq!(item.date.timestamp(), 1683803100);
+        assert_eq!(item.conditions.as_deref(), Some("Partially cloudy"));
+        assert_eq!(item.temp, Some(18.5));
+        assert_eq!(item.winddir, Some(20));
+        assert_eq!(item.dir, WindDeg::NorthNorthEast);
+        assert_eq!(item.geo.address, "Київ, Україна");
+        assert_eq!(item.geo.lat, "50.4501");
+        // Sunrise and sunset of the first day
+        assert_eq!(item.sunrise.map(|t| t.timestamp()), Some(1683771540));
+        assert_eq!(item.sunset.map(|t| t.timestamp()), Some(1683826560));
+        assert_eq!(
+            item.offset.map(|o| o.to_string()),
+            Some("+03:00".to_owned())
+        );
+
+        // No current conditions in the answer
+        let provider = self::provider("timeline/Kyiv?", r#"{"latitude": 50.4, "longitude": 30.5}"#);
+        assert!(provider.get_now("Kyiv".to_owned()).is_none());
+        // The server didn't resolve the address
+        let provider = self::provider("timeline/Kyiv?", r#"{"currentConditions": {}}"#);
+        assert!(provider.get_now("Kyiv".to_owned()).is_none());
+    }
+
+    #[test]
+    fn test_get_list() {
+        let provider = provider("timeline/Kyiv/2023-05-11?", TIMELINE);
+        let list = provider.get_list("Kyiv".to_owned(), "2023-05-11").unwrap();
+        // The day without hours is skipped
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].date.timestamp(), 1683802800);
+        assert_eq!(list[0].temp, Some(18.0));
+        assert_eq!(list[1].conditions.as_deref(), Some("Overcast"));
+        // The sunrise of the day is passed to each hour
+        assert_eq!(list[1].sunrise.map(|t| t.timestamp()), Some(1683771540));
+        // The offset of the answer, unless the hour has its own
+        assert_eq!(
+            list[0].offset.map(|o| o.to_string()),
+            Some("+03:00".to_owned())
+        );
+        assert_eq!(
+            list[1].offset.map(|o| o.to_string()),
+            Some("+02:00".to_owned())
+        );
+
+        // No days in the answer
+        let provider = self::provider("timeline/Kyiv/", r#"{"latitude": 50.4, "longitude": 30.5}"#);
+        assert!(provider.get_list("Kyiv".to_owned(), "2023-05-11").is_none());
+        // The server answered for another path
+        let provider = self::provider("timeline/Kyiv/2023-05-12?", TIMELINE);
+        assert!(provider.get_list("Kyiv".to_owned(), "2023-05-11").is_none());
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut provider = VisualCrossing::new();
+        assert_eq!(provider.serialize(), "VisualCrossing:");
+        assert!(provider.deserialize("VisualCrossing:abc123"));
+        assert_eq!(provider.key.as_deref(), Some("abc123"));
+        assert_eq!(provider.serialize(), "VisualCrossing:abc123");
+        // The colon is a part of the key
+        assert!(provider.deserialize("VisualCrossing:abc:123"));
+        assert_eq!(provider.serialize(), "VisualCrossing:abc:123");
+        assert!(provider.deserialize("VisualCrossing:"));
+        assert_eq!(provider.key, None);
+        assert_eq!(provider.serialize(), "VisualCrossing:");
+        assert!(!provider.deserialize("VisualCrossing"));
+        assert!(!provider.deserialize("WeatherAPI:abc123"));
+        assert!(!provider.deserialize(""));
+    }
+}
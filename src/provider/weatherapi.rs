@@ -2,29 +2,48 @@
 //!
 
 use std::{
+    env,
     io::{stdin, stdout, Write},
-    time::Duration,
 };
 
-use chrono::{DateTime, Local, TimeZone, Utc};
-use reqwest::blocking::Client;
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, TimeZone, Utc};
 use serde_json::{Map, Value};
 
-use crate::{geo::Geo, init::Date, wind::WindDeg, work::Provider};
+use crate::{
+    astro::AstroReport,
+    budget::Budget,
+    format::Format,
+    geo::Geo,
+    http::{Http, HttpClient, HttpError},
+    init::{Date, Options},
+    log::Log,
+    provider::{average_item, closest_item, day_curve, key_rows, mean, AirQuality, Trend},
+    wind::WindDeg,
+    work::{Capability, Provider, WeatherReport},
+};
+
+/// Endpoint of the current weather.
+const CURRENT_URL: &str = "https://api.weatherapi.com/v1/current.json";
 
 /// Describes 'WeatherAPI' credentials
 ///
 /// * `name: &'static str` - Provider name.
+/// * `client: Box<dyn HttpClient>` - Source of the server answers.
 /// * `key: Option<String>` - Api key.
+/// * `env_key: Option<String>` - Api key from the `WEATHER_WEATHERAPI_KEY` environment variable.
 pub struct WeatherAPI {
     /// Provider name.
     name: &'static str,
+    /// Source of the server answers.
+    client: Box<dyn HttpClient>,
     /// Api key
     key: Option<String>,
+    /// Api key from the environment variable, takes precedence over `key`.
+    env_key: Option<String>,
 }
 
 /// WeatherAPI data format for one item
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct WeatherAPIItem {
     /// Time of data calculation from provider. Local
     date: DateTime<Local>,
@@ -60,18 +79,30 @@ struct WeatherAPIItem {
     humidity: Option<u8>,
     /// Cloud cover as percentage
     cloud: Option<u8>,
-    /// Will it will rain or not
-    will_it_rain: Option<bool>,
     /// Chance of rain as percentage
     chance_of_rain: Option<u8>,
-    /// Will it will snow or not
-    will_it_snow: Option<bool>,
     /// Chance of snow as percentage
     chance_of_snow: Option<u8>,
     /// Visibility in kilometer
     vis: Option<f32>,
     /// UV Index
     uv: Option<f32>,
+    /// Air quality, provided with `aqi=yes` for the `--aqi` flag
+    air: Option<AirQuality>,
+    /// Active weather alerts, provided by the forecast with `alerts=yes`
+    alerts: Vec<String>,
+    /// Sunrise time of the forecast day
+    sunrise: Option<DateTime<Local>>,
+    /// Sunset time of the forecast day
+    sunset: Option<DateTime<Local>>,
+    /// UTC offset of the location, the system timezone is used when it's unknown
+    offset: Option<FixedOffset>,
+    /// Temperature of the hours of the requested day for the `--graph` flag
+    curve: Vec<(DateTime<Local>, f32)>,
+    /// Number of the items averaged for the `--average` flag, 0 - the closest item only
+    average: usize,
+    /// Trend of the temperature, pressure and humidity versus the adjacent items of the forecast
+    trend: Trend,
 }
 
 impl WeatherAPI {
@@ -79,7 +110,9 @@ impl WeatherAPI {
     pub fn new() -> WeatherAPI {
         WeatherAPI {
             name: "WeatherAPI",
+            client: Box::new(Http),
             key: None,
+            env_key: None,
         }
     }
 
@@ -88,129 +121,293 @@ impl WeatherAPI {
         &self,
         url: &str,
         address: &str,
-        date: Option<&str>,
+        query: Option<&str>,
     ) -> Option<(Map<String, Value>, Geo)> {
-        let key = match &self.key {
+        let key = match self.env_key.as_ref().or(self.key.as_ref()) {
             Some(key) => key,
             None => {
-                println!("WeatherAPI server API access key is not set. Please install it first.");
+                Log::error("WeatherAPI server API access key is not set. Please install it first.");
                 return None;
             }
         };
         // Find geo coordinates by address
-        let geo = match Geo::get(address) {
-            Some(mut geos) => match geos.pop() {
-                Some(geo) => geo,
-                None => {
-                    println!("Sorry, we couldn't find your address: {}", address);
-                    return None;
-                }
-            },
-            None => return None,
-        };
-        let url = match date {
-            Some(d) => format!("{}?key={}&q={},{}&dt={}", url, key, geo.lat, geo.lon, d),
-            None => format!("{}?key={}&q={},{}", url, key, geo.lat, geo.lon),
-        };
+        let geo = Geo::find(address)?;
+        let url = WeatherAPI::url(url, &geo, key, query);
 
-        // Client for url query
-        let client = match Client::builder().timeout(Duration::from_secs(3)).build() {
-            Ok(c) => c,
-            Err(e) => {
-                println!("The following error occurred while requesting coordinates for your address: {}", e);
+        let json = match Budget::get_json(self.name, self.client.as_ref(), &url, &[]) {
+            Ok(Value::Object(json)) => json,
+            Ok(_) => {
+                Log::info("Unable to recognize json response from server.");
                 return None;
             }
-        };
-
-        let json_str = match client.get(&url).send() {
-            Ok(s) => {
-                let status = s.status();
-                if status != 200 {
-                    println!("Error connecting to {}. Status code: {}", &url, status);
-                    return None;
-                }
-                match s.text() {
-                    Ok(s) => s,
-                    Err(e) => {
-                        println!("Error getting answer from {}. Error text: {}", &url, e);
-                        return None;
-                    }
-                }
-            }
             Err(e) => {
-                println!("Error connecting to {}. Error text: {}", &url, e);
+                e.log(Some(self.name));
                 return None;
             }
         };
-        // Parse json
-        match serde_json::from_str(&json_str) {
-            Ok(json) => Some((json, geo)),
-            Err(e) => {
-                println!(
-                    "Unable to recognize json response from server. Error text: {}",
-                    e
-                );
-                None
-            }
+        Some((json, geo))
+    }
+
+    /// Url of the request for the place.
+    ///
+    /// * `url: &str` - Endpoint, e.g. `CURRENT_URL`.
+    /// * `geo: &Geo` - The place.
+    /// * `key: &str` - API key.
+    /// * `query: Option<&str>` - Additional query parameters, e.g. `days=3`.
+    fn url(url: &str, geo: &Geo, key: &str, query: Option<&str>) -> String {
+        let options = Options::get();
+        let lang = options.lang();
+        let aqi = if options.aqi { "yes" } else { "no" };
+        match query {
+            Some(q) => format!(
+                "{}?key={}&q={},{}&lang={}&aqi={}&{}",
+                url, key, geo.lat, geo.lon, lang, aqi, q
+            ),
+            None => format!(
+                "{}?key={}&q={},{}&lang={}&aqi={}",
+                url, key, geo.lat, geo.lon, lang, aqi
+            ),
         }
     }
 
     /// Getting weather forecast for now
     fn get_now(&self, address: String) -> Option<WeatherAPIItem> {
-        let (items, geo) =
-            self.get_json("https://api.weatherapi.com/v1/current.json", &address, None)?;
+        let (items, geo) = self.get_json(CURRENT_URL, &address, None)?;
+        let offset = WeatherAPI::offset(&items);
         let items = items
             .get("current")
             .and_then(|its| its.as_object())
             .or_else(|| {
-                println!("The WeatherAPI server did not provide weather forecast data");
+                Log::info("The WeatherAPI server did not provide weather forecast data");
                 None
             })?;
-        self.detect(items, geo, address)
+        let mut item = self.detect(items, geo, address)?;
+        item.offset = offset;
+        Some(item)
     }
 
     /// Getting weather forecast for `date`
     fn get_date(&self, address: String, date: &DateTime<Local>) -> Option<WeatherAPIItem> {
+        let query = format!("dt={}", date.format("%Y-%m-%d"));
+        let list = self.get_list(address, &query)?;
+        let offset = list.first().and_then(|item| item.offset);
+        let curve = day_curve(&list, date, offset, |item| (item.date, item.temp));
+        let trend = Trend::around(
+            &list,
+            date,
+            true,
+            |item| item.date,
+            |item| [item.temp, item.pressure, item.humidity.map(|h| h as f32)],
+        );
+        // Hourly forecast: the upcoming item is preferred, `--average` takes the nearest items
+        let mut item = match Options::get().average {
+            Some(count) => average_item(
+                list,
+                date,
+                count,
+                |item| item.date,
+                |item, items| {
+                    item.temp = mean(items, |item| item.temp);
+                    item.feelslike = mean(items, |item| item.feelslike);
+                    item.wind = mean(items, |item| item.wind);
+                    item.humidity = mean(items, |item| item.humidity.map(|h| h as f32))
+                        .map(|h| h.round() as u8);
+                    item.pressure = mean(items, |item| item.pressure);
+                    item.average = items.len();
+                },
+            )?,
+            None => closest_item(list, date, true, |item| item.date)?,
+        };
+        item.curve = curve;
+        item.trend = trend;
+        Some(item)
+    }
+
+    /// Getting all items of weather forecast
+    ///
+    /// * `query: &str` - Forecast days: `dt=yyyy-mm-dd` for one day or `days=N` for N days from today.
+    fn get_list(&self, address: String, query: &str) -> Option<Vec<WeatherAPIItem>> {
         // Load json from provider
-        let dt = date.format("%Y-%m-%d").to_string();
         let (items, geo) = self.get_json(
             "https://api.weatherapi.com/v1/forecast.json",
             &address,
-            Some(&dt),
+            Some(&format!("{}&alerts=yes", query)),
         )?;
+        let alerts = WeatherAPI::alerts(&items);
+        let offset = WeatherAPI::offset(&items);
         // Get list of WeatherAPIItem
-        let its = items
+        let days = items
             .get("forecast")
             .and_then(|i| i.get("forecastday"))
-            .and_then(|i| i.get(0))
-            .and_then(|i| i.get("hour"))
             .and_then(|i| i.as_array())
             .or_else(|| {
-                println!("The WeatherAPI server did not provide weather forecast data");
+                Log::info("The WeatherAPI server did not provide weather forecast data");
                 None
             })?;
         // Load all WeatherAPIItem to vector
-        let mut list = Vec::with_capacity(24);
-        for item in its {
-            if let Value::Object(map) = item {
-                let res = self.detect(map, geo.clone(), address.clone());
-                if let Some(item) = res {
-                    list.push(item);
+        let mut list = Vec::with_capacity(24 * days.len());
+        for day in days {
+            let its = match day.get("hour").and_then(|i| i.as_array()) {
+                Some(its) => its,
+                None => continue,
+            };
+            let (sunrise, sunset) = WeatherAPI::astro(day, offset);
+            for item in its {
+                if let Value::Object(map) = item {
+                    let res = self.detect(map, geo.clone(), address.clone());
+                    if let Some(mut item) = res {
+                        item.alerts = alerts.clone();
+                        item.offset = offset;
+                        item.sunrise = sunrise;
+                        item.sunset = sunset;
+                        list.push(item);
+                    }
                 }
             }
         }
         if list.is_empty() {
             return None;
         }
-        // Find item with the closest date
-        list.into_iter().min_by(|item_a, item_b| {
-            let diff_a = item_a.date.signed_duration_since(*date).num_seconds().abs();
-            let diff_b = item_b.date.signed_duration_since(*date).num_seconds().abs();
+        Some(list)
+    }
+
+    /// Active weather alerts of the forecast, e.g. `Flood Warning (Moderate), 2023-05-11T10:00:00+03:00 - 2023-05-12T06:00:00+03:00: ...`
+    fn alerts(json: &Map<String, Value>) -> Vec<String> {
+        let alerts = match json
+            .get("alerts")
+            .and_then(|a| a.get("alert"))
+            .and_then(|a| a.as_array())
+        {
+            Some(alerts) => alerts,
+            None => return Vec::new(),
+        };
+        alerts
+            .iter()
+            .filter_map(|alert| {
+                let text = |name: &str| {
+                    alert
+                        .get(name)
+                        .and_then(|s| s.as_str())
+                        .map(|s| s.split_whitespace().collect::<Vec<&str>>().join(" "))
+                        .filter(|s| !s.is_empty())
+                };
+                let mut line = text("event").or_else(|| text("headline"))?;
+                if let Some(severity) = text("severity") {
+                    line += &format!(" ({})", severity);
+                }
+                if let (Some(effective), Some(expires)) = (text("effective"), text("expires")) {
+                    line += &format!(", {} - {}", effective, expires);
+                }
+                if let Some(headline) = text("headline") {
+                    line += &format!(": {}", headline);
+                }
+                Some(line)
+            })
+            .collect()
+    }
+
+    /// Sunrise and sunset of the forecast day, e.g. `"astro": {"sunrise": "05:12 AM", "sunset": "08:30 PM"}`.
+    ///
+    /// The times are 12-hour local times of the location without the date, the date of the day is used.
+    ///
+    /// * `day: &Value` - Item of `forecast.forecastday`.
+    /// * `offset: Option<FixedOffset>` - UTC offset of the location, the system timezone is used when it's unknown.
+    fn astro(
+        day: &Value,
+        offset: Option<FixedOffset>,
+    ) -> (Option<DateTime<Local>>, Option<DateTime<Local>>) {
+        let date = day.get("date").and_then(|s| s.as_str());
+        let time = |name: &str| WeatherAPI::astro_time(day.get("astro")?, date?, name, offset);
+        (time("sunrise"), time("sunset"))
+    }
+
+    /// Time of the `astro` object, e.g. `"moonrise": "10:21 PM"`.
+    ///
+    /// * `astro: &Value` - The `astro` object of the forecast day or of the astronomy answer.
+    /// * `date: &str` - Date of the times, `yyyy-mm-dd`.
+    /// * `name: &str` - Name of the time, e.g. `sunrise`.
+    /// * `offset: Option<FixedOffset>` - UTC offset of the location.
+    fn astro_time(
+        astro: &Value,
+        date: &str,
+        name: &str,
+        offset: Option<FixedOffset>,
+    ) -> Option<DateTime<Local>> {
+        let time = astro.get(name)?.as_str()?;
+        // "No sunrise" during the polar day or night, "No moonrise" on some days
+        let dt = NaiveDateTime::parse_from_str(
+            &format!("{} {}", date, time.trim()),
+            "%Y-%m-%d %I:%M %p",
+        )
+        .ok()?;
+        match offset {
+            Some(offset) => offset
+                .from_local_datetime(&dt)
+                .single()
+                .map(|dt| dt.with_timezone(&Local)),
+            None => Local.from_local_datetime(&dt).single(),
+        }
+    }
 
-            diff_a.cmp(&diff_b)
+    /// Parse the answer of the astronomy endpoint, e.g. `"astronomy": {"astro": {"sunrise": "05:12 AM", "moon_phase": "Full Moon"}}`.
+    ///
+    /// * `json: &Map<String, Value>` - Answer of the server.
+    /// * `geo: Geo` - Geo position.
+    /// * `address: String` - Request address.
+    /// * `date: DateTime<Local>` - Requested date, its day is the date of the times.
+    fn astronomy(
+        &self,
+        json: &Map<String, Value>,
+        geo: Geo,
+        address: String,
+        date: DateTime<Local>,
+    ) -> Option<AstroReport> {
+        let astro = json
+            .get("astronomy")
+            .and_then(|a| a.get("astro"))
+            .or_else(|| {
+                Log::info("The WeatherAPI server did not provide astronomy data");
+                None
+            })?;
+        let offset = WeatherAPI::offset(json);
+        let day = date.format("%Y-%m-%d").to_string();
+        let time = |name: &str| WeatherAPI::astro_time(astro, &day, name, offset);
+        let report = AstroReport::new(self.name, address, geo, date, offset);
+        Some(AstroReport {
+            sunrise: time("sunrise"),
+            sunset: time("sunset"),
+            moonrise: time("moonrise"),
+            moonset: time("moonset"),
+            moon_phase: astro
+                .get("moon_phase")
+                .and_then(|s| s.as_str())
+                .map_or(report.moon_phase.clone(), |s| s.to_owned()),
+            ..report
         })
     }
 
+    /// UTC offset of the location from its local time, e.g. `"localtime": "2023-05-11 14:05"`
+    ///
+    /// The `tz_id` is a timezone name, the local time and its epoch give the offset without the timezone database.
+    fn offset(json: &Map<String, Value>) -> Option<FixedOffset> {
+        let location = json.get("location")?;
+        let epoch = location.get("localtime_epoch").and_then(|s| s.as_i64())?;
+        let local = location.get("localtime").and_then(|s| s.as_str())?;
+        let local = NaiveDateTime::parse_from_str(local, "%Y-%m-%d %H:%M").ok()?;
+        // The local time has no seconds, the offsets are multiples of 15 minutes
+        let seconds = (local.and_utc().timestamp() - epoch) as f64;
+        Format::offset((seconds / 900.0).round() * 900.0)
+    }
+
+    /// Getting weather forecast for `date`
+    fn get_item(&self, address: String, date: &Date) -> Option<WeatherAPIItem> {
+        match date {
+            Date::Now => self.get_now(address),
+            Date::Set(dt) => self.get_date(address, dt),
+            Date::Error | Date::Range(..) => None,
+        }
+    }
+
     /// Parse json answer from server
     fn detect(
         &self,
@@ -249,8 +446,8 @@ impl WeatherAPI {
             .map(|s| s as f32);
         let degree = items
             .get("wind_degree")
-            .and_then(|s| s.as_u64())
-            .map(|s| s as u16);
+            .and_then(|s| s.as_f64())
+            .and_then(WindDeg::normalize);
         let dir = WindDeg::get(degree);
         let gust = items
             .get("gust_kph")
@@ -273,22 +470,27 @@ impl WeatherAPI {
             .get("chance_of_rain")
             .and_then(|s| s.as_u64())
             .map(|s| s as u8);
-        let will_it_rain = items.get("will_it_rain").and_then(|s| s.as_u64()).map(|s| {
-            s == 1
-        });
         let chance_of_snow = items
             .get("chance_of_snow")
             .and_then(|s| s.as_u64())
             .map(|s| s as u8);
-        let will_it_snow = items
-            .get("will_it_snow")
-            .and_then(|s| s.as_u64())
-            .map(|s| s == 1);
         let vis = items
             .get("vis_km")
             .and_then(|s| s.as_f64())
             .map(|s| s as f32);
         let uv = items.get("uv").and_then(|s| s.as_f64()).map(|s| s as f32);
+        let air = items.get("air_quality").map(|air| {
+            let float = |name: &str| air.get(name).and_then(|s| s.as_f64()).map(|s| s as f32);
+            AirQuality {
+                pm2_5: float("pm2_5"),
+                pm10: float("pm10"),
+                index: air
+                    .get("us-epa-index")
+                    .and_then(|s| s.as_u64())
+                    .map(|s| s as u32),
+                scale: "US EPA 1 good - 6 hazardous",
+            }
+        });
         let date = items
             .get("time_epoch")
             .and_then(|s| s.as_i64())
@@ -321,42 +523,54 @@ impl WeatherAPI {
             precip,
             humidity,
             cloud,
-            will_it_rain,
             chance_of_rain,
-            will_it_snow,
             chance_of_snow,
             vis,
             uv,
+            air,
+            alerts: Vec::new(),
+            sunrise: None,
+            sunset: None,
+            offset: None,
+            curve: Vec::new(),
+            average: 0,
+            trend: Trend::default(),
         })
     }
 
-    /// Display result
-    #[rustfmt::skip]
-    fn show(&self, item: &WeatherAPIItem, duration: i64, date: &str) {
-        println!("Weather for '{}'. WeatherAPI server. Request time {} ms.", date, duration);
-        println!("Request address: {}.", item.address);
-        println!("Found address: {} ({},{}).", item.geo.address, item.geo.lat, item.geo.lon);
-        println!("Forecast date on the server: {}", item.date.format("%Y-%m-%d %H:%M:%S (%:z)"));
-        println!("{}", "-".repeat(40));
-        println!("Weather condition text       : {}", item.condition.as_ref().map_or("None".to_owned(), |s| s.to_owned()));
-        println!("Temperature                  : {}", item.temp.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Feels like temperature       : {}", item.feelslike.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Windchill temperature        : {}", item.windchill.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Heat index                   : {}", item.heatindex.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Dew point                    : {}", item.dewpoint.map_or("None".to_owned(), |s| format!("{:#.1} °C", s)));
-        println!("Wind speed                   : {}", item.wind.map_or("None".to_owned(), |s| format!("{:#.1} km/hour", s)));
-        println!("Wind direction in degrees    : {:?} ({})", item.dir, item.degree.map_or("None".to_owned(), |s| s.to_string() + "°"));
-        println!("Wind gust                    : {}", item.gust.map_or("None".to_owned(), |s| format!("{:#.1} km/hour", s)));
-        println!("Atmospheric pressure         : {}", item.pressure.map_or("None".to_owned(), |s| format!("{:#.1} mbar", s)));
-        println!("Precipitation amount         : {}", item.precip.map_or("None".to_owned(), |s| format!("{:#.1} mm", s)));
-        println!("Humidity                     : {}", item.humidity.map_or("None".to_owned(), |s| s.to_string() + " %"));
-        println!("Cloud cover                  : {}", item.cloud.map_or("None".to_owned(), |s| s.to_string() + " %"));
-        println!("Will it will rain or not     : {}", item.will_it_rain.map_or("None".to_owned(), |s| format!("{}", s)));
-        println!("Chance of rain               : {}", item.chance_of_rain.map_or("None".to_owned(), |s| s.to_string() + " %"));
-        println!("Will it will snow or not     : {}", item.will_it_snow.map_or("None".to_owned(), |s| format!("{}", s)));
-        println!("Chance of snow               : {}", item.chance_of_snow.map_or("None".to_owned(), |s| s.to_string() + " %"));
-        println!("Visibility                   : {}", item.vis.map_or("None".to_owned(), |s| format!("{:#.1} km", s)));
-        println!("UV Index                     : {}", item.uv.map_or("None".to_owned(), |s| format!("{:#.1}", s)));
+    /// Convert result to the normalized weather report
+    fn report(&self, item: WeatherAPIItem) -> WeatherReport {
+        WeatherReport {
+            condition: item.condition,
+            temperature: item.temp,
+            feels_like: item.feelslike,
+            humidity: item.humidity.map(|s| s as u32),
+            // mbar is hPa
+            pressure: item.pressure,
+            wind_speed: item.wind,
+            wind_deg: item.degree,
+            wind_dir: item.dir,
+            precipitation: item.precip,
+            alerts: item.alerts,
+            wind_chill: item.windchill,
+            heat_index: item.heatindex,
+            dew_point: item.dewpoint,
+            wind_gust: item.gust,
+            precipitation_chance: item
+                .chance_of_rain
+                .max(item.chance_of_snow)
+                .map(|s| s as u32),
+            cloud_cover: item.cloud.map(|s| s as u32),
+            visibility: item.vis,
+            uv_index: item.uv,
+            sunrise: item.sunrise,
+            sunset: item.sunset,
+            air: item.air,
+            curve: item.curve,
+            average: item.average,
+            trend: item.trend,
+            ..WeatherReport::new(self.name, item.address, item.geo, item.date, item.offset)
+        }
     }
 }
 
@@ -369,7 +583,7 @@ impl Provider for WeatherAPI {
     }
 
     fn deserialize(&mut self, data: &str) -> bool {
-        let mut input = data.split(':');
+        let mut input = data.splitn(2, ':');
         match input.next() {
             Some(name) => {
                 if name != self.name {
@@ -377,14 +591,14 @@ impl Provider for WeatherAPI {
                 }
             }
             None => {
-                println!("The data file structure is damaged. The data file will be deleted.");
+                Log::info("The data file structure is damaged. The data file will be deleted.");
                 return false;
             }
         };
         let key = match input.next() {
             Some(key) => key.to_owned(),
             None => {
-                println!("The data file structure is damaged. The data file will be deleted.");
+                Log::info("The data file structure is damaged. The data file will be deleted.");
                 return false;
             }
         };
@@ -396,50 +610,35 @@ impl Provider for WeatherAPI {
         true
     }
 
-    fn get_weather(&self, address: String, date: Date) {
-        match date {
-            Date::Now => {
-                let start = Local::now();
-                let now = match self.get_now(address) {
-                    Some(now) => now,
-                    None => {
-                        println!("It is not possible to determine the date of the weather forecast sent by the provider");
-                        return;
-                    }
-                };
-                let duration = Local::now() - start;
-                self.show(&now, duration.num_milliseconds(), "now");
-            }
-            Date::Set(dt) => {
-                let start = Local::now();
-                let now = match self.get_date(address, &dt) {
-                    Some(now) => now,
-                    None => {
-                        println!("It is not possible to determine the date of the weather forecast sent by the provider");
-                        return;
-                    }
-                };
-                let duration = Local::now() - start;
-                self.show(
-                    &now,
-                    duration.num_milliseconds(),
-                    &dt.format("%Y-%m-%d %H:%M:%S (%:z)").to_string(),
-                );
-            }
-            _ => {}
-        }
-    }
-
     fn name(&self) -> &'static str {
         self.name
     }
 
+    fn capabilities(&self) -> &'static [Capability] {
+        &[
+            Capability::Temperature,
+            Capability::FeelsLike,
+            Capability::Uv,
+            Capability::Snow,
+            Capability::AirQuality,
+            Capability::Alerts,
+            Capability::Hourly,
+            Capability::DateRange,
+            Capability::Coordinates,
+        ]
+    }
+
+    fn forecast_days(&self) -> Option<i64> {
+        // The forecast with `dt` is limited to 14 days, 3 days on the free plan
+        Some(14)
+    }
+
     fn configure(&mut self) {
         println!("Configure credentials for {}: \n", self.name);
         match &self.key {
             Some(key) => print!(
                 "Please enter the API key to access the weather forecast. Current key={}: ",
-                key
+                Format::secret(key)
             ),
             None => print!("Please enter the API key to access the weather forecast: "),
         }
@@ -464,6 +663,80 @@ impl Provider for WeatherAPI {
             self.key = Some(key);
         }
     }
+
+    fn fetch_range(
+        &self,
+        address: String,
+        start: &DateTime<Local>,
+        end: &DateTime<Local>,
+    ) -> Option<Vec<WeatherReport>> {
+        // Forecast is available for up to 14 days from today
+        let days = (end.date_naive() - Local::now().date_naive())
+            .num_days()
+            .clamp(0, 13)
+            + 1;
+        let list = self.get_list(address, &format!("days={}", days))?;
+        let reports = list.into_iter().map(|item| self.report(item)).collect();
+        Some(WeatherReport::daily(reports, start, end))
+    }
+
+    fn key_from_env(&mut self) {
+        if let Ok(key) = env::var("WEATHER_WEATHERAPI_KEY") {
+            let key = key.trim();
+            if !key.is_empty() {
+                self.env_key = Some(key.to_owned());
+            }
+        }
+    }
+
+    fn credentials(&self) -> Vec<(&'static str, String)> {
+        key_rows(self.key.as_deref(), self.env_key.as_deref())
+    }
+
+    fn has_credentials(&self) -> bool {
+        self.key.is_some() || self.env_key.is_some()
+    }
+
+    fn reset(&mut self) {
+        self.key = None;
+    }
+
+    fn check(&self, geo: &Geo) -> Option<Result<(), HttpError>> {
+        let key = self.env_key.as_ref().or(self.key.as_ref())?;
+        let url = WeatherAPI::url(CURRENT_URL, geo, key, None);
+        Some(Budget::get_json(self.name, self.client.as_ref(), &url, &[]).map(|_| ()))
+    }
+
+    fn supports_aqi(&self) -> bool {
+        true
+    }
+
+    fn supports_graph(&self) -> bool {
+        true
+    }
+
+    fn supports_astro(&self) -> bool {
+        true
+    }
+
+    fn fetch_astro(&self, address: String, date: Date) -> Option<AstroReport> {
+        let date = match date {
+            Date::Now => Local::now(),
+            Date::Set(dt) => dt,
+            Date::Error | Date::Range(..) => return None,
+        };
+        // The dedicated endpoint answers without the hourly forecast
+        let (json, geo) = self.get_json(
+            "https://api.weatherapi.com/v1/astronomy.json",
+            &address,
+            Some(&format!("dt={}", date.format("%Y-%m-%d"))),
+        )?;
+        self.astronomy(&json, geo, address, date)
+    }
+
+    fn fetch(&self, address: String, date: Date) -> Option<WeatherReport> {
+        self.get_item(address, &date).map(|item| self.report(item))
+    }
 }
 
 impl Default for WeatherAPI {
@@ -471,3 +744,128 @@ impl Default for WeatherAPI {
         WeatherAPI::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset, Local, TimeZone};
+    use serde_json::{Map, Value};
+
+    use super::WeatherAPI;
+    use crate::{geo::Geo, work::Provider};
+
+    #[test]
+    fn test_alerts() {
+        let json = r#"{"alerts": {"alert": [{
+            "headline": "Flood Warning issued May 11 at 10:00AM",
+            "severity": "Moderate",
+            "areas": "Kyiv",
+            "event": "Flood Warning",
+            "effective": "2023-05-11T10:00:00+03:00",
+            "expires": "2023-05-12T06:00:00+03:00",
+            "desc": "..."
+        }, {"headline": ""}]}}"#;
+        let json: Map<String, Value> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            WeatherAPI::alerts(&json),
+            vec!["Flood Warning (Moderate), 2023-05-11T10:00:00+03:00 - 2023-05-12T06:00:00+03:00: Flood Warning issued May 11 at 10:00AM".to_owned()]
+        );
+        let json: Map<String, Value> =
+            serde_json::from_str(r#"{"alerts": {"alert": []}}"#).unwrap();
+        assert!(WeatherAPI::alerts(&json).is_empty());
+    }
+
+    #[test]
+    fn test_astro() {
+        let day = serde_json::json!({
+            "date": "2023-05-11",
+            "astro": {"sunrise": "05:12 AM", "sunset": "08:30 PM"}
+        });
+        let offset = FixedOffset::east_opt(3 * 3600);
+        let (sunrise, sunset) = WeatherAPI::astro(&day, offset);
+        assert_eq!(
+            sunrise.map(|dt| dt.with_timezone(&offset.unwrap()).to_rfc3339()),
+            Some("2023-05-11T05:12:00+03:00".to_owned())
+        );
+        assert_eq!(
+            sunset.map(|dt| dt.with_timezone(&offset.unwrap()).to_rfc3339()),
+            Some("2023-05-11T20:30:00+03:00".to_owned())
+        );
+        let day = serde_json::json!({
+            "date": "2023-06-21",
+            "astro": {"sunrise": "No sunrise", "sunset": "No sunset"}
+        });
+        assert_eq!(WeatherAPI::astro(&day, offset), (None, None));
+    }
+
+    #[test]
+    fn test_astronomy() {
+        let json = r#"{
+            "location": {"localtime_epoch": 1683806730, "localtime": "2023-05-11 15:05"},
+            "astronomy": {"astro": {
+                "sunrise": "05:12 AM", "sunset": "08:30 PM",
+                "moonrise": "No moonrise", "moonset": "11:04 AM", "moon_phase": "Last Quarter"
+            }}
+        }"#;
+        let json: Map<String, Value> = serde_json::from_str(json).unwrap();
+        let geo = Geo {
+            lat: "50.45".to_owned(),
+            lon: "30.52".to_owned(),
+            address: "Kyiv, Ukraine".to_owned(),
+            importance: None,
+        };
+        let offset = FixedOffset::east_opt(3 * 3600).unwrap();
+        let date = offset
+            .with_ymd_and_hms(2023, 5, 11, 12, 0, 0)
+            .unwrap()
+            .with_timezone(&Local);
+        let time =
+            |dt: Option<DateTime<Local>>| dt.map(|dt| dt.with_timezone(&offset).to_rfc3339());
+        let report = WeatherAPI::new()
+            .astronomy(&json, geo, "Kyiv".to_owned(), date)
+            .unwrap();
+        assert_eq!(report.offset, Some(offset));
+        assert_eq!(
+            time(report.sunrise),
+            Some("2023-05-11T05:12:00+03:00".to_owned())
+        );
+        assert_eq!(
+            time(report.moonset),
+            Some("2023-05-11T11:04:00+03:00".to_owned())
+        );
+        assert_eq!(report.moonrise, None);
+        assert_eq!(report.moon_phase, "Last Quarter");
+    }
+
+    #[test]
+    fn test_offset() {
+        // 12:05:30 UTC is 21:05 in Tokyo
+        let json = r#"{"location": {
+            "tz_id": "Asia/Tokyo", "localtime_epoch": 1683806730, "localtime": "2023-05-11 21:05"
+        }}"#;
+        let json: Map<String, Value> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            WeatherAPI::offset(&json).map(|offset| offset.to_string()),
+            Some("+09:00".to_owned())
+        );
+        let json: Map<String, Value> = serde_json::from_str(r#"{"location": {}}"#).unwrap();
+        assert_eq!(WeatherAPI::offset(&json), None);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut provider = WeatherAPI::new();
+        assert_eq!(provider.serialize(), "WeatherAPI:");
+        assert!(provider.deserialize("WeatherAPI:abc123"));
+        assert_eq!(provider.key.as_deref(), Some("abc123"));
+        assert_eq!(provider.serialize(), "WeatherAPI:abc123");
+        // The colon is a part of the key
+        assert!(provider.deserialize("WeatherAPI:abc:123"));
+        assert_eq!(provider.serialize(), "WeatherAPI:abc:123");
+        assert!(provider.deserialize("WeatherAPI:"));
+        assert_eq!(provider.key, None);
+        assert_eq!(provider.serialize(), "WeatherAPI:");
+        assert!(!provider.deserialize("WeatherAPI"));
+        assert!(!provider.deserialize("OpenWeather:abc123"));
+        assert!(!provider.deserialize(""));
+    }
+}
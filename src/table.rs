@@ -0,0 +1,268 @@
+//! The module responsible for the layout of the field/value lists of the weather providers.
+//!
+
+use crate::{init::Options, out::Out};
+
+/// Width of the field names of the plain list.
+const NAME_WIDTH: usize = 29;
+
+/// Keys of the `--fields` flag and the field names of the providers they select.
+const FIELDS: [(&str, &[&str]); 14] = [
+    (
+        "condition",
+        &[
+            "Description of weather",
+            "Group of weather parameters",
+            "Summary of the day",
+            "Weather condition",
+            "Weather condition text",
+            "Weather conditions",
+            "Weather description",
+            "Weather symbol",
+        ],
+    ),
+    (
+        "temp",
+        &[
+            "Temperature",
+            "Temperature min",
+            "Temperature max",
+            "Minimum temperature",
+            "Maximum temperature",
+            "Windchill temperature",
+            "Heat index",
+        ],
+    ),
+    (
+        "feels",
+        &[
+            "Feels like temperature",
+            "Human perception temperature",
+            "Real feel temperature",
+        ],
+    ),
+    ("dew", &["Dew point", "Dew point temperature"]),
+    ("humidity", &["Humidity"]),
+    ("pressure", &["Atmospheric pressure"]),
+    (
+        "wind",
+        &[
+            "Wind speed",
+            "Wind gust",
+            "Wind direction and degrees",
+            "Wind direction in degrees",
+        ],
+    ),
+    (
+        "precip",
+        &[
+            "Chance of precipitation",
+            "Chance of rain",
+            "Chance of snow",
+            "Precipitation amount",
+            "Precipitation depth",
+            "Precipitation intensity",
+            "Presence of precipitation",
+            "Probability of precipitation",
+            "Rain probability",
+            "Rain volume",
+            "Rain volume (day)",
+            "Rain volume (last 1 hour)",
+            "Rain volume (last 3 hour)",
+            "Snow amount",
+            "Snow depth",
+            "Snow probability",
+            "Snow volume",
+            "Snow volume (day)",
+            "Snow volume (last 1 hour)",
+            "Snow volume (last 3 hour)",
+            "The type of precipitation",
+            "Will it will rain or not",
+            "Will it will snow or not",
+        ],
+    ),
+    ("cloud", &["Cloud cover", "Cloudiness"]),
+    ("visibility", &["Visibility"]),
+    ("uv", &["UV Index", "UV index"]),
+    ("sun", &["Sunrise time", "Sunset time", "Day length"]),
+    ("moon", &["Moon phase"]),
+    ("air", &["PM2.5", "PM10", "Air quality index"]),
+];
+
+/// Keys of the top-priority fields displayed by the `--brief` flag.
+const BRIEF: [&str; 5] = ["condition", "temp", "feels", "wind", "precip"];
+
+/// Unit struct for the field/value layout
+pub struct Table;
+
+impl Table {
+    /// Keys of the `--fields` flag, e.g. `temp`.
+    pub fn fields() -> Vec<&'static str> {
+        FIELDS.iter().map(|(key, _)| *key).collect()
+    }
+
+    /// Keys of the fields displayed by the `--brief` flag.
+    pub fn brief() -> Vec<String> {
+        BRIEF.iter().map(|key| key.to_string()).collect()
+    }
+
+    /// Finds the key of the `--fields` flag that selects the field.
+    ///
+    /// * `name: &str` - Field name of the provider, e.g. `Wind gust`.
+    ///
+    /// Return
+    ///
+    /// `Option<&str>` - The key, e.g. `wind`.
+    ///   * `Option::None` - The field is unknown, it's hidden by `--fields`.
+    pub fn field(name: &str) -> Option<&'static str> {
+        FIELDS
+            .iter()
+            .find(|(_, names)| names.contains(&name))
+            .map(|(key, _)| *key)
+    }
+
+    /// Leaves only the fields selected by the `--fields` flag.
+    ///
+    /// * `rows: &[(&str, String)]` - Field names and values.
+    /// * `fields: &[String]` - Selected keys.
+    pub fn filter<'a>(rows: &[(&'a str, String)], fields: &[String]) -> Vec<(&'a str, String)> {
+        rows.iter()
+            .filter(|(name, _)| {
+                Table::field(name).is_some_and(|key| fields.iter().any(|field| field == key))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Displays the list of fields.
+    ///
+    /// The plain list is separated by a dashed line, the `--table` flag draws a bordered table.
+    /// The `--fields` flag hides the fields that were not selected, a section without fields is skipped.
+    ///
+    /// * `title: Option<&str>` - Title of the section.
+    /// * `rows: &[(&str, String)]` - Field names and values.
+    pub fn show(title: Option<&str>, rows: &[(&str, String)]) {
+        let options = Options::get();
+        let filtered;
+        let rows = match &options.fields {
+            Some(fields) => {
+                filtered = Table::filter(rows, fields);
+                if filtered.is_empty() {
+                    return;
+                }
+                &filtered[..]
+            }
+            None => rows,
+        };
+        if options.table {
+            if let Some(title) = title {
+                Out::line(title);
+            }
+            Out::line(Table::render(rows).trim_end_matches('\n'));
+            return;
+        }
+        if let Some(title) = title {
+            Out::line(&"-".repeat(40));
+            Out::line(title);
+        }
+        Out::line(&"-".repeat(40));
+        for (name, value) in rows {
+            Out::line(&format!("{:<width$}: {}", name, value, width = NAME_WIDTH));
+        }
+    }
+
+    /// Draws the bordered table with aligned columns using the box-drawing characters.
+    ///
+    /// * `rows: &[(&str, String)]` - Field names and values.
+    ///
+    /// Return
+    ///
+    /// `String` - The table, each line ends with a line feed.
+    pub fn render(rows: &[(&str, String)]) -> String {
+        let name = rows
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let value = rows
+            .iter()
+            .map(|(_, value)| value.chars().count())
+            .max()
+            .unwrap_or(0);
+        let border = |left: char, middle: char, right: char| {
+            format!(
+                "{}{}{}{}{}\n",
+                left,
+                "─".repeat(name + 2),
+                middle,
+                "─".repeat(value + 2),
+                right
+            )
+        };
+        let mut table = border('┌', '┬', '┐');
+        for (n, v) in rows {
+            table.push_str(&format!(
+                "│ {:<name$} │ {:<value$} │\n",
+                n,
+                v,
+                name = name,
+                value = value
+            ));
+        }
+        table.push_str(&border('└', '┴', '┘'));
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Table;
+
+    #[test]
+    fn test_render() {
+        let rows = [
+            ("Temperature", "18.5°C".to_owned()),
+            ("Humidity", "81 %".to_owned()),
+        ];
+        assert_eq!(
+            Table::render(&rows),
+            "┌─────────────┬────────┐\n\
+             │ Temperature │ 18.5°C │\n\
+             │ Humidity    │ 81 %   │\n\
+             └─────────────┴────────┘\n"
+        );
+        assert_eq!(Table::render(&[]), "┌──┬──┐\n└──┴──┘\n");
+    }
+
+    #[test]
+    fn test_field() {
+        assert_eq!(Table::field("Temperature"), Some("temp"));
+        assert_eq!(Table::field("Real feel temperature"), Some("feels"));
+        assert_eq!(Table::field("Wind direction and degrees"), Some("wind"));
+        assert_eq!(Table::field("Rain volume (day)"), Some("precip"));
+        assert_eq!(Table::field("PM2.5"), Some("air"));
+        assert_eq!(Table::field("Latitude"), None);
+        assert!(Table::fields().contains(&"humidity"));
+        assert!(Table::brief()
+            .iter()
+            .all(|key| Table::fields().contains(&key.as_str())));
+    }
+
+    #[test]
+    fn test_filter() {
+        let rows = [
+            ("Temperature", "18.5°C".to_owned()),
+            ("Humidity", "81 %".to_owned()),
+            ("Wind gust", "5 km/h".to_owned()),
+        ];
+        let fields = ["wind".to_owned(), "temp".to_owned()];
+        assert_eq!(
+            Table::filter(&rows, &fields),
+            vec![
+                ("Temperature", "18.5°C".to_owned()),
+                ("Wind gust", "5 km/h".to_owned())
+            ]
+        );
+        assert!(Table::filter(&rows, &["moon".to_owned()]).is_empty());
+    }
+}
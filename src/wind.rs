@@ -1,8 +1,12 @@
 //! Module responsible for detect wind direction.
 //!
 
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+
 /// Wind Direction and Degrees
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum WindDeg {
     None,
     Unknown,
@@ -25,14 +29,36 @@ pub enum WindDeg {
 }
 
 impl WindDeg {
+    /// Get wind direction from degrees
+    ///
+    /// * `degree: u16` - Wind direction in degrees.
+    pub fn from_degrees(degree: u16) -> WindDeg {
+        WindDeg::get(Some(degree))
+    }
+
+    /// Normalize the wind direction sent by the server, e.g. `123.5`, `-5` or `361`.
+    ///
+    /// * `degree: f64` - Wind direction in degrees, fractional, negative or wrapped.
+    ///
+    /// Return
+    ///
+    /// `Option<u16>` - Rounded degrees from 0 to 359, `None` if the value is not a number.
+    pub fn normalize(degree: f64) -> Option<u16> {
+        if !degree.is_finite() {
+            return None;
+        }
+        Some((degree.round() as i64).rem_euclid(360) as u16)
+    }
+
     /// Get wind direction from degrees
     pub fn get(degree: Option<u16>) -> WindDeg {
         let degree = match degree {
             Some(degree) => degree,
             None => return WindDeg::None,
         };
-        match degree {
-            0..=11 | 349..=360 => WindDeg::North,
+        // Some providers send wrapped values (e.g. 370)
+        match degree % 360 {
+            0..=11 | 349..=359 => WindDeg::North,
             12..=33 => WindDeg::NorthNorthEast,
             34..=56 => WindDeg::NorthEast,
             57..=78 => WindDeg::EastNorthEast,
@@ -51,4 +77,143 @@ impl WindDeg {
             _ => WindDeg::Unknown,
         }
     }
+
+    /// Short compass name of the wind direction, e.g. "NNE".
+    pub fn abbrev(&self) -> &'static str {
+        match self {
+            WindDeg::None => "",
+            WindDeg::Unknown => "?",
+            WindDeg::North => "N",
+            WindDeg::NorthNorthEast => "NNE",
+            WindDeg::NorthEast => "NE",
+            WindDeg::EastNorthEast => "ENE",
+            WindDeg::East => "E",
+            WindDeg::EastSouthEast => "ESE",
+            WindDeg::SouthEast => "SE",
+            WindDeg::SouthSouthEast => "SSE",
+            WindDeg::South => "S",
+            WindDeg::SouthSouthWest => "SSW",
+            WindDeg::SouthWest => "SW",
+            WindDeg::WestSouthWest => "WSW",
+            WindDeg::West => "W",
+            WindDeg::WestNorthWest => "WNW",
+            WindDeg::NorthWest => "NW",
+            WindDeg::NorthNorthWest => "NNW",
+        }
+    }
+
+    /// Arrow of the wind direction, e.g. '↗'.
+    pub fn arrow(&self) -> char {
+        match self {
+            WindDeg::None => ' ',
+            WindDeg::Unknown => '?',
+            WindDeg::North | WindDeg::NorthNorthWest => '↑',
+            WindDeg::NorthNorthEast | WindDeg::NorthEast => '↗',
+            WindDeg::EastNorthEast | WindDeg::East => '→',
+            WindDeg::EastSouthEast | WindDeg::SouthEast => '↘',
+            WindDeg::SouthSouthEast | WindDeg::South => '↓',
+            WindDeg::SouthSouthWest | WindDeg::SouthWest => '↙',
+            WindDeg::WestSouthWest | WindDeg::West => '←',
+            WindDeg::WestNorthWest | WindDeg::NorthWest => '↖',
+        }
+    }
+}
+
+/// Human readable name of the wind direction, e.g. `South-southeast`.
+impl fmt::Display for WindDeg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            WindDeg::None => "None",
+            WindDeg::Unknown => "Unknown",
+            WindDeg::North => "North",
+            WindDeg::NorthNorthEast => "North-northeast",
+            WindDeg::NorthEast => "Northeast",
+            WindDeg::EastNorthEast => "East-northeast",
+            WindDeg::East => "East",
+            WindDeg::EastSouthEast => "East-southeast",
+            WindDeg::SouthEast => "Southeast",
+            WindDeg::SouthSouthEast => "South-southeast",
+            WindDeg::South => "South",
+            WindDeg::SouthSouthWest => "South-southwest",
+            WindDeg::SouthWest => "Southwest",
+            WindDeg::WestSouthWest => "West-southwest",
+            WindDeg::West => "West",
+            WindDeg::WestNorthWest => "West-northwest",
+            WindDeg::NorthWest => "Northwest",
+            WindDeg::NorthNorthWest => "North-northwest",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The json value is the abbreviation, e.g. `"SSE"`, or `null` if the direction is not provided.
+impl Serialize for WindDeg {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            WindDeg::None => serializer.serialize_none(),
+            dir => serializer.serialize_str(dir.abbrev()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WindDeg;
+
+    #[test]
+    fn test_from_degrees() {
+        assert_eq!(WindDeg::from_degrees(0), WindDeg::North);
+        assert_eq!(WindDeg::from_degrees(11), WindDeg::North);
+        assert_eq!(WindDeg::from_degrees(12), WindDeg::NorthNorthEast);
+        assert_eq!(WindDeg::from_degrees(348), WindDeg::NorthNorthWest);
+        assert_eq!(WindDeg::from_degrees(349), WindDeg::North);
+        assert_eq!(WindDeg::from_degrees(359), WindDeg::North);
+        assert_eq!(WindDeg::from_degrees(360), WindDeg::North);
+        assert_eq!(WindDeg::from_degrees(361), WindDeg::North);
+        assert_eq!(WindDeg::from_degrees(720), WindDeg::North);
+        assert_eq!(WindDeg::from_degrees(370), WindDeg::North);
+        assert_eq!(WindDeg::from_degrees(405), WindDeg::NorthEast);
+        assert_eq!(WindDeg::from_degrees(540), WindDeg::South);
+        assert_eq!(WindDeg::from_degrees(708), WindDeg::NorthNorthWest);
+        // 65535 is 15 after wrapping
+        assert_eq!(WindDeg::from_degrees(u16::MAX), WindDeg::NorthNorthEast);
+        assert_eq!(WindDeg::get(None), WindDeg::None);
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(WindDeg::normalize(123.5), Some(124));
+        assert_eq!(WindDeg::normalize(123.4), Some(123));
+        assert_eq!(WindDeg::normalize(-5.0), Some(355));
+        assert_eq!(WindDeg::normalize(361.0), Some(1));
+        assert_eq!(WindDeg::normalize(359.6), Some(0));
+        assert_eq!(WindDeg::normalize(f64::NAN), None);
+        assert_eq!(WindDeg::get(WindDeg::normalize(123.5)), WindDeg::SouthEast);
+        assert_eq!(WindDeg::get(WindDeg::normalize(-5.0)), WindDeg::North);
+        assert_eq!(WindDeg::get(WindDeg::normalize(361.0)), WindDeg::North);
+    }
+
+    #[test]
+    fn test_display_serialize() {
+        assert_eq!(WindDeg::SouthSouthEast.to_string(), "South-southeast");
+        assert_eq!(WindDeg::NorthEast.to_string(), "Northeast");
+        assert_eq!(WindDeg::None.to_string(), "None");
+        assert_eq!(
+            serde_json::to_string(&WindDeg::SouthSouthEast).unwrap(),
+            "\"SSE\""
+        );
+        assert_eq!(serde_json::to_string(&WindDeg::None).unwrap(), "null");
+    }
+
+    #[test]
+    fn test_abbrev_arrow() {
+        assert_eq!(WindDeg::from_degrees(22).abbrev(), "NNE");
+        assert_eq!(WindDeg::from_degrees(22).arrow(), '↗');
+        assert_eq!(WindDeg::from_degrees(180).abbrev(), "S");
+        assert_eq!(WindDeg::from_degrees(180).arrow(), '↓');
+        assert_eq!(WindDeg::from_degrees(270).abbrev(), "W");
+        assert_eq!(WindDeg::from_degrees(270).arrow(), '←');
+        assert_eq!(WindDeg::None.abbrev(), "");
+        assert_eq!(WindDeg::Unknown.arrow(), '?');
+    }
 }
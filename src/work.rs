@@ -1,41 +1,689 @@
 //! Module responsible for program logic.
 //!
 use std::{
-    fs::File,
-    io::{stdin, stdout, BufRead, BufReader, ErrorKind, Write},
+    error::Error,
+    fmt,
+    fs::{self, File},
+    io::{stdin, stdout, BufRead, BufReader, ErrorKind, IsTerminal, Write},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{self, Instant},
 };
 
-use crate::init::Date;
+use chrono::{DateTime, Duration, FixedOffset, Local};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{
+    astro::{Astro, AstroReport},
+    budget::Budget,
+    comfort::Comfort,
+    format::Format,
+    geo::Geo,
+    geoip::GeoIp,
+    graph::Graph,
+    help::Help,
+    http::{Http, HttpError},
+    init::{Command, Date, Init, Options, Output, Settings, DATE_WINDOW},
+    interrupt::Interrupt,
+    log::Log,
+    out::Out,
+    provider::{no_forecast, show_age, show_alerts, show_average, show_units, AirQuality, Trend},
+    table::Table,
+    wind::WindDeg,
+};
+
+/// Latitude and longitude of the `weather doctor` requests, Kyiv.
+const DOCTOR_PLACE: (&str, &str) = ("50.4501", "30.5234");
+
+/// True - the header row of `--format=csv` is already printed.
+static CSV_HEADER: AtomicBool = AtomicBool::new(false);
 
 /// Interaction with weather forecast provider.
 ///
 /// * `fn serialize(&self) -> String` - Serialize provider credentials.
 /// * `fn deserialize(&mut self, data: &str) -> bool` - Deserialize provider credentials.
-/// * `fn get_weather(&self, address: String, date: Date)` - Displays weather for the provided address.
+/// * `fn get_weather(&self, address: String, date: Date) -> bool` - Displays the report of `fetch` for the provided address.
 /// * `fn name(&self) -> &'static str` - Get provider name..
 /// * `fn configure(&mut self)` - Configures credentials for the selected provider.
+/// * `fn fetch(&self, address: String, date: Date) -> Option<WeatherReport>` - Get a normalized weather report.
+/// * `fn fetch_range(&self, address: String, start: &DateTime<Local>, end: &DateTime<Local>) -> Option<Vec<WeatherReport>>` -
+///   Get normalized weather reports for each day of the range.
+/// * `fn key_from_env(&mut self)` - Load provider credentials from environment variables.
+/// * `fn supports_aqi(&self) -> bool` - Checking whether the provider displays the air quality.
+/// * `fn credentials(&self) -> Vec<(&'static str, String)>` - Stored credentials with the masked secrets.
+/// * `fn date_window(&self) -> (i64, i64)` - Number of days before and after today the provider can answer for.
+/// * `fn has_credentials(&self) -> bool` - Checking whether the credentials of the provider are set.
+/// * `fn reset(&mut self)` - Removes the saved credentials of the provider.
+/// * `fn check(&self, geo: &Geo) -> Option<Result<(), HttpError>>` - Sends the minimal request for `weather doctor`.
+/// * `fn supports_graph(&self) -> bool` - Checking whether the provider displays the temperature curve.
+/// * `fn forecast_days(&self) -> Option<i64>` - The last day after today the provider has the forecast for.
+/// * `fn supports_date(&self, date: &Date) -> bool` - Checking whether the provider has the forecast for the date.
+/// * `fn capabilities(&self) -> &'static [Capability]` - What the provider supplies, for `weather capabilities`.
+/// * `fn supports_astro(&self) -> bool` - Checking whether the provider supplies the sun times for `--astro`.
+/// * `fn fetch_astro(&self, address: String, date: Date) -> Option<AstroReport>` - Get the sun and moon times.
 pub trait Provider {
     /// Serialize provider credentials.
     fn serialize(&self) -> String;
     /// Deserialize provider credentials.
     fn deserialize(&mut self, data: &str) -> bool;
-    /// Displays weather for the provided address.
-    fn get_weather(&self, address: String, date: Date);
+    /// Displays weather for the provided address, false if the forecast is not received.
+    ///
+    /// The report of `fetch` is displayed, with the temperature curve and the air quality
+    /// if the flags are set and the provider supports them.
+    fn get_weather(&self, address: String, date: Date) -> bool {
+        let start = Local::now();
+        let report = match self.fetch(address, date.clone()) {
+            Some(report) => report,
+            None => {
+                no_forecast();
+                return false;
+            }
+        };
+        let duration = Local::now() - start;
+        report.show(duration.num_milliseconds(), &date.to_string());
+        let options = Options::get();
+        if options.graph && self.supports_graph() {
+            Graph::show(&report.curve, report.offset, &Format::temp);
+        }
+        if options.aqi && self.supports_aqi() {
+            AirQuality::show(report.air.as_ref());
+        }
+        true
+    }
     /// Get provider name.
     fn name(&self) -> &'static str;
     /// Configures credentials for the selected provider
     fn configure(&mut self);
+    /// Get a normalized weather report for the provided address.
+    fn fetch(&self, address: String, date: Date) -> Option<WeatherReport>;
+    /// Get normalized weather reports for each day of the range.
+    /// Days outside of the forecast returned by the provider are skipped.
+    fn fetch_range(
+        &self,
+        address: String,
+        start: &DateTime<Local>,
+        end: &DateTime<Local>,
+    ) -> Option<Vec<WeatherReport>>;
+    /// Load provider credentials from environment variables.
+    /// They take precedence over the key file and are never saved to it.
+    fn key_from_env(&mut self);
+    /// Checking whether the provider displays the air quality for the `--aqi` flag.
+    fn supports_aqi(&self) -> bool {
+        false
+    }
+    /// Stored credentials for `configure <provider> --show`, the secrets are masked, e.g. `("API key", "***cdef")`.
+    /// Empty for the providers that don't require credentials.
+    fn credentials(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+    /// Number of days before and after today the provider can answer for, the other dates are rejected
+    /// before any request. The providers with the historical data widen it.
+    fn date_window(&self) -> (i64, i64) {
+        (DATE_WINDOW, DATE_WINDOW)
+    }
+    /// Checking whether the credentials of the provider are set, e.g. for `--fallback`.
+    /// The providers that don't require credentials are always ready.
+    fn has_credentials(&self) -> bool {
+        true
+    }
+    /// Removes the saved credentials of the provider for `configure <provider> --reset`,
+    /// like the empty input of `configure`. The credentials of the environment variables are kept.
+    fn reset(&mut self) {}
+    /// Sends the minimal request with the credentials for the current weather of the place, for `weather doctor`.
+    /// `None` - the credentials are not set, nothing is sent.
+    fn check(&self, geo: &Geo) -> Option<Result<(), HttpError>>;
+    /// Checking whether the provider displays the temperature curve of the day for the `--graph` flag.
+    fn supports_graph(&self) -> bool {
+        false
+    }
+    /// The last day after today the provider has the forecast for, e.g. 4 for the 5-day forecast.
+    /// `None` - any date of `date_window` can be answered.
+    fn forecast_days(&self) -> Option<i64> {
+        None
+    }
+    /// Checking whether the provider has the forecast for the date, so the unsupported dates
+    /// are explained before any request.
+    fn supports_date(&self, date: &Date) -> bool {
+        match self.forecast_days() {
+            Some(days) => date.in_horizon(Local::now().date_naive(), days),
+            None => true,
+        }
+    }
+    /// What the provider supplies, for `weather capabilities`.
+    fn capabilities(&self) -> &'static [Capability];
+    /// Checking whether the provider supplies the sunrise and sunset for the `--astro` flag.
+    fn supports_astro(&self) -> bool {
+        false
+    }
+    /// Get the sun and moon times of the place for the date, `None` if they are not supplied.
+    fn fetch_astro(&self, _address: String, _date: Date) -> Option<AstroReport> {
+        None
+    }
+}
+
+/// What a provider supplies, the columns of `weather capabilities`.
+///
+/// * `Temperature` - Temperature.
+/// * `FeelsLike` - Temperature accounting for the human perception of weather.
+/// * `Uv` - UV index.
+/// * `Snow` - Snow amount or probability.
+/// * `AirQuality` - Air quality for the `--aqi` flag.
+/// * `Alerts` - Active weather alerts.
+/// * `Hourly` - Hourly or 3-hourly forecast, the other providers have the daily one.
+/// * `DateRange` - Daily summary for `date=<start>..<end>`.
+/// * `Coordinates` - The provider accepts the coordinates only, the address is geocoded by Nominatim first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Capability {
+    /// Temperature.
+    Temperature,
+    /// Temperature accounting for the human perception of weather.
+    FeelsLike,
+    /// UV index.
+    Uv,
+    /// Snow amount or probability.
+    Snow,
+    /// Air quality for the `--aqi` flag.
+    AirQuality,
+    /// Active weather alerts.
+    Alerts,
+    /// Hourly or 3-hourly forecast.
+    Hourly,
+    /// Daily summary for `date=<start>..<end>`.
+    DateRange,
+    /// The provider accepts the coordinates only, the address is geocoded by Nominatim first.
+    Coordinates,
+}
+
+impl Capability {
+    /// All capabilities in the order of the columns.
+    pub const ALL: [Capability; 9] = [
+        Capability::Temperature,
+        Capability::FeelsLike,
+        Capability::Uv,
+        Capability::Snow,
+        Capability::AirQuality,
+        Capability::Alerts,
+        Capability::Hourly,
+        Capability::DateRange,
+        Capability::Coordinates,
+    ];
+
+    /// Column header, e.g. `feels-like`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Capability::Temperature => "temp",
+            Capability::FeelsLike => "feels-like",
+            Capability::Uv => "uv",
+            Capability::Snow => "snow",
+            Capability::AirQuality => "aqi",
+            Capability::Alerts => "alerts",
+            Capability::Hourly => "hourly",
+            Capability::DateRange => "date-range",
+            Capability::Coordinates => "coordinates-only",
+        }
+    }
+
+    /// Description of the column for the legend.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Capability::Temperature => "Temperature",
+            Capability::FeelsLike => "Temperature accounting for the human perception of weather",
+            Capability::Uv => "UV index",
+            Capability::Snow => "Snow amount or probability",
+            Capability::AirQuality => "Air quality for the --aqi flag",
+            Capability::Alerts => "Active weather alerts",
+            Capability::Hourly => "Hourly or 3-hourly forecast, otherwise daily",
+            Capability::DateRange => "Daily summary for date=<start>..<end>",
+            Capability::Coordinates => {
+                "Accepts the coordinates only, the address is geocoded by Nominatim first"
+            }
+        }
+    }
+}
+
+/// Normalized weather report, the only view of the forecast for all providers and output formats.
+///
+/// All values are metric: temperature in °C, wind speed in km/h, pressure in hPa.
+/// The values after `alerts` are supplied by some providers only, json omits the missing ones.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeatherReport {
+    /// Provider name.
+    pub provider: &'static str,
+    /// Request address.
+    pub address: String,
+    /// Geo position.
+    pub geo: Geo,
+    /// Forecast date on the server.
+    pub date: DateTime<Local>,
+    /// Description of the weather condition.
+    pub condition: Option<String>,
+    /// Temperature, °C.
+    pub temperature: Option<f32>,
+    /// Temperature accounting for the human perception of weather, °C.
+    pub feels_like: Option<f32>,
+    /// Humidity, %.
+    pub humidity: Option<u32>,
+    /// Atmospheric pressure, hPa.
+    pub pressure: Option<f32>,
+    /// Wind speed, km/h.
+    pub wind_speed: Option<f32>,
+    /// Wind direction in degrees.
+    pub wind_deg: Option<u16>,
+    /// Wind direction (meteorological), the abbreviation in json, e.g. `SSE`.
+    pub wind_dir: WindDeg,
+    /// Precipitation amount, mm.
+    pub precipitation: Option<f32>,
+    /// Active weather alerts, e.g. a thunderstorm warning.
+    pub alerts: Vec<String>,
+    /// Human-readable description of the day. Daily forecasts only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// The lowest temperature of the day, °C. Daily forecasts only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature_min: Option<f32>,
+    /// The highest temperature of the day, °C. Daily forecasts only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature_max: Option<f32>,
+    /// Wind chill temperature of the server, °C.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wind_chill: Option<f32>,
+    /// Heat index of the server, °C.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heat_index: Option<f32>,
+    /// Dew point, °C.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dew_point: Option<f32>,
+    /// Wind gust, km/h.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wind_gust: Option<f32>,
+    /// Chance of precipitation, %.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precipitation_chance: Option<u32>,
+    /// Snow amount or depth, cm.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snow: Option<f32>,
+    /// Cloud cover, %.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloud_cover: Option<u32>,
+    /// Visibility, km.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<f32>,
+    /// UV index.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uv_index: Option<f32>,
+    /// Sunrise time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sunrise: Option<DateTime<Local>>,
+    /// Sunset time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sunset: Option<DateTime<Local>>,
+    /// Moon phase name of the server, otherwise it's calculated for the date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moon_phase: Option<String>,
+    /// Air quality for the `--aqi` flag.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub air: Option<AirQuality>,
+    /// UTC offset of the location, the system timezone is used when it's unknown.
+    #[serde(skip)]
+    pub offset: Option<FixedOffset>,
+    /// Temperature of the items of the requested day for the `--graph` flag.
+    #[serde(skip)]
+    pub curve: Vec<(DateTime<Local>, f32)>,
+    /// Number of the items averaged for the `--average` flag, 0 - the closest item only.
+    #[serde(skip)]
+    pub average: usize,
+    /// Trend of the temperature, pressure and humidity versus the adjacent items of the forecast.
+    #[serde(skip)]
+    pub trend: Trend,
+}
+
+impl WeatherReport {
+    /// Create the report without the weather values.
+    ///
+    /// * `provider: &'static str` - Provider name.
+    /// * `address: String` - Request address.
+    /// * `geo: Geo` - Geo position.
+    /// * `date: DateTime<Local>` - Forecast date on the server.
+    /// * `offset: Option<FixedOffset>` - UTC offset of the location.
+    pub fn new(
+        provider: &'static str,
+        address: String,
+        geo: Geo,
+        date: DateTime<Local>,
+        offset: Option<FixedOffset>,
+    ) -> WeatherReport {
+        WeatherReport {
+            provider,
+            address,
+            geo,
+            date,
+            condition: None,
+            temperature: None,
+            feels_like: None,
+            humidity: None,
+            pressure: None,
+            wind_speed: None,
+            wind_deg: None,
+            wind_dir: WindDeg::None,
+            precipitation: None,
+            alerts: Vec::new(),
+            summary: None,
+            temperature_min: None,
+            temperature_max: None,
+            wind_chill: None,
+            heat_index: None,
+            dew_point: None,
+            wind_gust: None,
+            precipitation_chance: None,
+            snow: None,
+            cloud_cover: None,
+            visibility: None,
+            uv_index: None,
+            sunrise: None,
+            sunset: None,
+            moon_phase: None,
+            air: None,
+            offset,
+            curve: Vec::new(),
+            average: 0,
+            trend: Trend::default(),
+        }
+    }
+
+    /// Rows of the text view: the common values, `None` if they are missing,
+    /// and the values the provider supplied.
+    ///
+    /// The wind chill and the heat index are calculated when the server doesn't return them,
+    /// except for the daily forecasts. The day length and the moon phase follow the sunrise and sunset.
+    #[rustfmt::skip]
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        let none = || "None".to_owned();
+        let percent = |value: u32| format!("{} %", value);
+        let time = |dt: Option<DateTime<Local>>| dt.map_or_else(none, |dt| Format::time(&dt, self.offset));
+        let daily = self.temperature_min.is_some() || self.temperature_max.is_some();
+        let comfort = |value: Option<f32>, calculated: Option<Option<f32>>| match (value, daily) {
+            (Some(value), _) => Some(Format::temp(value)),
+            (None, false) => Some(Comfort::text(calculated)),
+            (None, true) => None,
+        };
+        let astro = self.sunrise.is_some() || self.sunset.is_some();
+        let rows = [
+            ("Weather condition", Some(self.condition.clone().unwrap_or_else(none))),
+            ("Summary of the day", self.summary.clone()),
+            ("Temperature", Some(Trend::mark(self.temperature.map_or_else(none, Format::temp), self.trend.temp))),
+            ("Minimum temperature", self.temperature_min.map(Format::temp)),
+            ("Maximum temperature", self.temperature_max.map(Format::temp)),
+            ("Feels like temperature", Some(self.feels_like.map_or_else(none, Format::temp))),
+            ("Windchill temperature", comfort(self.wind_chill, self.temperature.zip(self.wind_speed).map(|(t, s)| Comfort::wind_chill(t, s)))),
+            ("Heat index", comfort(self.heat_index, self.temperature.zip(self.humidity).map(|(t, h)| Comfort::heat_index(t, h as f32)))),
+            ("Dew point", self.dew_point.map(Format::temp)),
+            ("Humidity", Some(Trend::mark(self.humidity.map_or_else(none, percent), self.trend.humidity))),
+            ("Atmospheric pressure", Some(Trend::mark(self.pressure.map_or_else(none, |s| Format::pressure(s, "hPa")), self.trend.pressure))),
+            ("Wind speed", Some(self.wind_speed.map_or_else(none, Format::speed))),
+            ("Wind direction and degrees", Some(format!("{} ({})", self.wind_dir, self.wind_deg.map_or_else(none, |s| s.to_string() + "°")))),
+            ("Wind gust", self.wind_gust.map(Format::speed)),
+            ("Precipitation amount", Some(self.precipitation.map_or_else(none, Format::precip))),
+            ("Chance of precipitation", self.precipitation_chance.map(percent)),
+            ("Snow amount", self.snow.map(|s| format!("{} cm", Format::num(s)))),
+            ("Cloud cover", self.cloud_cover.map(percent)),
+            ("Visibility", self.visibility.map(|s| format!("{} km", Format::num(s)))),
+            ("UV index", self.uv_index.map(Format::num)),
+            ("Sunrise time", astro.then(|| time(self.sunrise))),
+            ("Sunset time", astro.then(|| time(self.sunset))),
+            ("Day length", astro.then(|| Astro::day_length(self.sunrise, self.sunset).unwrap_or_else(none))),
+            ("Moon phase", astro.then(|| self.moon_phase.clone().unwrap_or_else(|| Astro::moon_phase(&self.date).to_owned()))),
+        ];
+        rows.into_iter().filter_map(|(name, value)| Some((name, value?))).collect()
+    }
+
+    /// Display the report.
+    ///
+    /// * `duration: i64` - Request time, ms.
+    /// * `date: &str` - The requested date, e.g. `now`.
+    #[rustfmt::skip]
+    pub fn show(&self, duration: i64, date: &str) {
+        show_units("km/h", "hPa");
+        Out::line(&format!("Weather for '{}'. {} server. Request time {} ms.", date, self.provider, duration));
+        Out::line(&format!("Request address: {}.", self.address));
+        Out::line(&format!("Found address: {} ({},{}).", self.geo.address, self.geo.lat, self.geo.lon));
+        Out::line(&format!("Forecast date on the server: {}", Format::time(&self.date, self.offset)));
+        show_age(&self.date, date);
+        show_average(self.average);
+        show_alerts(&self.alerts);
+        Table::show(None, &self.rows());
+    }
+
+    /// Select the closest report for each day of the range.
+    ///
+    /// * `reports: Vec<WeatherReport>` - All reports returned by the provider.
+    /// * `start: &DateTime<Local>` - The first day, its time is used for each day.
+    /// * `end: &DateTime<Local>` - The last day.
+    ///
+    /// Days outside of the returned reports are skipped.
+    pub fn daily(
+        reports: Vec<WeatherReport>,
+        start: &DateTime<Local>,
+        end: &DateTime<Local>,
+    ) -> Vec<WeatherReport> {
+        let (first, last) = match (
+            reports.iter().map(|r| r.date.date_naive()).min(),
+            reports.iter().map(|r| r.date.date_naive()).max(),
+        ) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Vec::new(),
+        };
+        let mut day = *start;
+        // Skip days before the forecast
+        if day.date_naive() < first {
+            day += Duration::days((first - day.date_naive()).num_days());
+        }
+        let mut days = Vec::new();
+        while day.date_naive() <= end.date_naive() && day.date_naive() <= last {
+            let closest = reports
+                .iter()
+                .min_by_key(|r| r.date.signed_duration_since(day).num_seconds().abs());
+            if let Some(report) = closest {
+                days.push(report.clone());
+            }
+            day += Duration::days(1);
+        }
+        days
+    }
+
+    /// Columns of `--format=csv`, the same for all providers.
+    pub const CSV_COLUMNS: [&'static str; 15] = [
+        "provider",
+        "address",
+        "lat",
+        "lon",
+        "date",
+        "condition",
+        "temperature",
+        "feels_like",
+        "humidity",
+        "pressure",
+        "wind_speed",
+        "wind_deg",
+        "wind_dir",
+        "precipitation",
+        "alerts",
+    ];
+
+    /// Values of the `CSV_COLUMNS`, metric as in json, the missing values are empty.
+    ///
+    /// The alerts are joined with `; `.
+    pub fn csv_row(&self) -> Vec<String> {
+        fn opt<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map(T::to_string).unwrap_or_default()
+        }
+        let wind_dir = match self.wind_dir {
+            WindDeg::None => String::new(),
+            dir => dir.abbrev().to_owned(),
+        };
+        vec![
+            self.provider.to_owned(),
+            self.address.clone(),
+            self.geo.lat.clone(),
+            self.geo.lon.clone(),
+            self.date.to_rfc3339(),
+            opt(&self.condition),
+            opt(&self.temperature),
+            opt(&self.feels_like),
+            opt(&self.humidity),
+            opt(&self.pressure),
+            opt(&self.wind_speed),
+            opt(&self.wind_deg),
+            wind_dir,
+            opt(&self.precipitation),
+            self.alerts.join("; "),
+        ]
+    }
+}
+
+/// The reason why the weather report can't be received.
+///
+/// * `ProviderNotFound(String)` - Weather provider not found.
+/// * `NoForecast(&'static str)` - The provider did not return a weather forecast.
+/// * `DateOutOfRange { provider, date }` - The provider can't answer for the date.
+/// * `DateNotSupported { provider, days, date }` - The date is beyond the forecast of the provider.
+/// * `NoAddress(&'static str)` - Neither the address nor the home address is given.
+/// * `FlagNotSupported { provider, flag, reason }` - The flag can't be used with the request.
+/// * `AstroNotSupported(&'static str)` - The provider doesn't supply the sun times for `--astro`.
+#[derive(Debug, PartialEq)]
+pub enum WeatherError {
+    /// Weather provider not found.
+    ProviderNotFound(String),
+    /// The provider did not return a weather forecast.
+    NoForecast(&'static str),
+    /// The provider can't answer for the date.
+    /// * `provider` - Provider name.
+    /// * `date` - The requested date.
+    DateOutOfRange {
+        provider: &'static str,
+        date: String,
+    },
+    /// The date is beyond the forecast of the provider.
+    /// * `provider` - Provider name.
+    /// * `days` - The last day after today the provider has the forecast for.
+    /// * `date` - The requested date.
+    DateNotSupported {
+        provider: &'static str,
+        days: i64,
+        date: String,
+    },
+    /// Neither the address nor the home address is given.
+    NoAddress(&'static str),
+    /// The flag can't be used with the request, e.g. `--oneline` with a date.
+    /// * `provider` - Provider name.
+    /// * `flag` - The flag, e.g. `--oneline`.
+    /// * `reason` - What the flag requires, e.g. `can only be used with date=now`.
+    FlagNotSupported {
+        provider: &'static str,
+        flag: &'static str,
+        reason: &'static str,
+    },
+    /// The provider doesn't supply the sun times for `--astro`.
+    AstroNotSupported(&'static str),
+}
+
+impl fmt::Display for WeatherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeatherError::ProviderNotFound(provider) => {
+                write!(f, "Weather provider {} not found.", provider)
+            }
+            WeatherError::NoForecast(provider) => write!(
+                f,
+                "It is not possible to determine the weather forecast sent by the {} provider",
+                provider
+            ),
+            WeatherError::DateOutOfRange { provider, date } => write!(
+                f,
+                "The date {} is too far from today, the {} provider can't answer for it",
+                date, provider
+            ),
+            WeatherError::DateNotSupported {
+                provider,
+                days,
+                date,
+            } => write!(
+                f,
+                "The {} provider only supports the forecast from today to {} days ahead, the date {} is out of it",
+                provider, days, date
+            ),
+            WeatherError::NoAddress(_) => write!(
+                f,
+                "The address is empty. Set the home address to omit it, e.g. weather configure home \"Kyiv, Ukraine\", or detect the location by the IP address with --geoip"
+            ),
+            WeatherError::FlagNotSupported { flag, reason, .. } => {
+                write!(f, "The {} flag {}.", flag, reason)
+            }
+            WeatherError::AstroNotSupported(provider) => {
+                write!(f, "The sunrise and sunset are not supplied by {}.", provider)
+            }
+        }
+    }
+}
+
+impl Error for WeatherError {}
+
+impl WeatherError {
+    /// Machine readable kind of the error, e.g. `not_found`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            WeatherError::ProviderNotFound(_) => "provider_not_found",
+            WeatherError::NoForecast(_) => "not_found",
+            WeatherError::DateOutOfRange { .. } => "date_out_of_range",
+            WeatherError::DateNotSupported { .. } => "date_not_supported",
+            WeatherError::NoAddress(_) => "no_address",
+            WeatherError::FlagNotSupported { .. } => "flag_not_supported",
+            WeatherError::AstroNotSupported(_) => "astro_not_supported",
+        }
+    }
+
+    /// Name of the provider the error belongs to.
+    pub fn provider(&self) -> &str {
+        match self {
+            WeatherError::ProviderNotFound(provider) => provider,
+            WeatherError::NoForecast(provider) => provider,
+            WeatherError::DateOutOfRange { provider, .. } => provider,
+            WeatherError::DateNotSupported { provider, .. } => provider,
+            WeatherError::NoAddress(provider) => provider,
+            WeatherError::FlagNotSupported { provider, .. } => provider,
+            WeatherError::AstroNotSupported(provider) => provider,
+        }
+    }
+
+    /// Error object for the json formats, e.g.
+    /// `{"error":{"kind":"not_found","message":"...","provider":"OpenWeather"}}`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "error": {
+                "kind": self.kind(),
+                "message": self.to_string(),
+                "provider": self.provider(),
+            }
+        })
+    }
 }
 
 /// Work struct with list of providers and default provider.
 ///
 /// * `providers: Vec<Box<dyn Provider>>` - List of weather providers.
 /// * `default: usize` - Default provider.
+/// * `settings: Settings` - Settings saved to the config file.
+/// * `dirty: bool` - The credentials, the default provider or the settings were changed.
 pub struct Work {
     /// List of weather providers.
     providers: Vec<Box<dyn Provider>>,
     /// Default provider.
     default: usize,
+    /// Settings saved to the config file.
+    settings: Settings,
+    /// The credentials, the default provider or the settings were changed and aren't saved yet,
+    /// so a read-only command doesn't write the key file.
+    dirty: bool,
 }
 
 impl Work {
@@ -46,17 +694,104 @@ impl Work {
             Box::new(crate::provider::weatherapi::WeatherAPI::new()),
             Box::new(crate::provider::accuweather::AccuWeather::new()),
             Box::new(crate::provider::aerisweather::AerisWeather::new()),
+            Box::new(crate::provider::visualcrossing::VisualCrossing::new()),
+            Box::new(crate::provider::metno::MetNorway::new()),
+            Box::new(crate::provider::tomorrowio::TomorrowIO::new()),
+            Box::new(crate::provider::onecall::OpenWeatherOneCall::new()),
         ];
 
         let mut work = Work {
             providers,
             default: 0,
+            settings: Settings::default(),
+            dirty: false,
         };
         work.load();
+        // The damaged key file is rewritten
         work.save();
+        // Flags take precedence over the saved settings
+        let mut options = Options::get();
+        options.apply(&work.settings);
+        Options::set(options);
+        for provider in work.providers.iter_mut() {
+            provider.key_from_env();
+        }
         work
     }
 
+    /// Runs the command that needs the providers.
+    ///
+    /// * `command: Command` - The recognized command, `Help`, `Version` and `Repl` are ignored.
+    ///
+    /// Return
+    ///
+    /// `bool` - False if the weather request failed with an error.
+    pub fn run(&mut self, command: Command) -> bool {
+        match command {
+            Command::List => self.list(),
+            Command::ListJson => self.list_json(),
+            Command::Config => self.config(),
+            Command::Capabilities => self.capabilities(),
+            Command::Doctor => return self.doctor(),
+            Command::Configure { provider } => self.configure(provider),
+            Command::ShowCredentials { provider } => self.show_credentials(provider),
+            Command::ResetCredentials { provider } => self.reset_credentials(provider),
+            Command::SetDefault { provider } => self.set_default(provider),
+            Command::Setting { name, value } => self.set_setting(name, value),
+            Command::Get {
+                provider,
+                address,
+                dates,
+            } => return self.get(provider, address, dates),
+            Command::Help { .. } | Command::Version | Command::Repl => {}
+        }
+        true
+    }
+
+    /// Reads the commands from stdin and runs them until `exit` or the end of input.
+    ///
+    /// Each line is parsed like the launch parameters, e.g. `--units=imperial get Kyiv date=tomorrow`,
+    /// the flags are applied to this line only. The providers are created once for the whole session.
+    pub fn repl(&mut self) {
+        println!("Type a command, e.g. \"get Kyiv\", \"list\" or \"exit\" to quit.");
+        let mut line = String::new();
+        loop {
+            print!("weather> ");
+            if let Err(e) = stdout().flush() {
+                Log::error(&e.to_string());
+                return;
+            }
+            line.clear();
+            match stdin().read_line(&mut line) {
+                Ok(0) => return,
+                Ok(_) => {}
+                Err(e) => {
+                    Log::error(&e.to_string());
+                    return;
+                }
+            }
+            let list: Vec<String> = line.split_whitespace().map(|s| s.to_owned()).collect();
+            match list.first().map(|s| s.as_str()) {
+                None => continue,
+                Some("exit" | "quit") => return,
+                Some(_) => {}
+            }
+            let init = Init::parse(&list);
+            // Flags take precedence over the saved settings
+            let mut options = init.options.clone();
+            options.apply(&self.settings);
+            Options::set(options);
+            match init.command {
+                Command::Help { error } => Help::show(error, &init.args),
+                Command::Version => Help::version(),
+                Command::Repl => Log::error("The repl is already running."),
+                command => {
+                    self.run(command);
+                }
+            }
+        }
+    }
+
     /// Displays a list of available providers and allows to set the default.
     pub fn list(&mut self) {
         // Display header
@@ -116,6 +851,7 @@ impl Work {
             );
             return;
         }
+        self.dirty |= self.default != num - 1;
         self.default = num - 1;
 
         // Display footer
@@ -127,47 +863,933 @@ impl Work {
         self.save();
     }
 
+    /// Prints the providers as a json array, e.g. for scripts.
+    pub fn list_json(&self) {
+        println!("{}", self.providers_json());
+    }
+
+    /// The providers with the menu index, the default flag and whether the credentials are set.
+    ///
+    /// Return
+    ///
+    /// `Value` - Array of `{index, name, is_default, configured}` objects.
+    fn providers_json(&self) -> Value {
+        Value::Array(
+            self.providers
+                .iter()
+                .enumerate()
+                .map(|(index, provider)| {
+                    json!({
+                        "index": index + 1,
+                        "name": provider.name(),
+                        "is_default": index == self.default,
+                        "configured": provider.has_credentials(),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// Prints the effective configuration.
+    pub fn config(&self) {
+        for line in self.config_lines(&Options::get()) {
+            println!("{}", line);
+        }
+    }
+
+    /// Lines of the configuration: the default provider, then each setting with its effective value
+    /// and its source: `saved`, `flag` or `default`.
+    ///
+    /// * `options: &Options` - Flags with the applied settings.
+    fn config_lines(&self, options: &Options) -> Vec<String> {
+        let defaults = Options::default();
+        let mut lines = vec![
+            "Configuration of key.txt, the flags override the saved values:".to_owned(),
+            format!(
+                "  {:<12}  {}",
+                Settings::PROVIDER,
+                self.providers[self.default].name()
+            ),
+        ];
+        for name in Settings::NAMES {
+            let saved = self.settings.get(name);
+            let line = match (options.value(name), saved) {
+                (Some(value), Some(saved)) if value == saved => format!("{} (saved)", value),
+                (Some(value), _) if options.value(name) != defaults.value(name) => {
+                    format!("{} (flag)", value)
+                }
+                (Some(value), _) => format!("{} (default)", value),
+                // The settings without a flag
+                (None, Some(saved)) => saved,
+                (None, None) => "None".to_owned(),
+            };
+            lines.push(format!("  {:<12}  {}", name, line));
+        }
+        lines
+    }
+
+    /// Displays the table of the fields each provider supplies.
+    pub fn capabilities(&self) {
+        for line in Work::capability_lines(&self.providers) {
+            println!("{}", line);
+        }
+    }
+
+    /// Checks the geocoder and each configured provider with a request for the current weather
+    /// of a fixed place, then prints the status and latency of each service.
+    ///
+    /// Return
+    ///
+    /// `bool` - False if any of the checks failed, the providers without credentials are skipped.
+    pub fn doctor(&self) -> bool {
+        let options = Options::get();
+        let mut checks = Vec::with_capacity(self.providers.len() + 1);
+        let url = format!("{}?q=Kyiv&format=json&limit=1", options.geocoder_url());
+        let start = Instant::now();
+        let status = match Http::fetch_json(
+            &url,
+            time::Duration::from_secs(options.geo_timeout()),
+            &[("User-Agent", options.user_agent())],
+        ) {
+            Ok(_) => "OK",
+            Err(e) => {
+                e.log(None);
+                e.kind()
+            }
+        };
+        checks.push(("Nominatim", status, Some(start.elapsed().as_millis())));
+        // The coordinates are known, the providers are checked without the geocoder
+        let geo = Geo {
+            lat: DOCTOR_PLACE.0.to_owned(),
+            lon: DOCTOR_PLACE.1.to_owned(),
+            address: "Kyiv".to_owned(),
+            importance: None,
+        };
+        for provider in &self.providers {
+            let start = Instant::now();
+            let status = match provider.check(&geo) {
+                Some(Ok(())) => "OK",
+                Some(Err(e)) => {
+                    e.log(Some(provider.name()));
+                    e.kind()
+                }
+                None => {
+                    checks.push((provider.name(), "not configured", None));
+                    continue;
+                }
+            };
+            checks.push((provider.name(), status, Some(start.elapsed().as_millis())));
+        }
+        for line in Work::doctor_lines(&checks) {
+            println!("{}", line);
+        }
+        checks
+            .iter()
+            .all(|(_, status, latency)| *status == "OK" || latency.is_none())
+    }
+
+    /// Lines of the `weather doctor` table: the service, pass/fail, the status and the latency.
+    ///
+    /// * `checks: &[(&str, &str, Option<u128>)]` - Service name, status and latency in ms, `None` - the check is skipped.
+    fn doctor_lines(checks: &[(&str, &str, Option<u128>)]) -> Vec<String> {
+        let width = checks
+            .iter()
+            .map(|(name, _, _)| name.chars().count())
+            .max()
+            .unwrap_or_default()
+            .max("Service".len());
+        let mut lines = vec![format!(
+            "{:width$}  Result  {:14}  Latency",
+            "Service",
+            "Status",
+            width = width
+        )];
+        for (name, status, latency) in checks {
+            let result = match (status, latency) {
+                (_, None) => "skip",
+                (&"OK", _) => "pass",
+                _ => "fail",
+            };
+            let line = format!(
+                "{:width$}  {:6}  {:14}  {}",
+                name,
+                result,
+                status,
+                latency.map_or(String::new(), |ms| format!("{} ms", ms)),
+                width = width
+            );
+            lines.push(line.trim_end().to_owned());
+        }
+        lines
+    }
+
+    /// Lines of the capability table: a row per provider, a column per capability, then the legend.
+    ///
+    /// * `providers: &[Box<dyn Provider>]` - List of weather providers.
+    fn capability_lines(providers: &[Box<dyn Provider>]) -> Vec<String> {
+        let width = providers
+            .iter()
+            .map(|vec| vec.name().chars().count())
+            .max()
+            .unwrap_or_default();
+        let mut header = format!("{:width$}", "Provider", width = width);
+        for capability in Capability::ALL {
+            header += &format!("  {}", capability.name());
+        }
+        let mut lines = vec![header];
+        for provider in providers {
+            let mut line = format!("{:width$}", provider.name(), width = width);
+            for capability in Capability::ALL {
+                let mark = match provider.capabilities().contains(&capability) {
+                    true => "✓",
+                    false => "-",
+                };
+                line += &format!("  {:^width$}", mark, width = capability.name().len());
+            }
+            lines.push(line.trim_end().to_owned());
+        }
+        lines.push(String::new());
+        for capability in Capability::ALL {
+            lines.push(format!(
+                "  {} - {}",
+                capability.name(),
+                capability.description()
+            ));
+        }
+        lines
+    }
+
+    /// Sets the default provider without a prompt.
+    ///
+    /// * `provider: String` - The selected provider.
+    pub fn set_default(&mut self, provider: String) {
+        match self.position(&provider) {
+            Some(index) => {
+                self.dirty |= self.default != index;
+                self.default = index;
+                println!(
+                    "The '{}' provider was successfully installed by default.",
+                    self.providers[index].name()
+                );
+                self.save();
+            }
+            None => self.not_found(&provider),
+        }
+    }
+
+    /// Index of the provider by the name, the case and the surrounding spaces are ignored,
+    /// e.g. `accuweather` is `AccuWeather`.
+    ///
+    /// * `name: &str` - Provider name.
+    fn position(&self, name: &str) -> Option<usize> {
+        let name = name.trim();
+        self.providers
+            .iter()
+            .position(|vec| vec.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Report the unknown provider with the closest name, e.g. `AccuWether` - `AccuWeather`.
+    ///
+    /// * `name: &str` - Provider name.
+    fn not_found(&self, name: &str) {
+        Work::fail(&WeatherError::ProviderNotFound(name.to_owned()));
+        if let Some(suggestion) = self.suggest(name) {
+            Log::info(&format!("Did you mean {}?", suggestion));
+        }
+    }
+
+    /// The provider name closest to the unknown one: a name starting with it
+    /// or the name with the smallest edit distance, up to 3 edits.
+    ///
+    /// * `name: &str` - Provider name.
+    fn suggest(&self, name: &str) -> Option<&'static str> {
+        let name = name.trim().to_lowercase();
+        if name.is_empty() {
+            return None;
+        }
+        let names = self.providers.iter().map(|vec| vec.name());
+        if let Some(found) = names
+            .clone()
+            .find(|found| found.to_lowercase().starts_with(&name))
+        {
+            return Some(found);
+        }
+        names
+            .map(|found| (Work::distance(&name, &found.to_lowercase()), found))
+            .filter(|(distance, _)| *distance <= 3)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, found)| found)
+    }
+
+    /// Levenshtein distance: the number of the inserted, deleted or replaced characters.
+    fn distance(a: &str, b: &str) -> usize {
+        let b: Vec<char> = b.chars().collect();
+        let mut row: Vec<usize> = (0..=b.len()).collect();
+        for (i, ca) in a.chars().enumerate() {
+            let mut prev = row[0];
+            row[0] = i + 1;
+            for (j, cb) in b.iter().enumerate() {
+                let cur = row[j + 1];
+                row[j + 1] = match ca == *cb {
+                    true => prev,
+                    false => 1 + prev.min(row[j]).min(cur),
+                };
+                prev = cur;
+            }
+        }
+        row[b.len()]
+    }
+
+    /// Saves the setting to the config file.
+    ///
+    /// * `name: String` - Setting name.
+    /// * `value: String` - Setting value, empty value removes the setting.
+    pub fn set_setting(&mut self, name: String, value: String) {
+        // The default provider is stored apart from the settings
+        if name == Settings::PROVIDER {
+            return self.set_default(value);
+        }
+        let value = match name.as_str() {
+            "order" => match self.order_names(&value) {
+                Some(names) => names,
+                None => return,
+            },
+            _ => value,
+        };
+        match self.settings.set(&name, &value) {
+            Ok(()) => {
+                println!("The setting '{}' was saved successfully.", name);
+                self.dirty = true;
+                self.save();
+            }
+            Err(e) => Log::error(&e),
+        }
+    }
+
     /// Configures credentials for the selected provider
     pub fn configure(&mut self, provider: String) {
-        let mut res = None;
-        for vec in self.providers.iter_mut() {
-            if vec.name() == provider {
-                res = Some(vec);
-                break;
+        match self.position(&provider) {
+            Some(index) => {
+                let saved = self.providers[index].serialize();
+                self.providers[index].configure();
+                self.dirty |= self.providers[index].serialize() != saved;
             }
+            None => self.not_found(&provider),
         }
-        match res {
-            Some(provider) => provider.configure(),
-            None => println!("Weather provider {} not found.", provider),
+        self.save();
+    }
+
+    /// Removes the saved credentials of the selected provider without a prompt.
+    ///
+    /// * `provider: String` - The selected provider.
+    pub fn reset_credentials(&mut self, provider: String) {
+        let index = match self.position(&provider) {
+            Some(index) => index,
+            None => {
+                self.not_found(&provider);
+                return;
+            }
+        };
+        let provider = &mut self.providers[index];
+        if provider.credentials().is_empty() {
+            println!("{} does not require credentials.", provider.name());
+            return;
+        }
+        let saved = provider.serialize();
+        provider.reset();
+        self.dirty |= provider.serialize() != saved;
+        println!("The credentials of {} were removed.", provider.name());
+        if provider.has_credentials() {
+            println!("The credentials of the environment variables are still used.");
         }
         self.save();
     }
 
+    /// Prints the stored credentials of the selected provider without a prompt.
+    ///
+    /// * `provider: String` - The selected provider.
+    pub fn show_credentials(&self, provider: String) {
+        let provider = match self.position(&provider) {
+            Some(index) => &self.providers[index],
+            None => {
+                self.not_found(&provider);
+                return;
+            }
+        };
+        let rows = provider.credentials();
+        if rows.is_empty() {
+            println!("{} does not require credentials.", provider.name());
+            return;
+        }
+        println!("Credentials for {}:", provider.name());
+        for (name, value) in rows {
+            println!("  {}: {}", name, value);
+        }
+    }
+
     /// Displays weather for the provided address.
     ///
     /// * `provider: Option<String>` - Using the default provider.
+    /// * `address: String` - The provided address, empty - the saved home address.
+    /// * `dates: Vec<Date>` - Displays weather for each of the specified dates.
+    ///
+    /// Return
+    ///
+    /// `bool` - False if an error was reported for any of the addresses.
+    pub fn get(&self, provider: Option<String>, address: String, dates: Vec<Date>) -> bool {
+        let options = Options::get();
+        // The saved order replaces the default provider, its providers are tried in turn
+        let order: Vec<&dyn Provider> = self
+            .ordered()
+            .into_iter()
+            .filter(|provider| provider.has_credentials())
+            .collect();
+        let providers = match provider {
+            Some(provider) => match self.position(&provider) {
+                Some(index) => self.candidates(self.providers[index].as_ref(), options.fallback),
+                None => {
+                    self.not_found(&provider);
+                    return false;
+                }
+            },
+            None if !order.is_empty() && !options.fallback => order,
+            None if !order.is_empty() => self.candidates(order[0], true),
+            None => self.candidates(self.providers[self.default].as_ref(), options.fallback),
+        };
+        let provider = providers[0];
+        let address = match (address.is_empty(), &self.settings.home) {
+            (false, _) => address,
+            (true, Some(home)) => {
+                Log::info(&format!("The saved home address is used: {}.", home));
+                home.clone()
+            }
+            (true, None) => match options.geoip.then(GeoIp::find).flatten() {
+                Some(geo) => {
+                    Log::info(&format!(
+                        "The location detected by the IP address is used: {} ({},{}).",
+                        geo.address, geo.lat, geo.lon
+                    ));
+                    format!("@{},{}", geo.lat, geo.lon)
+                }
+                None => {
+                    Work::fail(&WeatherError::NoAddress(provider.name()));
+                    return false;
+                }
+            },
+        };
+        let secs = match options.watch {
+            None => return Work::get_once(&providers, &address, &dates),
+            Some(_) if address == "-" => {
+                Log::error("The --watch flag can't be used with the addresses from stdin.");
+                return false;
+            }
+            Some(secs) => secs,
+        };
+        // The screen is cleared only for the text forecast on the terminal
+        let clear =
+            options.format() == Output::Text && options.output.is_none() && stdout().is_terminal();
+        // Ctrl-C stops the watch after the current update, e.g. back to the `repl` prompt
+        Interrupt::catch();
+        let mut ok;
+        loop {
+            if clear {
+                print!("\x1B[2J\x1B[H");
+            }
+            ok = Work::get_once(&providers, &address, &dates);
+            if Interrupt::is_set() {
+                break;
+            }
+            Log::info(&format!(
+                "Updated at {}. The next update in {} s, press Ctrl-C to stop.",
+                Local::now().format("%H:%M:%S"),
+                secs
+            ));
+            if !Interrupt::sleep(time::Duration::from_secs(secs)) {
+                break;
+            }
+        }
+        Interrupt::release();
+        Log::info("The watch is stopped.");
+        ok
+    }
+
+    /// Displays weather once with the providers, to stdout or to the `--output` file.
+    ///
+    /// * `providers: &[&dyn Provider]` - Providers in the order of the requests.
+    /// * `address: &str` - The provided address, `-` - the addresses from stdin.
+    /// * `dates: &[Date]` - Displays weather for each of the specified dates.
+    ///
+    /// Return
+    ///
+    /// `bool` - False if an error was reported for any of the addresses.
+    fn get_once(providers: &[&dyn Provider], address: &str, dates: &[Date]) -> bool {
+        let options = Options::get();
+        if let Some(path) = &options.output {
+            Out::capture();
+            // The rewritten file gets its own header, the appended one only if it's new
+            if options.format() == Output::Csv {
+                let header = options.append
+                    && fs::metadata(path)
+                        .map(|meta| meta.len() > 0)
+                        .unwrap_or(false);
+                CSV_HEADER.store(header, Ordering::Relaxed);
+            }
+        }
+        // Each run requests the fresh forecast, the dates within it share the answers
+        Budget::remember();
+        let ok = if address == "-" {
+            Work::get_batch(providers, dates)
+        } else {
+            Work::get_dates(providers, address, dates)
+        };
+        Budget::forget();
+        if let (Some(path), Some(text)) = (&options.output, Out::take()) {
+            // Nothing is written if the forecast is not received, the log doesn't get empty entries
+            if !text.is_empty() {
+                // The csv file stays a table without the time lines
+                let stamp = options.append && options.format() != Output::Csv;
+                Out::save(path, options.append, stamp, &text);
+            }
+        }
+        ok
+    }
+
+    /// Providers to request: the selected one, then with `--fallback` the other providers
+    /// with the credentials in the saved order, then in the order of the list.
+    ///
+    /// * `provider: &'a dyn Provider` - The selected provider.
+    /// * `fallback: bool` - Add the other providers.
+    fn candidates<'a>(
+        &'a self,
+        provider: &'a dyn Provider,
+        fallback: bool,
+    ) -> Vec<&'a dyn Provider> {
+        let mut providers = vec![provider];
+        if fallback {
+            let list = self.providers.iter().map(|vec| vec.as_ref());
+            for next in self.ordered().into_iter().chain(list) {
+                if next.has_credentials() && providers.iter().all(|vec| vec.name() != next.name()) {
+                    providers.push(next);
+                }
+            }
+        }
+        providers
+    }
+
+    /// Providers of the saved `order` setting, the unknown names are skipped.
+    fn ordered(&self) -> Vec<&dyn Provider> {
+        self.settings
+            .order
+            .iter()
+            .flatten()
+            .filter_map(|name| self.position(name))
+            .map(|index| self.providers[index].as_ref())
+            .collect()
+    }
+
+    /// Provider names of the `order` setting as they are listed, e.g. `accuweather` is `AccuWeather`.
+    ///
+    /// * `value: &str` - The names separated by commas or spaces.
+    ///
+    /// Return
+    ///
+    /// `Option<String>` - The names separated by commas, `None` if a name is unknown or repeated.
+    fn order_names(&self, value: &str) -> Option<String> {
+        let mut names: Vec<&str> = Vec::new();
+        for name in value
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|name| !name.is_empty())
+        {
+            let name = match self.position(name) {
+                Some(index) => self.providers[index].name(),
+                None => {
+                    self.not_found(name);
+                    return None;
+                }
+            };
+            if names.contains(&name) {
+                Log::error(&format!("The provider {} is repeated in the order.", name));
+                return None;
+            }
+            names.push(name);
+        }
+        Some(names.join(","))
+    }
+
+    /// Displays weather for each address read from stdin, one address per line.
+    ///
+    /// An address that fails is reported and the batch continues with the next line.
+    ///
+    /// Return
+    ///
+    /// `bool` - False if an error was reported for any of the addresses.
+    fn get_batch(providers: &[&dyn Provider], dates: &[Date]) -> bool {
+        let mut first = true;
+        let mut ok = true;
+        for line in stdin().lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    Log::error(&format!(
+                        "Unable to read the address from stdin. Error: {}",
+                        e
+                    ));
+                    return false;
+                }
+            };
+            let address = line.trim();
+            if address.is_empty() {
+                continue;
+            }
+            if !first {
+                Work::separate();
+            }
+            first = false;
+            ok &= Work::get_dates(providers, address, dates);
+        }
+        ok
+    }
+
+    /// Displays weather for each of the dates, the place is geocoded once for all of them
+    /// and the forecast received for one date serves the others.
+    ///
+    /// * `providers: &[&dyn Provider]` - Providers in the order of the requests.
+    /// * `address: &str` - The provided address.
+    /// * `dates: &[Date]` - Displays weather for each of the specified dates.
+    ///
+    /// Return
+    ///
+    /// `bool` - False if an error was reported for any of the dates.
+    fn get_dates(providers: &[&dyn Provider], address: &str, dates: &[Date]) -> bool {
+        let mut ok = true;
+        for (index, date) in dates.iter().enumerate() {
+            if index > 0 {
+                Work::separate();
+            }
+            ok &= Work::get_any(providers, address.to_owned(), date.clone());
+        }
+        ok
+    }
+
+    /// Separates the blocks of the text output with an empty line.
+    fn separate() {
+        let options = Options::get();
+        if !options.oneline && options.format() == Output::Text {
+            Out::line("");
+        }
+    }
+
+    /// Displays weather using the first provider that answers.
+    ///
+    /// The error of the last provider is reported if none of them answers.
+    ///
+    /// * `providers: &[&dyn Provider]` - Providers in the order of the requests.
     /// * `address: String` - The provided address.
     /// * `date: Date` - Displays weather for the specified date.
-    pub fn get(&self, provider: Option<String>, address: String, date: Date) {
-        match provider {
-            Some(provider) => {
-                let mut res = None;
-                for vec in &self.providers {
-                    if vec.name() == provider {
-                        res = Some(vec);
-                        break;
+    ///
+    /// Return
+    ///
+    /// `bool` - False if an error was reported.
+    fn get_any(providers: &[&dyn Provider], address: String, date: Date) -> bool {
+        for (index, provider) in providers.iter().enumerate() {
+            let error = match Work::get_one(*provider, address.clone(), date.clone()) {
+                Ok(()) => {
+                    if index > 0 {
+                        Log::info(&format!(
+                            "The forecast was received from the {} provider.",
+                            provider.name()
+                        ));
                     }
+                    return true;
                 }
-                match res {
-                    Some(provider) => provider.get_weather(address, date),
-                    None => println!("Weather provider {} not found.", provider),
+                Err(error) => error,
+            };
+            match providers.get(index + 1) {
+                Some(next) => Log::info(&format!(
+                    "The {} provider failed, trying {}.",
+                    provider.name(),
+                    next.name()
+                )),
+                None => Work::fail(&error),
+            }
+        }
+        false
+    }
+
+    /// Displays weather for the provided address in the selected output format.
+    fn get_one(provider: &dyn Provider, address: String, date: Date) -> Result<(), WeatherError> {
+        Work::check_date(provider, &date)?;
+        let options = Options::get();
+        if options.oneline {
+            return Work::get_oneline(provider, address, date);
+        }
+        if options.astro {
+            return Work::get_astro(provider, address, date);
+        }
+        if options.format() != Output::Text {
+            return Work::get_json(provider, address, date);
+        }
+        let received = match date {
+            Date::Range(start, end) => Work::get_range(provider, address, &start, &end),
+            date => {
+                let received = provider.get_weather(address, date);
+                if received && options.aqi && !provider.supports_aqi() {
+                    Out::line("Air quality: not supported by this provider.");
                 }
+                if received && options.graph && !provider.supports_graph() {
+                    Out::line("Temperature curve of the day: not supported by this provider.");
+                }
+                received
+            }
+        };
+        if received || options.dry_run {
+            Ok(())
+        } else {
+            Err(WeatherError::NoForecast(provider.name()))
+        }
+    }
+
+    /// Reports the error: an error object on stdout in the json formats, so the output stays
+    /// parsable, otherwise an error message on stderr.
+    fn fail(error: &WeatherError) {
+        let json = match (Options::get().format(), error) {
+            // The text output reports the missing forecast where it's detected
+            (Output::Text, WeatherError::NoForecast(_)) => return,
+            // The csv table has no place for the errors
+            (Output::Text | Output::Csv, _) => {
+                Log::error(&error.to_string());
+                return;
             }
+            (Output::Json, _) => serde_json::to_string_pretty(&error.to_json()),
+            (Output::JsonLines, _) => serde_json::to_string(&error.to_json()),
+        };
+        match json {
+            Ok(json) => Out::line(&json),
+            Err(e) => Log::error(&format!("Unable to create json. Error: {}", e)),
+        }
+    }
+
+    /// Reports the missing forecast like `provider::no_forecast`.
+    fn no_forecast(text: &str) {
+        if !Options::get().dry_run {
+            Log::error(text);
+        }
+    }
+
+    /// Displays the sun and moon times for `--astro` instead of the forecast.
+    ///
+    /// * `provider: &dyn Provider` - Weather provider.
+    /// * `address: String` - The provided address.
+    /// * `date: Date` - Date of the sun and moon times.
+    fn get_astro(provider: &dyn Provider, address: String, date: Date) -> Result<(), WeatherError> {
+        let unsupported = |reason| {
+            Err(WeatherError::FlagNotSupported {
+                provider: provider.name(),
+                flag: "--astro",
+                reason,
+            })
+        };
+        if let Date::Range(..) = date {
+            return unsupported("can't be used with a range of dates");
+        }
+        let options = Options::get();
+        if options.format() == Output::Csv {
+            return unsupported("can't be used with --format=csv");
+        }
+        if !provider.supports_astro() {
+            return Err(WeatherError::AstroNotSupported(provider.name()));
+        }
+        let start = Local::now();
+        let report = match provider.fetch_astro(address, date) {
+            Some(report) => report,
+            None if options.dry_run => return Ok(()),
+            None => return Err(WeatherError::NoForecast(provider.name())),
+        };
+        let json = match options.format() {
+            Output::Text | Output::Csv => {
+                report.show((Local::now() - start).num_milliseconds());
+                return Ok(());
+            }
+            Output::JsonLines => serde_json::to_string(&report),
+            Output::Json => serde_json::to_string_pretty(&report),
+        };
+        match json {
+            Ok(json) => Out::line(&json),
+            Err(e) => Log::error(&format!("Unable to create json. Error: {}", e)),
+        }
+        Ok(())
+    }
+
+    /// Displays a one line summary of the current weather.
+    fn get_oneline(
+        provider: &dyn Provider,
+        address: String,
+        date: Date,
+    ) -> Result<(), WeatherError> {
+        if date != Date::Now {
+            return Err(WeatherError::FlagNotSupported {
+                provider: provider.name(),
+                flag: "--oneline",
+                reason: "can only be used with date=now",
+            });
+        }
+        match provider.fetch(address, date) {
+            Some(report) => Out::line(&Format::oneline(&report)),
+            None if Options::get().dry_run => {}
             None => {
-                let provider = &self.providers[self.default];
-                provider.get_weather(address, date);
+                let error = WeatherError::NoForecast(provider.name());
+                Work::no_forecast(&error.to_string());
+                return Err(error);
             }
         }
+        Ok(())
+    }
+
+    /// Displays normalized weather reports in json.
+    ///
+    /// `--json-lines` prints each report as a compact object on its own line,
+    /// the pretty printed `--format=json` prints an array for the range of days.
+    fn get_json(provider: &dyn Provider, address: String, date: Date) -> Result<(), WeatherError> {
+        let options = Options::get();
+        let reports = match date {
+            Date::Range(start, end) => provider
+                .fetch_range(address, &start, &end)
+                .filter(|reports| !reports.is_empty())
+                .map(|reports| (reports, true)),
+            date => provider
+                .fetch(address, date)
+                .map(|report| (vec![report], false)),
+        };
+        let (reports, range) = match reports {
+            Some(reports) => reports,
+            None if options.dry_run => return Ok(()),
+            None => return Err(WeatherError::NoForecast(provider.name())),
+        };
+        let json = match options.format() {
+            Output::Csv => {
+                Work::csv_rows(&reports);
+                return Ok(());
+            }
+            Output::JsonLines => reports
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<Result<Vec<_>, _>>()
+                .map(|lines| lines.join("\n")),
+            _ if range => serde_json::to_string_pretty(&reports),
+            _ => serde_json::to_string_pretty(&reports[0]),
+        };
+        match json {
+            Ok(json) => Out::line(&json),
+            Err(e) => Log::error(&format!("Unable to create json. Error: {}", e)),
+        }
+        Ok(())
+    }
+
+    /// Displays the reports as csv rows, the header row is printed before the first row only,
+    /// so the batch and watch modes continue the same table.
+    fn csv_rows(reports: &[WeatherReport]) {
+        if !CSV_HEADER.swap(true, Ordering::Relaxed) {
+            Out::line(&WeatherReport::CSV_COLUMNS.join(","));
+        }
+        for report in reports {
+            Out::line(&Format::csv(&report.csv_row()));
+        }
+    }
+
+    /// Displays a daily summary table for the range of days, false if the forecast is not received.
+    fn get_range(
+        provider: &dyn Provider,
+        address: String,
+        start: &DateTime<Local>,
+        end: &DateTime<Local>,
+    ) -> bool {
+        let begin = Local::now();
+        let reports = match provider.fetch_range(address, start, end) {
+            Some(reports) if !reports.is_empty() => reports,
+            _ => {
+                Work::no_forecast(
+                    "It is not possible to determine the weather forecast for the specified days",
+                );
+                return false;
+            }
+        };
+        let duration = Local::now() - begin;
+        Work::show_range(
+            &reports,
+            duration.num_milliseconds(),
+            &Date::Range(*start, *end).to_string(),
+        );
+        true
+    }
+
+    /// Display daily summary table
+    #[rustfmt::skip]
+    fn show_range(reports: &[WeatherReport], duration: i64, date: &str) {
+        let first = &reports[0];
+        Out::line(&format!("Weather for '{}'. {} server. Request time {} ms.", date, first.provider, duration));
+        Out::line(&format!("Request address: {}.", first.address));
+        Out::line(&format!("Found address: {} ({},{}).", first.geo.address, first.geo.lat, first.geo.lon));
+        Out::line(&"-".repeat(40));
+        Out::line(&format!("{:<16} | {:>11} | {:>11} | {:>8} | {:>15} | Condition", "Date", "Temperature", "Feels like", "Humidity", "Wind"));
+        for report in reports {
+            Out::line(&format!("{:<16} | {:>11} | {:>11} | {:>8} | {:>15} | {}",
+                report.date.format("%Y-%m-%d %H:%M").to_string(),
+                report.temperature.map_or("None".to_owned(), Format::temp),
+                report.feels_like.map_or("None".to_owned(), Format::temp),
+                report.humidity.map_or("None".to_owned(), |s| s.to_string() + " %"),
+                report.wind_speed.map_or("None".to_owned(), Format::speed) + &match report.wind_dir {
+                    WindDeg::None => String::new(),
+                    dir => format!(" {} {}", dir.arrow(), dir.abbrev()),
+                },
+                report.condition.as_ref().map_or("None", |s| s.as_str()),
+            ));
+        }
+    }
+
+    /// Get a normalized weather report for the provided address.
+    ///
+    /// * `provider: Option<&str>` - Weather provider, `None` - using the default provider.
+    /// * `address: String` - The provided address.
+    /// * `date: Date` - Forecast date.
+    pub fn fetch(
+        &self,
+        provider: Option<&str>,
+        address: String,
+        date: Date,
+    ) -> Result<WeatherReport, WeatherError> {
+        let provider = match provider {
+            Some(name) => self
+                .position(name)
+                .map(|index| &self.providers[index])
+                .ok_or_else(|| WeatherError::ProviderNotFound(name.to_owned()))?,
+            None => &self.providers[self.default],
+        };
+        Work::check_date(provider.as_ref(), &date)?;
+        provider
+            .fetch(address, date)
+            .ok_or(WeatherError::NoForecast(provider.name()))
+    }
+
+    /// Checking whether the date is within the window and the forecast of the provider.
+    ///
+    /// * `provider: &dyn Provider` - Weather provider.
+    /// * `date: &Date` - The requested date.
+    fn check_date(provider: &dyn Provider, date: &Date) -> Result<(), WeatherError> {
+        let (past, future) = provider.date_window();
+        if !date.in_window(&Local::now(), past, future) {
+            return Err(WeatherError::DateOutOfRange {
+                provider: provider.name(),
+                date: date.to_string(),
+            });
+        }
+        match (provider.supports_date(date), provider.forecast_days()) {
+            (false, Some(days)) => Err(WeatherError::DateNotSupported {
+                provider: provider.name(),
+                days,
+                date: date.to_string(),
+            }),
+            _ => Ok(()),
+        }
     }
 
     /// Load credentials from text file
@@ -177,7 +1799,7 @@ impl Work {
             Err(e) => {
                 match e.kind() {
                     ErrorKind::NotFound => {}
-                    _ => println!("Could not open the key file. Error: {}.", e),
+                    _ => Log::error(&format!("Could not open the key file. Error: {}.", e)),
                 }
                 return;
             }
@@ -186,7 +1808,7 @@ impl Work {
         let vec = match buf_reader.lines().collect::<std::io::Result<Vec<String>>>() {
             Ok(vec) => vec,
             Err(e) => {
-                println!("Could not read the key file. Error: {}.", e);
+                Log::error(&format!("Could not read the key file. Error: {}.", e));
                 return;
             }
         };
@@ -195,34 +1817,51 @@ impl Work {
         }
         let default = &vec[0];
         for keys in &vec[1..] {
+            if self.settings.parse_line(keys) {
+                continue;
+            }
+            let mut known = false;
             for (index, vec) in self.providers.iter_mut().enumerate() {
-                if vec.deserialize(keys) && default == vec.name() {
-                    self.default = index;
+                if vec.deserialize(keys) {
+                    known = true;
+                    if default == vec.name() {
+                        self.default = index;
+                    }
                     break;
                 }
             }
+            // The unknown line is dropped by rewriting the file
+            self.dirty |= !known;
         }
     }
 
-    /// Save credentials to text file
-    fn save(&self) {
+    /// Save credentials to text file if they were changed
+    fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
         let mut data = Vec::with_capacity(self.providers.len() + 1);
         data.push(self.providers[self.default].name().to_owned());
         for provider in &self.providers {
             data.push(provider.serialize());
         }
+        data.extend(self.settings.lines());
         let mut file = match File::create("key.txt") {
             Ok(file) => file,
             Err(e) => {
-                println!(
+                Log::error(&format!(
                     "An error occurred while writing the keys to the file. Error: {}.",
                     e
-                );
+                ));
                 return;
             }
         };
-        if let Err(e) = file.write_all(data.join("\n").as_bytes()) {
-            println!("An error occurred while writing these keys. Error: {}.", e);
+        match file.write_all(data.join("\n").as_bytes()) {
+            Ok(()) => self.dirty = false,
+            Err(e) => Log::error(&format!(
+                "An error occurred while writing these keys. Error: {}.",
+                e
+            )),
         }
     }
 }
@@ -232,3 +1871,374 @@ impl Default for Work {
         Work::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone};
+    use serde_json::json;
+
+    use super::{Capability, Provider, WeatherError, WeatherReport, Work};
+    use crate::{
+        format::Format,
+        geo::Geo,
+        init::{Date, Options, Settings},
+        provider::{
+            accuweather::AccuWeather, metno::MetNorway, onecall::OpenWeatherOneCall,
+            openweather::OpenWeather, visualcrossing::VisualCrossing,
+        },
+        wind::WindDeg,
+    };
+
+    #[test]
+    fn test_check_date() {
+        let old = Date::Set(date("1990-01-01 12:00"));
+        assert_eq!(
+            Work::check_date(&MetNorway::new(), &old),
+            Err(WeatherError::DateOutOfRange {
+                provider: "MetNorway",
+                date: old.to_string()
+            })
+        );
+        assert_eq!(Work::check_date(&VisualCrossing::new(), &old), Ok(()));
+        assert_eq!(Work::check_date(&MetNorway::new(), &Date::Now), Ok(()));
+        let later = Date::Set(Local::now() + Duration::days(30));
+        assert_eq!(
+            Work::check_date(&MetNorway::new(), &later),
+            Err(WeatherError::DateNotSupported {
+                provider: "MetNorway",
+                days: 9,
+                date: later.to_string()
+            })
+        );
+        assert_eq!(Work::check_date(&VisualCrossing::new(), &later), Ok(()));
+    }
+
+    #[test]
+    fn test_candidates() {
+        let work = Work {
+            providers: vec![
+                Box::new(OpenWeather::new()),
+                Box::new(MetNorway::new()),
+                Box::new(VisualCrossing::new()),
+            ],
+            default: 0,
+            settings: Settings::default(),
+            dirty: false,
+        };
+        let names = |fallback| {
+            work.candidates(work.providers[2].as_ref(), fallback)
+                .iter()
+                .map(|provider| provider.name())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(false), vec!["VisualCrossing"]);
+        // OpenWeather has no key
+        assert_eq!(names(true), vec!["VisualCrossing", "MetNorway"]);
+    }
+
+    #[test]
+    fn test_order() {
+        let mut visual = VisualCrossing::new();
+        assert!(visual.deserialize("VisualCrossing:key"));
+        let mut work = Work {
+            providers: vec![
+                Box::new(OpenWeather::new()),
+                Box::new(MetNorway::new()),
+                Box::new(visual),
+                Box::new(AccuWeather::new()),
+            ],
+            default: 0,
+            settings: Settings::default(),
+            dirty: false,
+        };
+        assert_eq!(
+            work.order_names("visualcrossing, openweather metnorway")
+                .as_deref(),
+            Some("VisualCrossing,OpenWeather,MetNorway")
+        );
+        assert_eq!(work.order_names("MetNorway,Unknown"), None);
+        assert_eq!(work.order_names("MetNorway,metnorway"), None);
+        work.settings.order = Some(vec!["VisualCrossing".to_owned(), "MetNorway".to_owned()]);
+        let names = |providers: Vec<&dyn Provider>| {
+            providers
+                .iter()
+                .map(|provider| provider.name())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(work.ordered()), vec!["VisualCrossing", "MetNorway"]);
+        // The saved order goes before the order of the list
+        let provider = work.providers[3].as_ref();
+        assert_eq!(
+            names(work.candidates(provider, true)),
+            vec!["AccuWeather", "VisualCrossing", "MetNorway"]
+        );
+    }
+
+    #[test]
+    fn test_providers_json() {
+        let work = Work {
+            providers: vec![Box::new(OpenWeather::new()), Box::new(MetNorway::new())],
+            default: 1,
+            settings: Settings::default(),
+            dirty: false,
+        };
+        assert_eq!(
+            work.providers_json(),
+            json!([
+                {"index": 1, "name": "OpenWeather", "is_default": false, "configured": false},
+                {"index": 2, "name": "MetNorway", "is_default": true, "configured": true},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_config_lines() {
+        let mut work = Work {
+            providers: vec![Box::new(OpenWeather::new()), Box::new(MetNorway::new())],
+            default: 1,
+            settings: Settings::default(),
+            dirty: false,
+        };
+        work.settings.set("units", "imperial").unwrap();
+        work.settings.set("precision", "2").unwrap();
+        work.settings.set("home", "Kyiv, Ukraine").unwrap();
+        let mut options = Options {
+            precision: Some(0),
+            api_timeout: Some(10),
+            ..Options::default()
+        };
+        options.apply(&work.settings);
+        let lines = work.config_lines(&options);
+        assert_eq!(lines[1], "  provider      MetNorway");
+        assert_eq!(lines[2], "  units         imperial (saved)");
+        assert_eq!(lines[3], "  format        text (default)");
+        assert_eq!(lines[4], "  precision     0 (flag)");
+        assert_eq!(lines[7], "  home          Kyiv, Ukraine");
+        assert_eq!(lines[8], "  geo-timeout   3 (default)");
+        assert_eq!(lines[9], "  api-timeout   10 (flag)");
+    }
+
+    #[test]
+    fn test_capability_lines() {
+        let providers: Vec<Box<dyn Provider>> =
+            vec![Box::new(OpenWeather::new()), Box::new(MetNorway::new())];
+        let lines = Work::capability_lines(&providers);
+        assert_eq!(
+            lines[0],
+            "Provider     temp  feels-like  uv  snow  aqi  alerts  hourly  date-range  coordinates-only"
+        );
+        assert_eq!(
+            lines[2],
+            "MetNorway     ✓        -       -    -     -     -       ✓         ✓              ✓"
+        );
+        assert_eq!(lines.len(), 3 + 1 + Capability::ALL.len());
+    }
+
+    #[test]
+    fn test_doctor_lines() {
+        let lines = Work::doctor_lines(&[
+            ("Nominatim", "OK", Some(320)),
+            ("OpenWeather", "auth-failed", Some(95)),
+            ("WeatherAPI", "not configured", None),
+        ]);
+        assert_eq!(
+            lines,
+            vec![
+                "Service      Result  Status          Latency",
+                "Nominatim    pass    OK              320 ms",
+                "OpenWeather  fail    auth-failed     95 ms",
+                "WeatherAPI   skip    not configured",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_position() {
+        let work = Work {
+            providers: vec![
+                Box::new(OpenWeather::new()),
+                Box::new(AccuWeather::new()),
+                Box::new(OpenWeatherOneCall::new()),
+            ],
+            default: 0,
+            settings: Settings::default(),
+            dirty: false,
+        };
+        assert_eq!(work.position("accuweather"), Some(1));
+        assert_eq!(work.position(" AccuWeather "), Some(1));
+        assert_eq!(work.position("Accu"), None);
+        assert_eq!(work.suggest("Accu"), Some("AccuWeather"));
+        assert_eq!(work.suggest("AccuWether"), Some("AccuWeather"));
+        assert_eq!(work.suggest("openweathr"), Some("OpenWeather"));
+        assert_eq!(work.suggest("OpenWeatherOne"), Some("OpenWeatherOneCall"));
+        assert_eq!(work.suggest("Sky"), None);
+        assert_eq!(Work::distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_error_json() {
+        assert_eq!(
+            WeatherError::NoForecast("OpenWeather")
+                .to_json()
+                .to_string(),
+            r#"{"error":{"kind":"not_found","message":"It is not possible to determine the weather forecast sent by the OpenWeather provider","provider":"OpenWeather"}}"#
+        );
+        let error = WeatherError::ProviderNotFound("Sky".to_owned());
+        assert_eq!(error.kind(), "provider_not_found");
+        assert_eq!(error.provider(), "Sky");
+        assert_eq!(WeatherError::NoAddress("MetNorway").kind(), "no_address");
+        let error = WeatherError::FlagNotSupported {
+            provider: "MetNorway",
+            flag: "--oneline",
+            reason: "can only be used with date=now",
+        };
+        assert_eq!(error.kind(), "flag_not_supported");
+        assert_eq!(
+            WeatherError::AstroNotSupported("MetNorway").to_string(),
+            "The sunrise and sunset are not supplied by MetNorway."
+        );
+        assert_eq!(
+            error.to_string(),
+            "The --oneline flag can only be used with date=now."
+        );
+    }
+
+    fn date(dt: &str) -> DateTime<Local> {
+        Local
+            .from_local_datetime(&NaiveDateTime::parse_from_str(dt, "%Y-%m-%d %H:%M").unwrap())
+            .single()
+            .unwrap()
+    }
+
+    fn report(dt: &str) -> WeatherReport {
+        let geo = Geo {
+            lat: "50.45".to_owned(),
+            lon: "30.52".to_owned(),
+            address: "Kyiv".to_owned(),
+            importance: None,
+        };
+        WeatherReport::new("Test", "Kyiv".to_owned(), geo, date(dt), None)
+    }
+
+    #[test]
+    fn test_daily() {
+        let reports = vec![
+            report("2023-05-02 09:00"),
+            report("2023-05-02 15:00"),
+            report("2023-05-03 09:00"),
+            report("2023-05-03 15:00"),
+            report("2023-05-04 09:00"),
+        ];
+        let days = WeatherReport::daily(
+            reports.clone(),
+            &date("2023-05-01 14:00"),
+            &date("2023-05-10 14:00"),
+        );
+        let days: Vec<DateTime<Local>> = days.into_iter().map(|r| r.date).collect();
+        assert_eq!(
+            days,
+            vec![
+                date("2023-05-02 15:00"),
+                date("2023-05-03 15:00"),
+                date("2023-05-04 09:00")
+            ]
+        );
+        let days = WeatherReport::daily(
+            reports.clone(),
+            &date("2023-05-03 08:00"),
+            &date("2023-05-03 08:00"),
+        );
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].date, date("2023-05-03 09:00"));
+        assert!(WeatherReport::daily(
+            reports,
+            &date("2023-05-05 08:00"),
+            &date("2023-05-06 08:00")
+        )
+        .is_empty());
+        assert!(WeatherReport::daily(
+            Vec::new(),
+            &date("2023-05-01 08:00"),
+            &date("2023-05-06 08:00")
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_csv_row() {
+        let mut full = report("2023-05-02 09:00");
+        full.condition = Some("Rain, heavy".to_owned());
+        full.temperature = Some(18.5);
+        full.humidity = Some(80);
+        full.wind_deg = Some(158);
+        full.wind_dir = WindDeg::from_degrees(158);
+        full.alerts = vec!["Storm".to_owned(), "Flood".to_owned()];
+        for row in [report("2023-05-02 09:00").csv_row(), full.csv_row()] {
+            assert_eq!(row.len(), WeatherReport::CSV_COLUMNS.len());
+        }
+        let row = full.csv_row();
+        assert_eq!(row[..4], ["Test", "Kyiv", "50.45", "30.52"]);
+        assert_eq!(row[4], date("2023-05-02 09:00").to_rfc3339());
+        assert_eq!(
+            row[5..],
+            [
+                "Rain, heavy",
+                "18.5",
+                "",
+                "80",
+                "",
+                "",
+                "158",
+                "SSE",
+                "",
+                "Storm; Flood"
+            ]
+        );
+        assert!(Format::csv(&row).contains(",\"Rain, heavy\",18.5,"));
+    }
+
+    #[test]
+    fn test_rows() {
+        let names = |report: &WeatherReport| {
+            report
+                .rows()
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<&str>>()
+        };
+        // The common values are shown even if they are missing, the comfort values are calculated
+        let mut current = report("2023-05-02 09:00");
+        assert_eq!(
+            names(&current),
+            [
+                "Weather condition",
+                "Temperature",
+                "Feels like temperature",
+                "Windchill temperature",
+                "Heat index",
+                "Humidity",
+                "Atmospheric pressure",
+                "Wind speed",
+                "Wind direction and degrees",
+                "Precipitation amount",
+            ]
+        );
+        assert_eq!(current.rows()[0].1, "None");
+        current.uv_index = Some(3.0);
+        current.sunset = Some(date("2023-05-02 20:30"));
+        current.moon_phase = Some("Full Moon".to_owned());
+        let rows = current.rows();
+        assert!(rows.contains(&("UV index", Format::num(3.0))));
+        assert!(rows.contains(&("Sunrise time", "None".to_owned())));
+        assert!(rows.contains(&("Moon phase", "Full Moon".to_owned())));
+
+        // The daily forecast has no comfort values without the server ones
+        let mut day = report("2023-05-02 12:00");
+        day.temperature_min = Some(10.0);
+        day.temperature_max = Some(20.0);
+        let names = names(&day);
+        assert!(names.contains(&"Minimum temperature"));
+        assert!(!names.contains(&"Windchill temperature"));
+        assert!(!names.contains(&"Heat index"));
+    }
+}